@@ -3,7 +3,7 @@ use reqwest::header::USER_AGENT;
 use serde::Deserialize;
 
 use crate::VERSION;
-use crate::errors::Error;
+use crate::errors::{Error, ErrorKind};
 
 // CRATES.IO URLS
 const VERSIONS_URL: &str = "/v1/crates/tod/versions";
@@ -50,9 +50,14 @@ pub async fn get_latest_version(mock_url: Option<String>) -> Result<String, Erro
         let cr: CargoResponse = serde_json::from_str(&response.text().await?)?;
         Ok(cr.versions.first().unwrap().num.clone())
     } else {
+        let kind = ErrorKind::from_status(response.status().as_u16());
         let message = format!("Error: {:#?}", response.text().await);
         let source = "get_latest_version response failure".to_string();
-        Err(Error { message, source })
+        Err(Error {
+            message,
+            source,
+            kind,
+        })
     }
 }
 #[cfg(test)]
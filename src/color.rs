@@ -1,5 +1,20 @@
 use colored::*;
 
+use crate::config::Config;
+use crate::tasks::priority::Priority;
+
+/// Turns off colors for every color helper when the config or NO_COLOR asks for plain output
+pub fn apply_config(config: &Config) {
+    if !config.color_enabled() {
+        disable();
+    }
+}
+
+/// Turns off colors for the rest of the run, i.e. when writing to a file
+pub fn disable() {
+    colored::control::set_override(false);
+}
+
 pub fn green_string(str: &str) -> String {
     if cfg!(test) {
         return normal_string(str);
@@ -56,6 +71,107 @@ pub fn debug_string(str: &str) -> String {
     String::from(str).bright_blue().on_yellow().to_string()
 }
 
+pub fn dim_string(str: &str) -> String {
+    if cfg!(test) {
+        return normal_string(str);
+    }
+
+    String::from(str).dimmed().to_string()
+}
+
 pub fn normal_string(str: &str) -> String {
     String::from(str).normal().to_string()
 }
+
+/// Colors text the way tasks of that priority are shown: P1 red, P2 orange, P3 blue and P4 uncolored
+pub fn priority_string(priority: &Priority, text: &str) -> String {
+    if cfg!(test) {
+        return normal_string(text);
+    }
+
+    priority_colored(priority, text).to_string()
+}
+
+fn priority_colored(priority: &Priority, text: &str) -> ColoredString {
+    match priority {
+        Priority::High => text.red(),
+        Priority::Medium => text.truecolor(255, 165, 0),
+        Priority::Low => text.blue(),
+        Priority::None => text.normal(),
+    }
+}
+
+/// Shows a change of priority i.e. "NONE (P4) → HIGH (P1)".
+/// Red when urgency is raised, green when it is lowered.
+pub fn priority_transition_string(from: &Priority, to: &Priority) -> String {
+    if cfg!(test) {
+        return normal_string(&priority_transition(from, to));
+    }
+
+    priority_transition(from, to).to_string()
+}
+
+fn priority_transition(from: &Priority, to: &Priority) -> ColoredString {
+    let text = format!("{} → {}", from.name(), to.name());
+    match to.to_integer().cmp(&from.to_integer()) {
+        std::cmp::Ordering::Greater => text.red(),
+        std::cmp::Ordering::Less => text.green(),
+        std::cmp::Ordering::Equal => text.normal(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_no_color_removes_escape_sequences() {
+        let mut config = test::fixtures::config().await;
+        config.no_color = true;
+        assert!(!config.color_enabled());
+
+        apply_config(&config);
+        let output = priority_transition(&Priority::None, &Priority::High).to_string();
+        assert_eq!(output, "NONE (P4) → HIGH (P1)");
+        assert!(!"text".green().to_string().contains('\u{1b}'));
+    }
+
+    #[test]
+    fn test_priority_colored() {
+        let color = |priority| priority_colored(&priority, "text").fgcolor;
+        assert_eq!(color(Priority::High), Some(Color::Red));
+        assert_eq!(
+            color(Priority::Medium),
+            Some(Color::TrueColor {
+                r: 255,
+                g: 165,
+                b: 0
+            })
+        );
+        assert_eq!(color(Priority::Low), Some(Color::Blue));
+        assert_eq!(color(Priority::None), None);
+        assert_eq!(priority_string(&Priority::High, "text"), "text");
+    }
+
+    #[test]
+    fn test_priority_transition() {
+        assert_eq!(
+            priority_transition(&Priority::None, &Priority::High),
+            "NONE (P4) → HIGH (P1)".red()
+        );
+        assert_eq!(
+            priority_transition(&Priority::Medium, &Priority::Low),
+            "MEDIUM (P2) → LOW (P3)".green()
+        );
+        assert_eq!(
+            priority_transition(&Priority::Low, &Priority::Low),
+            "LOW (P3) → LOW (P3)".normal()
+        );
+        assert_eq!(
+            priority_transition_string(&Priority::None, &Priority::High),
+            "NONE (P4) → HIGH (P1)"
+        );
+    }
+}
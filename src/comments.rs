@@ -87,6 +87,19 @@ pub struct ImageAttachment {
     pub tn_l: Option<serde_json::Value>,
 }
 
+impl Attachment {
+    /// Where the attached file or page can be opened
+    pub fn url(&self) -> &str {
+        match self {
+            Attachment::File(FileAttachment { file_url, .. }) => file_url,
+            Attachment::Url(UrlAttachment { url, .. })
+            | Attachment::ShortUrl(ShortUrlAttachment { url, .. })
+            | Attachment::Video(VideoAttachment { url, .. })
+            | Attachment::Image(ImageAttachment { url, .. }) => url,
+        }
+    }
+}
+
 impl Comment {
     pub fn fmt(&self, config: &Config) -> Result<String, Error> {
         let timezone = config.get_timezone()?;
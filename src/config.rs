@@ -2,17 +2,21 @@ use crate::cargo::Version;
 use crate::errors::Error;
 use crate::id::Resource;
 use crate::input::page_size;
+use crate::lists::UndoEntry;
 use crate::projects::{LegacyProject, Project};
-use crate::tasks::Task;
+use crate::tasks::{SortOrder, Task};
 use crate::time::{SystemTimeProvider, TimeProviderEnum};
 use crate::{VERSION, cargo, color, debug, input, oauth, time, todoist};
 use rand::distr::{Alphanumeric, SampleString};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, BufRead, Write};
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use terminal_size::{Height, Width, terminal_size};
 use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -22,6 +26,13 @@ use tokio::sync::mpsc::UnboundedSender;
 use crate::test_time::FixedTimeProvider;
 
 const MAX_COMMENT_LENGTH: u32 = 500;
+const DEFAULT_COMMENT_FETCH_CONCURRENCY: usize = 8;
+const DEFAULT_MAX_RETRIES: u8 = 3;
+/// Retries beyond this would only add more waits of MAX_BACKOFF
+const MAX_RETRIES: u8 = 10;
+const DEFAULT_RETRY_DELAY_MS: u64 = 100;
+const DEFAULT_DEFER_LABEL: &str = "later";
+const DEFAULT_FILTER_CACHE_TTL: u64 = 0;
 pub const DEFAULT_DEADLINE_VALUE: u8 = 30;
 pub const DEFAULT_DEADLINE_DAYS: u8 = 5;
 pub const OAUTH: &str = "Login with OAuth (recommended)";
@@ -35,6 +46,14 @@ pub struct Completed {
     date: String,
 }
 
+/// Tasks skipped in list process on a given day
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Skipped {
+    date: String,
+    ids: Vec<String>,
+}
+
 /// App configuration, serialized as json in $XDG_CONFIG_HOME/tod.cfg
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(default, deny_unknown_fields)]
@@ -81,6 +100,9 @@ pub struct Config {
     pub spinners: Option<bool>,
     #[serde(default)]
     pub disable_links: bool,
+    /// Print everything without colors, the NO_COLOR environment variable does the same
+    #[serde(default)]
+    pub no_color: bool,
     pub completed: Option<Completed>,
     /// Maximum length for printing comments
     pub max_comment_length: Option<u32>,
@@ -94,6 +116,33 @@ pub struct Config {
     /// Goes straight to natural language input in datetime selection
     pub natural_language_only: Option<bool>,
     pub sort_value: Option<SortValue>,
+    /// Sort order used when a command is not given one, i.e. "priority,datetime"
+    pub default_sort: Option<SortOrder>,
+    /// Delay in milliseconds before the first retry, doubled for each retry after that
+    pub retry_delay_ms: Option<u64>,
+    /// Number of times to retry a GET request after a rate limit, server or connection error
+    pub max_retries: Option<u8>,
+    /// Maximum number of comment requests to run at the same time when processing a list
+    pub comment_fetch_concurrency: Option<usize>,
+    /// Seconds that tasks fetched by filter are reused within one run, off unless set
+    pub filter_cache_ttl: Option<u64>,
+    /// Label added to tasks that are deferred while processing, defaults to "later"
+    pub defer_label: Option<String>,
+    /// What the last complete-all or prioritize changed, so that it can be undone
+    pub last_operation: Option<Vec<UndoEntry>>,
+    /// The id of the logged in Todoist user, saved the first time it is needed
+    pub user_id: Option<String>,
+    /// Durations suggested by timebox for tasks with a label, i.e. "quick" to "15m"
+    #[serde(default)]
+    pub duration_defaults: HashMap<String, String>,
+    /// Whether tasks skipped in list process stay hidden from later runs on the same day
+    #[serde(default)]
+    pub remember_skipped: bool,
+    /// Bulk operations on more tasks than this ask for confirmation first, 0 always asks
+    #[serde(default)]
+    pub confirm_threshold: usize,
+    /// Tasks skipped in list process today, only used when remember_skipped is set
+    pub skipped: Option<Skipped>,
 
     /// For storing arguments from the commandline
     #[serde(skip)]
@@ -118,6 +167,53 @@ pub struct Args {
 #[derive(Default, Clone, Debug)]
 pub struct Internal {
     pub tx: Option<UnboundedSender<Error>>,
+    /// Tasks fetched by filter, shared between clones of the config
+    pub task_cache: TaskCache,
+    /// Tasks created with an idempotency key, so a repeated create in the same run is not sent again
+    pub created_tasks: CreatedTasks,
+}
+
+/// Tasks keyed by the idempotency key they were created with
+#[derive(Default, Clone, Debug)]
+pub struct CreatedTasks(Arc<Mutex<HashMap<String, Task>>>);
+
+impl CreatedTasks {
+    pub fn get(&self, key: &str) -> Option<Task> {
+        let created = self.0.lock().expect("created tasks lock poisoned");
+        created.get(key).cloned()
+    }
+
+    pub fn insert(&self, key: String, task: Task) {
+        let mut created = self.0.lock().expect("created tasks lock poisoned");
+        created.insert(key, task);
+    }
+}
+
+/// In-memory cache of tasks keyed by filter query, entries expire after the filter cache TTL
+#[derive(Default, Clone, Debug)]
+pub struct TaskCache(Arc<Mutex<HashMap<String, CachedTasks>>>);
+
+/// When the tasks were fetched, and the tasks
+type CachedTasks = (Instant, Vec<Task>);
+
+impl TaskCache {
+    /// Returns the cached tasks for the key if they were stored less than ttl ago
+    pub fn get(&self, key: &str, ttl: Duration) -> Option<Vec<Task>> {
+        let cache = self.0.lock().expect("task cache lock poisoned");
+        cache
+            .get(key)
+            .filter(|(stored_at, _)| stored_at.elapsed() < ttl)
+            .map(|(_, tasks)| tasks.clone())
+    }
+
+    pub fn insert(&self, key: String, tasks: Vec<Task>) {
+        let mut cache = self.0.lock().expect("task cache lock poisoned");
+        cache.insert(key, (Instant::now(), tasks));
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().expect("task cache lock poisoned").clear();
+    }
 }
 
 // Determining how
@@ -218,6 +314,15 @@ impl Config {
         Ok(color::green_string("✓"))
     }
 
+    /// Name of a project already in config, without migrating legacy projects
+    pub fn project_name(&self, project_id: &str) -> Option<&str> {
+        self.projects
+            .as_deref()?
+            .iter()
+            .find(|project| project.id == project_id)
+            .map(|project| project.name.as_str())
+    }
+
     /// Converts legacy projects to the new projects if necessary
     pub async fn projects(self: &Config) -> Result<Vec<Project>, Error> {
         let projects = self.projects.clone().unwrap_or_default();
@@ -246,6 +351,42 @@ impl Config {
             Ok(new_projects)
         }
     }
+    /// Returns false when colors are turned off in the config or with a non-empty NO_COLOR environment variable
+    pub fn color_enabled(&self) -> bool {
+        !self.no_color && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+    }
+
+    /// Returns how many comment requests can run at once, never less than one
+    pub fn comment_fetch_concurrency(&self) -> usize {
+        self.comment_fetch_concurrency
+            .unwrap_or(DEFAULT_COMMENT_FETCH_CONCURRENCY)
+            .max(1)
+    }
+
+    /// Returns how long tasks fetched by filter are reused
+    pub fn filter_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.filter_cache_ttl.unwrap_or(DEFAULT_FILTER_CACHE_TTL))
+    }
+
+    /// Returns the label given to deferred tasks
+    pub fn defer_label(&self) -> String {
+        self.defer_label
+            .clone()
+            .unwrap_or_else(|| DEFAULT_DEFER_LABEL.to_string())
+    }
+
+    /// Returns how many times a failed GET request is retried
+    pub fn max_retries(&self) -> u8 {
+        self.max_retries
+            .unwrap_or(DEFAULT_MAX_RETRIES)
+            .min(MAX_RETRIES)
+    }
+
+    /// Returns the delay before the first retry
+    pub fn retry_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.retry_delay_ms.unwrap_or(DEFAULT_RETRY_DELAY_MS))
+    }
+
     // Returns the maximum comment length if configured, otherwise estimates based on terminal window size (if supported)
     pub fn max_comment_length(&self) -> u32 {
         match self.max_comment_length {
@@ -306,10 +447,7 @@ impl Config {
                         color::cyan_string("cargo install tod --force"),
                         color::cyan_string("brew update && brew upgrade tod")
                     );
-                    self.tx().send(Error {
-                        message,
-                        source: "Crates.io".into(),
-                    })?;
+                    self.tx().send(Error::new("Crates.io", &message))?;
                     new_config.clone().save().await?;
                 }
                 Ok(Version::Latest) => (),
@@ -322,10 +460,9 @@ impl Config {
 
     // Get timezone from config, or API if necessary
     pub fn get_timezone(&self) -> Result<String, Error> {
-        self.timezone.clone().ok_or_else(|| Error {
-            message: "Must set timezone".to_string(),
-            source: "get_timezone".to_string(),
-        })
+        self.timezone
+            .clone()
+            .ok_or_else(|| Error::new("get_timezone", "Must set timezone"))
     }
 
     /// Prompt user for timezone if it does not exist and write to disk
@@ -346,6 +483,27 @@ impl Config {
         Ok(config)
     }
 
+    /// The sort order given on the command line, then default_sort, then the command's own default
+    pub fn sort_order(&self, sort: Option<&SortOrder>, fallback: SortOrder) -> SortOrder {
+        sort.or(self.default_sort.as_ref())
+            .cloned()
+            .unwrap_or(fallback)
+    }
+
+    /// The id of the logged in user, fetched from Todoist the first time and saved to disk
+    pub async fn user_id(&self) -> Result<String, Error> {
+        if let Some(user_id) = &self.user_id {
+            return Ok(user_id.clone());
+        }
+
+        let user = todoist::get_user_data(self).await?;
+        let mut config = self.reload().await?;
+        config.user_id = Some(user.id.clone());
+        config.save().await?;
+
+        Ok(user.id)
+    }
+
     pub fn clear_next_task(self) -> Config {
         let next_task: Option<Task> = None;
 
@@ -375,6 +533,25 @@ impl Config {
         })
     }
 
+    /// Ids of the tasks skipped in list process today, empty when they were skipped on another day
+    pub fn skipped_today(&self) -> Result<HashSet<String>, Error> {
+        let today = time::naive_date_today(self)?.to_string();
+        match &self.skipped {
+            Some(Skipped { date, ids }) if *date == today => Ok(ids.iter().cloned().collect()),
+            _ => Ok(HashSet::new()),
+        }
+    }
+
+    pub fn with_skipped(&self, ids: &HashSet<String>) -> Result<Config, Error> {
+        let date = time::naive_date_today(self)?.to_string();
+        let mut ids = ids.iter().cloned().collect::<Vec<String>>();
+        ids.sort();
+        Ok(Config {
+            skipped: Some(Skipped { date, ids }),
+            ..self.clone()
+        })
+    }
+
     pub async fn load(path: &PathBuf) -> Result<Config, Error> {
         let mut json = String::new();
         fs::File::open(path)
@@ -409,6 +586,7 @@ impl Config {
             timezone: None,
             completed: None,
             disable_links: false,
+            no_color: false,
             spinners: Some(true),
             mock_url: None,
             no_sections: None,
@@ -416,10 +594,25 @@ impl Config {
             mock_string: None,
             mock_select: None,
             max_comment_length: None,
+            retry_delay_ms: None,
+            max_retries: None,
+            comment_fetch_concurrency: None,
+            filter_cache_ttl: None,
+            last_operation: None,
+            user_id: None,
+            duration_defaults: HashMap::new(),
+            remember_skipped: false,
+            confirm_threshold: 0,
+            skipped: None,
+            default_sort: None,
+            defer_label: None,
             comment_exclude_regex: None,
             task_exclude_regex: None,
             verbose: None,
-            internal: Internal { tx },
+            internal: Internal {
+                tx,
+                ..Default::default()
+            },
             args: Args {
                 verbose: false,
                 timeout: None,
@@ -433,7 +626,9 @@ impl Config {
         })
     }
 
+    /// Reads the config from disk again, clearing cached tasks so they are fetched fresh
     pub async fn reload(&self) -> Result<Self, Error> {
+        self.internal.task_cache.clear();
         Config::load(&self.path).await.map(|config| Config {
             internal: self.internal.clone(),
             time_provider: self.time_provider.clone(),
@@ -575,6 +770,7 @@ impl Default for Config {
             timezone: None,
             completed: None,
             disable_links: false,
+            no_color: false,
             spinners: Some(true),
             mock_url: None,
             no_sections: None,
@@ -582,8 +778,20 @@ impl Default for Config {
             mock_string: None,
             mock_select: None,
             max_comment_length: None,
+            retry_delay_ms: None,
+            max_retries: None,
+            comment_fetch_concurrency: None,
+            filter_cache_ttl: None,
+            last_operation: None,
+            user_id: None,
+            duration_defaults: HashMap::new(),
+            remember_skipped: false,
+            confirm_threshold: 0,
+            skipped: None,
+            default_sort: None,
+            defer_label: None,
             verbose: None,
-            internal: Internal { tx: None },
+            internal: Internal::default(),
             args: Args {
                 verbose: false,
                 timeout: None,
@@ -627,6 +835,7 @@ pub async fn get_or_create(
         args: Args { timeout, verbose },
         internal: Internal {
             tx: Some(tx.clone()),
+            ..Default::default()
         },
         ..config
     };
@@ -756,7 +965,7 @@ mod tests {
                     verbose: false,
                     timeout: None,
                 },
-                internal: Internal { tx: None },
+                internal: Internal::default(),
                 sort_value: Some(SortValue::default()),
                 projects: Some(vec![]),
                 legacy_projects: Some(vec![]),
@@ -777,8 +986,21 @@ mod tests {
                 mock_select: None,
                 spinners: None,
                 disable_links: false,
+                no_color: false,
                 completed: None,
                 max_comment_length: None,
+                retry_delay_ms: None,
+                max_retries: None,
+                comment_fetch_concurrency: None,
+                filter_cache_ttl: None,
+                last_operation: None,
+                user_id: None,
+                duration_defaults: HashMap::new(),
+                remember_skipped: false,
+                confirm_threshold: 0,
+                skipped: None,
+                default_sort: None,
+                defer_label: None,
                 verbose: None,
                 no_sections: None,
                 natural_language_only: None,
@@ -806,6 +1028,13 @@ mod tests {
             }
         }
 
+        pub fn with_confirm_threshold(self: &Config, confirm_threshold: usize) -> Config {
+            Config {
+                confirm_threshold,
+                ..self.clone()
+            }
+        }
+
         pub fn with_path(self: &Config, path: PathBuf) -> Config {
             Config {
                 path,
@@ -996,7 +1225,10 @@ mod tests {
         assert!(args_debug.contains("timeout"));
 
         let (tx, _rx) = unbounded_channel::<Error>();
-        let internal = Internal { tx: Some(tx) };
+        let internal = Internal {
+            tx: Some(tx),
+            ..Default::default()
+        };
         let internal_debug = format!("{internal:?}");
         assert!(internal_debug.contains("Internal"));
 
@@ -1016,7 +1248,7 @@ mod tests {
         let args_clone = args.clone();
         assert_eq!(args, args_clone);
 
-        let internal = Internal { tx: None };
+        let internal = Internal::default();
         let internal_clone = internal.clone();
         assert_eq!(internal.tx.is_none(), internal_clone.tx.is_none());
 
@@ -1124,6 +1356,48 @@ mod tests {
         assert_eq!(config.max_comment_length(), 1234);
     }
 
+    #[test]
+    fn comment_fetch_concurrency_should_default_to_eight() {
+        let config = Config::default_test();
+        assert_eq!(config.comment_fetch_concurrency(), 8);
+
+        let config = Config {
+            comment_fetch_concurrency: Some(0),
+            ..Config::default_test()
+        };
+        assert_eq!(config.comment_fetch_concurrency(), 1);
+    }
+
+    #[test]
+    fn sort_order_should_fall_back_to_default_sort() {
+        let config = Config::default_test();
+        assert_eq!(
+            config.sort_order(None, SortOrder::Datetime),
+            SortOrder::Datetime
+        );
+
+        let config = Config {
+            default_sort: Some(SortOrder::Composite(vec![
+                SortOrder::Priority,
+                SortOrder::Content,
+            ])),
+            ..Config::default_test()
+        };
+        assert_eq!(
+            config.sort_order(None, SortOrder::Datetime),
+            SortOrder::Composite(vec![SortOrder::Priority, SortOrder::Content])
+        );
+        assert_eq!(
+            config.sort_order(Some(&SortOrder::Deadline), SortOrder::Datetime),
+            SortOrder::Deadline
+        );
+
+        let json = serde_json::to_string(&config.default_sort).unwrap();
+        assert_eq!(json, r#""priority,content""#);
+        let parsed: Option<SortOrder> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, config.default_sort);
+    }
+
     #[test]
     fn max_comment_length_should_fallback_when_not_set() {
         let config = Config {
@@ -5,18 +5,53 @@ use std::{
 
 use crate::color;
 use homedir::GetHomeError;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::{sync::oneshot::error::RecvError, task::JoinError};
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct Error {
     pub message: String,
     pub source: String,
+    /// What went wrong, so callers can tell a retryable failure from a permanent one
+    #[serde(default)]
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ErrorKind {
+    /// The request could not be sent or the server had a problem
+    Network,
+    /// The token was rejected
+    Auth,
+    NotFound,
+    RateLimited,
+    /// A response or value could not be parsed
+    Parse,
+    Io,
+    /// Nothing failed, but the user should know about it, such as results that may be incomplete
+    Warning,
+    #[default]
+    Other,
+}
+
+impl ErrorKind {
+    /// Maps a failed HTTP status code to the kind of error
+    pub fn from_status(status_code: u16) -> ErrorKind {
+        match status_code {
+            401 | 403 => ErrorKind::Auth,
+            404 => ErrorKind::NotFound,
+            429 => ErrorKind::RateLimited,
+            500..=599 => ErrorKind::Network,
+            _ => ErrorKind::Other,
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let Error { source, message } = self;
+        let Error {
+            source, message, ..
+        } = self;
         write!(
             f,
             "Error from {}:\n{}",
@@ -26,11 +61,14 @@ impl Display for Error {
     }
 }
 
+impl std::error::Error for Error {}
+
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
         Self {
             source: "io".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Io,
         }
     }
 }
@@ -40,6 +78,7 @@ impl From<RecvError> for Error {
         Self {
             source: String::from("RecvError"),
             message: "Sender dropped without sending".to_string(),
+            kind: ErrorKind::Other,
         }
     }
 }
@@ -49,6 +88,7 @@ impl From<TryFromIntError> for Error {
         Self {
             source: "TryFromIntError".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Parse,
         }
     }
 }
@@ -58,6 +98,7 @@ impl From<JoinError> for Error {
         Self {
             source: "Join on future".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Other,
         }
     }
 }
@@ -67,6 +108,7 @@ impl From<chrono::LocalResult<chrono::DateTime<chrono_tz::Tz>>> for Error {
         Self {
             source: "chrono".into(),
             message: format!("{value:?}"),
+            kind: ErrorKind::Parse,
         }
     }
 }
@@ -76,6 +118,7 @@ impl From<tokio::sync::mpsc::error::SendError<Error>> for Error {
         Self {
             source: "tokio mpsc".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Other,
         }
     }
 }
@@ -85,6 +128,7 @@ impl From<chrono_tz::ParseError> for Error {
         Self {
             source: "chrono_tz".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Parse,
         }
     }
 }
@@ -94,6 +138,7 @@ impl From<ParseIntError> for Error {
         Self {
             source: "ParseIntError".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Parse,
         }
     }
 }
@@ -103,6 +148,7 @@ impl From<chrono::ParseError> for Error {
         Self {
             source: "chrono".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Parse,
         }
     }
 }
@@ -112,6 +158,7 @@ impl From<GetHomeError> for Error {
         Self {
             source: "homedir".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Io,
         }
     }
 }
@@ -121,15 +168,24 @@ impl From<serde_json::Error> for Error {
         Self {
             source: "serde_json".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Parse,
         }
     }
 }
 
 impl From<reqwest::Error> for Error {
     fn from(value: reqwest::Error) -> Self {
+        let kind = if value.is_decode() {
+            ErrorKind::Parse
+        } else if let Some(status) = value.status() {
+            ErrorKind::from_status(status.as_u16())
+        } else {
+            ErrorKind::Network
+        };
         Self {
             source: "reqwest".into(),
             message: format!("{value}"),
+            kind,
         }
     }
 }
@@ -139,6 +195,7 @@ impl From<inquire::InquireError> for Error {
         Self {
             source: "inquire".into(),
             message: format!("{value}"),
+            kind: ErrorKind::Io,
         }
     }
 }
@@ -148,8 +205,28 @@ impl Error {
         Error {
             source: source.into(),
             message: message.into(),
+            kind: ErrorKind::Other,
         }
     }
+
+    pub fn with_kind(self, kind: ErrorKind) -> Error {
+        Error { kind, ..self }
+    }
+
+    /// Network failures and rate limits may succeed if tried again later
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind, ErrorKind::Network | ErrorKind::RateLimited)
+    }
+
+    /// Machine readable form for scripts, i.e. {"kind":"RateLimited","message":"...","source":"..."}
+    pub fn json(&self) -> String {
+        serde_json::json!({
+            "kind": self.kind,
+            "message": self.message,
+            "source": self.source,
+        })
+        .to_string()
+    }
 }
 
 #[cfg(test)]
@@ -162,7 +239,21 @@ mod tests {
         let error = Error {
             message: "there".to_string(),
             source: "hello".to_string(),
+            kind: ErrorKind::Other,
         };
         assert_eq!(error.to_string(), String::from("Error from hello:\nthere"))
     }
+
+    #[test]
+    fn can_format_json() {
+        let error = Error::new("request", "Too many requests").with_kind(ErrorKind::RateLimited);
+        assert_eq!(
+            error.json(),
+            String::from(
+                r#"{"kind":"RateLimited","message":"Too many requests","source":"request"}"#
+            )
+        );
+        let value: serde_json::Value = serde_json::from_str(&error.json()).unwrap();
+        assert_eq!(value["kind"], "RateLimited");
+    }
 }
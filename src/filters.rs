@@ -24,10 +24,7 @@ pub async fn edit_task(config: &Config, filter: String) -> Result<String, Error>
     let selections = input::multi_select(input::ATTRIBUTES, options, config.mock_select)?;
 
     if selections.is_empty() {
-        return Err(Error {
-            message: "Nothing selected".to_string(),
-            source: "edit_task".to_string(),
-        });
+        return Err(Error::new("edit_task", "Nothing selected"));
     }
 
     let mut handles = Vec::new();
@@ -47,7 +44,7 @@ pub async fn edit_task(config: &Config, filter: String) -> Result<String, Error>
 pub async fn next_task(config: &Config, filter: &str) -> Result<String, Error> {
     match fetch_next_task(config, filter).await {
         Ok(Some((task, remaining))) => {
-            let comments = todoist::all_comments(config, &task, None).await?;
+            let comments = todoist::all_comments(config, &task, None, None).await?;
             config.set_next_task(task.clone()).save().await?;
             let task_string = task.fmt(comments, config, FormatType::Single, true).await?;
             Ok(format!("{task_string}\n{remaining} task(s) remaining"))
@@ -77,7 +74,7 @@ pub async fn schedule(config: &Config, filter: &str, sort: &SortOrder) -> Result
         .flat_map(|(_, tasks)| tasks.to_owned())
         .collect::<Vec<Task>>();
 
-    let tasks = tasks::sort(tasks, config, sort);
+    let tasks = tasks::sort(tasks, config, sort, false);
 
     if tasks.is_empty() {
         Ok(color::green_string(&format!(
@@ -105,7 +102,7 @@ pub async fn deadline(config: &Config, filter: &str, sort: &SortOrder) -> Result
         .flat_map(|(_, tasks)| tasks.to_owned())
         .collect::<Vec<Task>>();
 
-    let tasks = tasks::sort(tasks, config, sort);
+    let tasks = tasks::sort(tasks, config, sort, false);
     let filtered_tasks: Vec<Task> = tasks
         .into_iter()
         .filter(|task| !task.filter(config, &TaskFilter::Recurring))
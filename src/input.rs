@@ -13,7 +13,7 @@ pub const FILTER: &str = "Set filter";
 pub const PATH: &str = "Set path";
 pub const DATE: &str = "Set a due date";
 pub const TIME: &str = "Set time, i.e. 3pm or 1500";
-pub const DURATION: &str = "Set duration in minutes";
+pub const DURATION: &str = "Set duration, i.e. 45, 2h or 2d";
 
 // Select
 pub const ATTRIBUTES: &str = "Select attributes";
@@ -24,18 +24,35 @@ pub const PRIORITY: &str = "Select priority";
 pub const OPTION: &str = "Select an option";
 pub const SELECT_DATE: &str = "Select a date";
 pub const TASK: &str = "Select a task";
+pub const SELECT_TASKS: &str = "Select tasks to complete";
 
 // Options
 pub const NAT_LANG: &str = "Natural Language";
 pub const NO_DATE: &str = "No Date";
 pub const COMPLETE: &str = "Complete";
+pub const COMPLETE_OCCURRENCE: &str = "Complete this occurrence";
+pub const STOP_RECURRENCE: &str = "Complete and stop recurrence";
 pub const TIMEBOX: &str = "Timebox";
 pub const COMMENT: &str = "Comment";
 pub const SKIP: &str = "Skip";
 pub const DELETE: &str = "Delete";
 pub const CANCEL: &str = "Cancel";
+pub const CREATE: &str = "Create";
 pub const QUIT: &str = "Quit";
 pub const SCHEDULE: &str = "Schedule";
+pub const MOVE: &str = "Move";
+pub const RESCHEDULE: &str = "Reschedule";
+pub const MERGE: &str = "Merge";
+pub const CHANGE_PRIORITY: &str = "Change priority";
+pub const RENAME: &str = "Rename";
+pub const POSTPONE: &str = "Postpone";
+pub const SNOOZE: &str = "Snooze";
+pub const SET_DURATION: &str = "Set duration";
+pub const ASSIGN: &str = "Assign";
+pub const DEFER: &str = "Defer";
+pub const EDIT: &str = "Edit content";
+
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
 
 pub enum DateTimeInput {
     Skip,
@@ -108,10 +125,7 @@ pub fn datetime(
         NO_DATE => Ok(DateTimeInput::None),
         "Complete" => Ok(DateTimeInput::Complete),
         SKIP => Ok(DateTimeInput::Skip),
-        _ => Err(Error {
-            message: "Unrecognized input".into(),
-            source: "Datetime Input".into(),
-        }),
+        _ => Err(Error::new("Datetime Input", "Unrecognized input")),
     }
 }
 
@@ -209,6 +223,14 @@ pub fn page_size() -> usize {
     }
 }
 
+/// Width of the terminal in columns, 80 when it can't be detected
+pub fn terminal_width() -> usize {
+    match terminal_size() {
+        Some((Width(width), Height(_))) => width.into(),
+        None => DEFAULT_TERMINAL_WIDTH,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,52 +1,670 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use crate::{
     color,
     comments::Comment,
     config::Config,
-    errors::Error,
+    errors::{Error, ErrorKind},
+    input,
     projects::Project,
-    tasks::{self, FormatType, SortOrder, Task, priority::Priority},
-    todoist,
+    sections::Section,
+    tasks::{self, FormatType, SortOrder, Task, TaskAction, priority::Priority},
+    time,
+    todoist::{self, TaskUpdate},
 };
-use futures::future;
-use tokio::{fs, io::AsyncReadExt, task::JoinError};
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use futures::{Stream, StreamExt, TryStreamExt, future, stream, stream::FuturesOrdered};
+use once_cell::sync::Lazy;
+use pad::PadStr;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    sync::{Semaphore, broadcast},
+    task::{AbortHandle, JoinError, JoinHandle},
+};
+
+/// Tasks due today, including overdue tasks, the same as the Today view in Todoist
+const TODAY_QUERY: &str = "today | overdue";
+const OVERDUE_QUERY: &str = "overdue";
+/// Heading for tasks without labels in label stats
+const UNLABELED: &str = "(unlabeled)";
+/// Maximum number of tasks formatted at the same time in view
+const FORMAT_CONCURRENCY: usize = 8;
+/// Passing this as the file path to import reads from stdin instead
+pub const STDIN_PATH: &str = "-";
+/// Separates content from annotations on a line of an import file
+const IMPORT_SEPARATOR: &str = " | ";
+/// Marks the annotation of an import line that becomes the task description
+const IMPORT_DESCRIPTION_PREFIX: &str = "desc:";
+const PAD_WIDTH: usize = 30;
+/// Number of task updates from prioritize or label that are sent in a single batch request
+const BATCH_THRESHOLD: usize = 5;
+/// Widest bar drawn by histogram
+const HISTOGRAM_WIDTH: usize = 50;
+
+/// A change made by the last complete-all or prioritize, stored in the config so it can be undone
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum UndoEntry {
+    /// The task was completed and needs to be reopened
+    Reopen(Task),
+    /// The task had its priority changed, it still holds the previous priority
+    RestorePriority(Task),
+}
+
+/// The result of a bulk operation, `changed` is the number of tasks that were acted on
+#[derive(Debug, PartialEq)]
+pub struct OperationOutcome {
+    pub message: String,
+    pub changed: usize,
+}
+
+impl OperationOutcome {
+    fn new(message: String, changed: usize) -> OperationOutcome {
+        OperationOutcome { message, changed }
+    }
+}
+
+impl Display for OperationOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", color::green_string(&self.message))
+    }
+}
 
 #[derive(Clone)]
 pub enum Flag {
     Project(Project),
+    /// A project together with its sub-projects from config
+    ProjectTree(Project),
+    /// A single section of a project
+    Section(Section),
     Filter(String),
+    /// Several filters whose tasks are combined, a task is only listed under the first filter that returns it
+    Filters(Vec<String>),
+    /// Shortcut for a filter of tasks due today or overdue
+    Today,
+    /// Shortcut for a filter of overdue tasks
+    Overdue,
+    /// Tasks with this label, the same as the filter @label
+    Label(String),
 }
 
 impl Display for Flag {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Flag::Project(project) => write!(f, "{project}"),
+            Flag::ProjectTree(project) => write!(f, "{project} and its sub-projects"),
+            Flag::Section(section) => write!(f, "{}", section.name),
             Flag::Filter(filter) => write!(f, "'{filter}'"),
+            Flag::Filters(filters) => {
+                let quoted = filters
+                    .iter()
+                    .map(|filter| format!("'{filter}'"))
+                    .collect::<Vec<String>>();
+                write!(f, "{}", quoted.join(", "))
+            }
+            Flag::Today => write!(f, "today"),
+            Flag::Overdue => write!(f, "overdue"),
+            Flag::Label(label) => write!(f, "@{label}"),
+        }
+    }
+}
+
+impl Flag {
+    /// A filter flag, split into several filters when the string is comma separated
+    pub fn from_filter(filter: &str) -> Flag {
+        let filters = filter
+            .split(',')
+            .map(|filter| filter.trim().to_string())
+            .filter(|filter| !filter.is_empty())
+            .collect::<Vec<String>>();
+        match filters.len() {
+            0 | 1 => Flag::Filter(filter.to_string()),
+            _ => Flag::Filters(filters),
+        }
+    }
+
+    /// Projects and sections hold every task, including ones scheduled for later
+    fn is_project_scoped(&self) -> bool {
+        matches!(
+            self,
+            Flag::Project(_) | Flag::ProjectTree(_) | Flag::Section(_)
+        )
+    }
+
+    /// The project or section name, or the Todoist filter query used to fetch tasks
+    pub fn query(&self) -> String {
+        match self {
+            Flag::Project(project) | Flag::ProjectTree(project) => project.name.clone(),
+            Flag::Section(section) => section.name.clone(),
+            Flag::Filter(filter) => filter.clone(),
+            Flag::Filters(filters) => filters.join(","),
+            Flag::Today => TODAY_QUERY.into(),
+            Flag::Overdue => OVERDUE_QUERY.into(),
+            Flag::Label(label) => format!("@{label}"),
         }
     }
 }
 
-/// Get a list of all tasks
-pub async fn view(config: &mut Config, flag: Flag, sort: &SortOrder) -> Result<String, Error> {
-    let list_of_tasks = match flag.clone() {
-        Flag::Project(project) => vec![(
+/// Fetches tasks for the flag, grouped by project name or filter query
+async fn tasks_by_query(config: &Config, flag: &Flag) -> Result<Vec<(String, Vec<Task>)>, Error> {
+    match flag {
+        Flag::Project(project) => Ok(vec![(
             project.name.clone(),
-            todoist::all_tasks_by_project(config, &project, None).await?,
-        )],
-        Flag::Filter(filter) => todoist::all_tasks_by_filters(config, &filter).await?,
-    };
+            todoist::all_tasks_by_project(config, project, None, false).await?,
+        )]),
+        Flag::ProjectTree(project) => {
+            let tasks = todoist::all_tasks_by_project(config, project, None, true).await?;
+            let projects = config.projects().await?;
+            let mut list_of_tasks: Vec<(String, Vec<Task>)> = Vec::new();
+            for task in tasks {
+                let name = projects
+                    .iter()
+                    .find(|p| p.id == task.project_id)
+                    .map(|p| p.name.clone())
+                    .unwrap_or_else(|| task.project_id.clone());
+                match list_of_tasks.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, project_tasks)) => project_tasks.push(task),
+                    None => list_of_tasks.push((name, vec![task])),
+                }
+            }
+            Ok(list_of_tasks)
+        }
+        Flag::Section(section) => Ok(vec![(
+            section.name.clone(),
+            todoist::all_tasks_by_section(config, section, None).await?,
+        )]),
+        Flag::Filters(_) => {
+            let mut seen = HashSet::new();
+            let list_of_tasks = todoist::all_tasks_by_filters(config, &flag.query())
+                .await?
+                .into_iter()
+                .map(|(query, tasks)| {
+                    let tasks = tasks
+                        .into_iter()
+                        .filter(|task| seen.insert(task.id.clone()))
+                        .collect();
+                    (query, tasks)
+                })
+                .collect();
+            Ok(list_of_tasks)
+        }
+        Flag::Filter(_) | Flag::Today | Flag::Overdue | Flag::Label(_) => {
+            todoist::all_tasks_by_filters(config, &flag.query()).await
+        }
+    }
+}
 
-    let mut buffer = String::new();
+/// Fetches tasks for the flag as a single list
+async fn all_tasks(config: &Config, flag: &Flag) -> Result<Vec<Task>, Error> {
+    Ok(tasks_by_query(config, flag)
+        .await?
+        .into_iter()
+        .flat_map(|(_, tasks)| tasks)
+        .collect::<Vec<Task>>())
+}
+
+/// How view lists tasks, with every option off by default
+#[derive(Debug, Default)]
+pub struct ViewOptions<'a> {
+    pub reverse: bool,
+    /// Only tasks with this label are kept
+    pub label_filter: Option<&'a str>,
+    /// Splits the list into sections by due date, with undated tasks last
+    pub group_by_due: bool,
+    /// Prefixes each task with its Todoist id
+    pub show_ids: bool,
+    /// Writes each task to this file as it is formatted and returns a short confirmation instead
+    pub output: Option<&'a str>,
+    /// Leaves out tasks assigned to someone else
+    pub only_mine: bool,
+    /// Starts each section with a summary of its tasks
+    pub stats_header: bool,
+    /// Ends each section with the total duration of its tasks and when they would be finished
+    pub show_eta: bool,
+    /// Only keeps tasks due at a time within this HH:MM-HH:MM window
+    pub due_window: Option<&'a str>,
+}
+
+/// Get a list of all tasks. Use FormatType::Json for a JSON array of tasks or FormatType::Markdown for a checklist instead of a colored list.
+/// FormatType::Plain gives one line of content per task, without titles or headings.
+/// FormatType::Table gives aligned columns fitted to the terminal width.
+/// In lists and Markdown subtasks are indented beneath their parent.
+pub async fn view(
+    config: &mut Config,
+    flag: Flag,
+    sort: &SortOrder,
+    format: FormatType,
+    options: &ViewOptions<'_>,
+) -> Result<String, Error> {
+    let ViewOptions {
+        reverse,
+        label_filter,
+        group_by_due,
+        show_ids,
+        output,
+        only_mine,
+        stats_header,
+        show_eta,
+        due_window,
+    } = *options;
+    let window = due_window.map(parse_due_window).transpose()?;
+    let mut list_of_tasks = tasks_by_query(config, &flag).await?;
+    if let Some(label) = label_filter {
+        for (_, tasks) in list_of_tasks.iter_mut() {
+            tasks.retain(|task| task.labels.iter().any(|l| l == label));
+        }
+    }
+    if only_mine {
+        let user_id = config.user_id().await?;
+        for (_, tasks) in list_of_tasks.iter_mut() {
+            tasks.retain(|task| is_mine(task, &user_id));
+        }
+    }
+    if let Some((start, end)) = window {
+        for (_, tasks) in list_of_tasks.iter_mut() {
+            *tasks = filter_due_between(std::mem::take(tasks), config, start, end);
+        }
+    }
+    let count: usize = list_of_tasks.iter().map(|(_, tasks)| tasks.len()).sum();
+    let mut writer = ViewWriter::new(output).await?;
+
+    if format == FormatType::Json {
+        let mut json = Vec::new();
+        for (_, tasks) in list_of_tasks {
+            for task in tasks::sort(tasks, config, sort, reverse) {
+                json.push(task.json(config).await?);
+            }
+        }
+        writer.push(&serde_json::to_string_pretty(&json)?).await?;
+        return writer.finish(&flag, count).await;
+    }
 
+    let markdown = format == FormatType::Markdown;
+    let plain = format == FormatType::Plain;
     for (query, tasks) in list_of_tasks {
         let title = format!("Tasks for {query}");
+        if markdown {
+            writer.push(&format!("\n## {title}\n")).await?;
+        } else if !plain {
+            writer
+                .push(&format!("\n{}\n", color::green_string(&title)))
+                .await?;
+        }
+        let tasks = tasks::sort(tasks, config, sort, reverse);
+        let sections = if group_by_due {
+            group_by_due_date(tasks, config)?
+                .into_iter()
+                .map(|(heading, tasks)| (Some(heading), tasks))
+                .collect()
+        } else {
+            vec![(None, tasks)]
+        };
+        for (heading, tasks) in sections {
+            match heading {
+                Some(heading) if markdown => writer.push(&format!("\n### {heading}\n")).await?,
+                Some(heading) if !plain => {
+                    writer
+                        .push(&format!("\n{}\n", color::cyan_string(&heading)))
+                        .await?
+                }
+                _ => (),
+            }
+            if stats_header && !plain {
+                writer.push(&stats_line(&tasks)).await?;
+                writer.push("\n").await?;
+            }
+            if format == FormatType::Table {
+                let width = input::terminal_width();
+                writer.push("\n").await?;
+                writer
+                    .push(&tasks::format::table(&tasks, config, width))
+                    .await?;
+            } else {
+                let (depths, tasks): (Vec<usize>, Vec<Task>) = if plain {
+                    (vec![0; tasks.len()], tasks.clone())
+                } else {
+                    nest_subtasks(tasks.clone()).into_iter().unzip()
+                };
+                let texts = format_tasks(&tasks, config, format, show_ids).await?;
+                for (text, depth) in texts.iter().zip(depths) {
+                    // Markdown list items and plain lines go on consecutive lines
+                    if !markdown && !plain {
+                        writer.push("\n").await?;
+                    }
+                    writer.push(&indent(text, depth)).await?;
+                }
+            }
+            if show_eta && !plain {
+                if let Some(eta) = eta_line(&tasks, config)? {
+                    writer.push("\n").await?;
+                    writer.push(&eta).await?;
+                    writer.push("\n").await?;
+                }
+            }
+        }
+    }
+    writer.finish(&flag, count).await
+}
+
+/// One line summary of the tasks, counted from what was already fetched
+fn stats_line(tasks: &[Task]) -> String {
+    let prioritized = tasks
+        .iter()
+        .filter(|task| task.priority != Priority::None)
+        .count();
+    let dated = tasks.iter().filter(|task| task.due.is_some()).count();
+    format!(
+        "{} tasks, {prioritized} prioritized, {dated} with due dates",
+        tasks.len()
+    )
+}
+
+/// Total duration of the tasks and the time they would be done if started now, in the config timezone.
+/// Tasks without a duration are left out, None when no task has one.
+fn eta_line(tasks: &[Task], config: &Config) -> Result<Option<String>, Error> {
+    let minutes: u32 = tasks
+        .iter()
+        .filter_map(|task| task.duration.as_ref())
+        .map(|duration| duration.minutes())
+        .sum();
+    if minutes == 0 {
+        return Ok(None);
+    }
+
+    let finish = time::datetime_now(config)? + chrono::Duration::minutes(minutes.into());
+    let total = match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    };
+    Ok(Some(format!(
+        "Total: {total}, finishing ~{}",
+        finish.format(time::FORMAT_TIME)
+    )))
+}
+
+/// Where view sends its text, either a buffer that is returned or a file that is written as it goes
+struct ViewWriter {
+    buffer: String,
+    file: Option<(String, fs::File)>,
+}
+
+impl ViewWriter {
+    async fn new(output: Option<&str>) -> Result<ViewWriter, Error> {
+        let file = match output {
+            Some(path) => Some((path.to_string(), fs::File::create(path).await?)),
+            None => None,
+        };
+        Ok(ViewWriter {
+            buffer: String::new(),
+            file,
+        })
+    }
+
+    async fn push(&mut self, text: &str) -> Result<(), Error> {
+        match &mut self.file {
+            Some((_, file)) => file.write_all(text.as_bytes()).await?,
+            None => self.buffer.push_str(text),
+        }
+        Ok(())
+    }
+
+    /// The buffered text, or a confirmation once the file is flushed
+    async fn finish(self, flag: &Flag, count: usize) -> Result<String, Error> {
+        match self.file {
+            Some((path, mut file)) => {
+                file.write_all(b"\n").await?;
+                file.flush().await?;
+                Ok(color::green_string(&format!(
+                    "Wrote {count} tasks from {flag} to {path}"
+                )))
+            }
+            None => Ok(self.buffer),
+        }
+    }
+}
+
+/// Unassigned tasks and tasks assigned to the user
+fn is_mine(task: &Task, user_id: &str) -> bool {
+    task.responsible_uid
+        .as_ref()
+        .is_none_or(|responsible_uid| responsible_uid == user_id)
+}
+
+/// Parses a due time window such as 08:00-12:00
+fn parse_due_window(window: &str) -> Result<(NaiveTime, NaiveTime), Error> {
+    let invalid = || {
+        Error::new(
+            "parse_due_window",
+            &format!("Invalid time window '{window}', expected HH:MM-HH:MM"),
+        )
+    };
+    let (start, end) = window.split_once('-').ok_or_else(invalid)?;
+    let start =
+        NaiveTime::parse_from_str(start.trim(), time::FORMAT_TIME).map_err(|_| invalid())?;
+    let end = NaiveTime::parse_from_str(end.trim(), time::FORMAT_TIME).map_err(|_| invalid())?;
+    if start > end {
+        return Err(Error::new(
+            "parse_due_window",
+            &format!("Time window '{window}' ends before it starts"),
+        ));
+    }
+    Ok((start, end))
+}
+
+/// Tasks due at a time between start and end inclusive, in the config timezone.
+/// Tasks that are undated or only have a due date are left out.
+fn filter_due_between(
+    tasks: Vec<Task>,
+    config: &Config,
+    start: NaiveTime,
+    end: NaiveTime,
+) -> Vec<Task> {
+    tasks
+        .into_iter()
+        .filter(|task| {
+            task.due_time(config)
+                .is_some_and(|due_time| start <= due_time && due_time <= end)
+        })
+        .collect()
+}
+
+/// Orders tasks so that subtasks come right after their parent, each paired with how deeply it is nested.
+/// Siblings keep their sorted order. Subtasks whose parent is not in the list are kept at the top level.
+fn nest_subtasks(tasks: Vec<Task>) -> Vec<(usize, Task)> {
+    let ids = tasks
+        .iter()
+        .map(|task| task.id.clone())
+        .collect::<HashSet<String>>();
+    let mut children: HashMap<String, Vec<Task>> = HashMap::new();
+    let mut top_level = Vec::new();
+    for task in tasks {
+        match &task.parent_id {
+            Some(parent_id) if ids.contains(parent_id) => {
+                children.entry(parent_id.clone()).or_default().push(task)
+            }
+            _ => top_level.push(task),
+        }
+    }
+
+    let mut nested = Vec::new();
+    let mut stack = top_level
+        .into_iter()
+        .rev()
+        .map(|task| (0, task))
+        .collect::<Vec<(usize, Task)>>();
+    while let Some((depth, task)) = stack.pop() {
+        if let Some(subtasks) = children.remove(&task.id) {
+            stack.extend(subtasks.into_iter().rev().map(|task| (depth + 1, task)));
+        }
+        nested.push((depth, task));
+    }
+    nested
+}
+
+/// Indents every line of a formatted task by two spaces per level of nesting
+fn indent(text: &str, depth: usize) -> String {
+    let padding = "  ".repeat(depth);
+    text.split_inclusive('\n')
+        .map(|line| match line.trim() {
+            "" => line.to_string(),
+            _ => format!("{padding}{line}"),
+        })
+        .collect()
+}
+
+/// Formats tasks concurrently, the results are in the same order as the tasks
+async fn format_tasks(
+    tasks: &[Task],
+    config: &Config,
+    format: FormatType,
+    show_ids: bool,
+) -> Result<Vec<String>, Error> {
+    stream::iter(tasks)
+        .map(|task| async move {
+            let text = task.fmt(Vec::new(), config, format, true).await?;
+            if show_ids {
+                let id = color::dim_string(&format!("[{}]", task.id));
+                Ok(format!("{id} {text}"))
+            } else {
+                Ok(text)
+            }
+        })
+        .buffered(FORMAT_CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+/// Splits sorted tasks into sections by due date, earliest first and undated tasks last.
+/// Tasks keep their sorted order within a section.
+fn group_by_due_date(tasks: Vec<Task>, config: &Config) -> Result<Vec<(String, Vec<Task>)>, Error> {
+    let mut groups: Vec<(Option<NaiveDate>, Vec<Task>)> = Vec::new();
+    for task in tasks {
+        let date = task.due_date(config);
+        match groups
+            .iter_mut()
+            .find(|(group_date, _)| *group_date == date)
+        {
+            Some((_, group)) => group.push(task),
+            None => groups.push((date, vec![task])),
+        }
+    }
+    groups.sort_by_key(|(date, _)| (date.is_none(), *date));
+
+    groups
+        .into_iter()
+        .map(|(date, tasks)| {
+            let heading = match date {
+                None => String::from("No date"),
+                Some(date) => match time::naive_date_days_in_future(date, config)? {
+                    0 => String::from("Today"),
+                    1 => String::from("Tomorrow"),
+                    _ => date.to_string(),
+                },
+            };
+            Ok((heading, tasks))
+        })
+        .collect()
+}
+
+/// List the tasks in a project completed since a date such as 2025-01-31, the last 30 days by default
+pub async fn completed(
+    config: &Config,
+    project: &Project,
+    since: Option<&str>,
+) -> Result<String, Error> {
+    let since = match since {
+        None => None,
+        Some(date) => {
+            let tz = time::timezone_from_str(&config.get_timezone()?)?;
+            let date = time::date_from_str(date, tz)?;
+            Some(time::datetime_from_str(&format!("{date}T00:00:00"), tz)?)
+        }
+    };
+    let tasks = todoist::completed_tasks_by_project(config, project, since, None).await?;
+
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!(
+            "No completed tasks for {project}"
+        )));
+    }
+
+    let title = format!("Completed tasks for {project}");
+    let mut buffer = color::green_string(&title);
+    buffer.push('\n');
+    for task in tasks {
+        let text = task
+            .fmt(Vec::new(), config, FormatType::List, false)
+            .await?;
+        buffer.push('\n');
+        buffer.push_str(&text);
+    }
+    Ok(buffer)
+}
+
+/// Tasks in a project completed between two dates such as 2025-01-31, grouped by the day they were completed.
+/// Both dates are included and until defaults to today.
+pub async fn completed_report(
+    config: &Config,
+    project: &Project,
+    since: &str,
+    until: Option<&str>,
+) -> Result<String, Error> {
+    let tz = time::timezone_from_str(&config.get_timezone()?)?;
+    let since = time::date_from_str(since, tz)?;
+    let until = match until {
+        Some(date) => time::date_from_str(date, tz)?,
+        None => time::naive_date_today(config)?,
+    };
+    if until < since {
+        return Err(Error::new(
+            "completed_report",
+            &format!("{until} is before {since}"),
+        ));
+    }
+
+    let start = time::datetime_from_str(&format!("{since}T00:00:00"), tz)?;
+    let end = time::datetime_from_str(&format!("{until}T23:59:59"), tz)?;
+    let tasks =
+        todoist::completed_tasks_by_project(config, project, Some(start), Some(end)).await?;
+
+    let mut days: BTreeMap<NaiveDate, Vec<Task>> = BTreeMap::new();
+    for task in tasks {
+        match completed_date(&task, tz) {
+            Some(date) if date >= since && date <= until => {
+                days.entry(date).or_default().push(task)
+            }
+            _ => (),
+        }
+    }
+
+    let window = format!("from {since} to {until}");
+    if days.is_empty() {
+        return Ok(color::green_string(&format!(
+            "No completed tasks for {} {window}",
+            project.name
+        )));
+    }
+
+    let title = format!("Completed tasks for {} {window}", project.name);
+    let mut buffer = color::green_string(&title);
+    buffer.push('\n');
+    for (date, tasks) in days {
         buffer.push('\n');
-        buffer.push_str(&color::green_string(&title));
+        buffer.push_str(&color::cyan_string(&date.to_string()));
         buffer.push('\n');
-        for task in tasks::sort(tasks, config, sort) {
-            let comments = Vec::new();
-            let text = task.fmt(comments, config, FormatType::List, true).await?;
+        for task in tasks {
+            let text = task
+                .fmt(Vec::new(), config, FormatType::List, false)
+                .await?;
             buffer.push('\n');
             buffer.push_str(&text);
         }
@@ -54,258 +672,5401 @@ pub async fn view(config: &mut Config, flag: Flag, sort: &SortOrder) -> Result<S
     Ok(buffer)
 }
 
-/// Prioritize all unprioritized tasks
-pub async fn prioritize(config: &Config, flag: Flag, sort: &SortOrder) -> Result<String, Error> {
-    let tasks = match flag.clone() {
-        Flag::Project(project) => todoist::all_tasks_by_project(config, &project, None)
-            .await?
-            .into_iter()
-            .filter(|task| task.priority == Priority::None)
-            .collect::<Vec<Task>>(),
-        Flag::Filter(filter) => todoist::all_tasks_by_filters(config, &filter)
-            .await?
-            .iter()
-            .flat_map(|(_, tasks)| tasks.to_owned())
-            .collect::<Vec<Task>>(),
+/// The day a task was completed in the config timezone
+fn completed_date(task: &Task, tz: Tz) -> Option<NaiveDate> {
+    let completed_at = task.completed_at.as_deref()?;
+    let datetime = DateTime::parse_from_rfc3339(completed_at).ok()?;
+    Some(datetime.with_timezone(&tz).date_naive())
+}
+
+/// Prioritize all tasks that currently have the `from` priority.
+/// Projects default to unprioritized tasks, filters default to all tasks.
+pub async fn prioritize(
+    config: &Config,
+    flag: Flag,
+    sort: &SortOrder,
+    reverse: bool,
+    from: Option<Priority>,
+    dry_run: bool,
+    limit: Option<usize>,
+) -> Result<OperationOutcome, Error> {
+    let from = match (&flag, from) {
+        (_, Some(priority)) => Some(priority),
+        (flag, None) if flag.is_project_scoped() => Some(Priority::None),
+        (_, None) => None,
     };
 
-    let empty_text = format!("No tasks for {flag}");
-    let success = format!("Successfully prioritized {flag}");
+    let tasks = all_tasks(config, &flag)
+        .await?
+        .into_iter()
+        .filter(|task| {
+            from.as_ref()
+                .is_none_or(|priority| task.priority == *priority)
+        })
+        .collect::<Vec<Task>>();
 
+    let empty_text = match &from {
+        Some(priority) => format!("No tasks with priority {priority} for {flag}"),
+        None => format!("No tasks for {flag}"),
+    };
     if tasks.is_empty() {
-        return Ok(color::green_string(&empty_text));
+        return Ok(OperationOutcome::new(empty_text, 0));
     }
 
-    let tasks = tasks::sort(tasks, config, sort);
+    let tasks = tasks::sort(tasks, config, sort, reverse);
+    let (tasks, limit_note) = apply_limit(tasks, limit);
+    let success = if dry_run {
+        format!("Dry run: would have prioritized {flag}{limit_note}")
+    } else {
+        format!("Successfully prioritized {flag}{limit_note}")
+    };
 
-    let mut handles = Vec::new();
+    let mut sender = UpdateSender::new(config, tasks.len());
+    let mut undo_entries = Vec::new();
+    let mut prompted = Ok(());
     for task in tasks {
         println!();
-        let handle = tasks::set_priority(config, task, true).await?;
-        handles.push(handle);
+        // Only tasks whose priority actually changes need restoring on undo
+        match tasks::set_priority(config, task.clone(), true, dry_run).await {
+            Ok(Some(update)) => {
+                undo_entries.push(UndoEntry::RestorePriority(task));
+                sender.push(update);
+            }
+            Ok(None) => (),
+            Err(e) => {
+                prompted = Err(e);
+                break;
+            }
+        }
     }
-    future::join_all(handles).await;
-    Ok(color::green_string(&success))
+    // Every pushed update is sent, so the undo log matches what reached Todoist
+    let changed = undo_entries.len();
+    let sent = sender.finish().await;
+    if !dry_run {
+        save_undo_log(config, Some(undo_entries)).await?;
+    }
+    prompted?;
+    sent?;
+    Ok(OperationOutcome::new(success, changed))
+}
+
+/// Moves every task delta priority levels up, or down when delta is negative.
+/// Tasks that are already at HIGH or NONE in that direction are left alone.
+pub async fn bump_priority(
+    config: &Config,
+    flag: Flag,
+    delta: i8,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let mut updates = Vec::new();
+    let mut undo_entries = Vec::new();
+    let mut skipped = 0;
+    for task in tasks::sort(tasks, config, sort, false) {
+        let priority = task.priority.shifted(delta);
+        if priority == task.priority {
+            skipped += 1;
+            continue;
+        }
+        undo_entries.push(UndoEntry::RestorePriority(task.clone()));
+        updates.push(TaskUpdate::Priority(task, priority));
+    }
+    let changed = updates.len();
+    let desc = format!("Change the priority of {changed} tasks?");
+    if !confirmed(config, &desc, input::CHANGE_PRIORITY, changed)? {
+        return Ok("Cancelled".into());
+    }
+    let sent = send_updates(config, updates).await;
+    if changed > 0 {
+        save_undo_log(config, Some(undo_entries)).await?;
+    }
+    sent?;
+
+    let skipped_note = match skipped {
+        0 => String::new(),
+        n => format!(", skipped {n} already at the limit"),
+    };
+    Ok(color::green_string(&format!(
+        "Changed the priority of {changed} tasks in {flag}{skipped_note}"
+    )))
 }
 
 /// Gives tasks durations
-pub async fn timebox(config: &Config, flag: Flag, sort: &SortOrder) -> Result<String, Error> {
-    let tasks = match flag.clone() {
-        Flag::Project(project) => todoist::all_tasks_by_project(config, &project, None)
-            .await?
+pub async fn timebox(
+    config: &Config,
+    flag: Flag,
+    sort: &SortOrder,
+    reverse: bool,
+    dry_run: bool,
+    limit: Option<usize>,
+) -> Result<OperationOutcome, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    let tasks = if flag.is_project_scoped() {
+        tasks
             .into_iter()
             .filter(|task| task.duration.is_none())
-            .collect::<Vec<Task>>(),
-        Flag::Filter(filter) => todoist::all_tasks_by_filters(config, &filter)
-            .await?
-            .into_iter()
-            .flat_map(|(_, tasks)| tasks.to_owned())
-            .collect::<Vec<Task>>(),
+            .collect::<Vec<Task>>()
+    } else {
+        tasks
     };
 
     let empty_text = format!("No tasks for {flag}");
-    let success = format!("Successfully timeboxed {flag}");
 
     if tasks.is_empty() {
-        return Ok(color::green_string(&empty_text));
+        return Ok(OperationOutcome::new(empty_text, 0));
     }
 
-    let tasks = tasks::sort(tasks, config, sort);
-    let mut task_count = tasks.len() as i32;
+    let tasks = tasks::sort(tasks, config, sort, reverse);
+    let (tasks, limit_note) = apply_limit(tasks, limit);
+    let success = if dry_run {
+        format!("Dry run: would have timeboxed {flag}{limit_note}")
+    } else {
+        format!("Successfully timeboxed {flag}{limit_note}")
+    };
+    let total = tasks.len() as i32;
+    let mut task_count = total;
     let mut handles = Vec::new();
     for task in tasks {
         println!();
-        match tasks::timebox_task(&config.reload().await?, task, &mut task_count, false).await? {
-            Some(handle) => handles.push(handle),
-            None => return Ok(color::green_string("Exited")),
+        maybe_print_progress(total, task_count);
+        match tasks::timebox_task(
+            &config.reload().await?,
+            task,
+            &mut task_count,
+            false,
+            dry_run,
+        )
+        .await?
+        {
+            TaskAction::Changed(handle) | TaskAction::Completed(handle) => handles.push(handle),
+            TaskAction::Unchanged => (),
+            TaskAction::Quit => {
+                return Ok(OperationOutcome::new("Exited".into(), handles.len()));
+            }
         }
     }
+    let changed = handles.len();
     future::join_all(handles).await;
-    Ok(color::green_string(&success))
+    Ok(OperationOutcome::new(success, changed))
 }
 
-/// Get next tasks and give an interactive prompt for completing them one by one
-pub async fn process(config: &Config, flag: Flag, sort: &SortOrder) -> Result<String, Error> {
-    let tasks = match flag.clone() {
-        Flag::Project(project) => {
-            let tasks = todoist::all_tasks_by_project(config, &project, None).await?;
-            tasks::filter_not_in_future(tasks, config)?
-        }
-
-        Flag::Filter(filter) => todoist::all_tasks_by_filters(config, &filter)
-            .await?
-            .into_iter()
-            .flat_map(|(_, tasks)| tasks.to_owned())
-            .collect::<Vec<Task>>(),
-    };
-
-    let with_project = match flag.clone() {
-        Flag::Project(..) => false,
-        Flag::Filter(..) => true,
-    };
-    let tasks = tasks::reject_parent_tasks(tasks, config).await;
-
-    let empty_text = format!("No tasks for {flag}");
-    let success = format!("Successfully processed {flag}");
+/// Gives due dates to tasks that don't have one, one task at a time
+pub async fn schedule(config: &Config, flag: Flag, sort: &SortOrder) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag)
+        .await?
+        .into_iter()
+        .filter(|task| task.due.is_none())
+        .collect::<Vec<Task>>();
 
     if tasks.is_empty() {
-        return Ok(color::green_string(&empty_text));
+        return Ok(color::green_string(&format!("No undated tasks for {flag}")));
     }
 
-    let tasks = tasks::sort(tasks, config, sort);
-    let mut task_count = tasks.len() as i32;
-    let tasks_with_comments = fetch_comments_for_tasks(tasks, config).await;
+    let tasks = tasks::sort(tasks, config, sort, false);
+    let total = tasks.len() as i32;
+    let mut task_count = total;
     let mut handles = Vec::new();
-    for task_with_comments in tasks_with_comments {
-        match task_with_comments {
-            Ok((task, Ok(comments))) => {
-                println!();
-                match tasks::process_task(
-                    comments,
-                    &config.reload().await?,
-                    task,
-                    &mut task_count,
-                    with_project,
-                )
-                .await?
-                {
-                    Some(handle) => handles.push(handle),
-                    None => return Ok(color::green_string("Exited")),
-                }
-            }
-            Ok((task, Err(Error { message, source }))) => {
-                println!("Could not fetch comments from {source}: {message}");
-                let comments = Vec::new();
-                println!();
-                match tasks::process_task(
-                    comments,
-                    &config.reload().await?,
-                    task,
-                    &mut task_count,
-                    false,
-                )
-                .await?
-                {
-                    Some(handle) => handles.push(handle),
-                    None => return Ok(color::green_string("Exited")),
-                }
-            }
-            Err(JoinError { .. }) => {
-                println!("JoinError");
+    for task in tasks {
+        println!();
+        maybe_print_progress(total, task_count);
+        match tasks::schedule_undated_task(config, task, &mut task_count).await? {
+            TaskAction::Changed(handle) | TaskAction::Completed(handle) => handles.push(handle),
+            TaskAction::Unchanged => (),
+            TaskAction::Quit => {
+                future::join_all(handles).await;
+                return Ok(color::green_string("Exited"));
             }
         }
     }
+    let num_scheduled = handles.len();
     future::join_all(handles).await;
-    Ok(color::green_string(&success))
+    Ok(color::green_string(&format!(
+        "Scheduled {num_scheduled} tasks in {flag}"
+    )))
 }
 
-async fn fetch_comments_for_tasks(
-    tasks: Vec<Task>,
-    config: &Config,
-) -> Vec<Result<(Task, Result<Vec<Comment>, Error>), JoinError>> {
-    let mut handles = Vec::new();
+/// Shows only the first actionable task after sorting, with its comments.
+/// Parent tasks and, for projects, tasks in the future are rejected the same way as in process.
+pub async fn next(config: &Config, flag: Flag, sort: &SortOrder) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    let tasks = if flag.is_project_scoped() {
+        tasks::filter_not_in_future(tasks, config, 0)?
+    } else {
+        tasks
+    };
+    let tasks = tasks::reject_parent_tasks(tasks, config).await;
 
-    for task in tasks {
-        let config = config.clone();
-        let handle = tokio::spawn(async move {
-            (
-                task.clone(),
-                todoist::all_comments(&config, &task, None).await,
-            )
-        });
-        handles.push(handle);
-    }
-    future::join_all(handles).await
+    let Some(task) = tasks::sort(tasks, config, sort, false).into_iter().next() else {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    };
+
+    let with_project = !flag.is_project_scoped();
+    let comments = todoist::all_comments(config, &task, None, None).await?;
+    task.fmt(comments, config, FormatType::Single, with_project)
+        .await
 }
 
-/// Puts labels on tasks
-pub async fn label(
+/// Get next tasks and give an interactive prompt for completing them one by one
+pub async fn process(
     config: &Config,
     flag: Flag,
-    labels: &Vec<String>,
     sort: &SortOrder,
+    reverse: bool,
+    limit: Option<usize>,
+    only_mine: bool,
+    focus_break: Option<Duration>,
 ) -> Result<String, Error> {
-    let tasks = match flag.clone() {
-        Flag::Project(project) => todoist::all_tasks_by_project(config, &project, None).await?,
-        Flag::Filter(filter) => todoist::all_tasks_by_filters(config, &filter)
-            .await?
-            .into_iter()
-            .flat_map(|(_, tasks)| tasks.to_owned())
-            .collect::<Vec<Task>>(),
+    let mut tasks = all_tasks(config, &flag).await?;
+    if only_mine {
+        let user_id = config.user_id().await?;
+        tasks.retain(|task| is_mine(task, &user_id));
+    }
+    let tasks = if flag.is_project_scoped() {
+        tasks::filter_not_in_future(tasks, config, 0)?
+    } else {
+        tasks
+    };
+
+    let with_project = !flag.is_project_scoped();
+    let mut tasks = tasks::reject_parent_tasks(tasks, config).await;
+
+    let mut skipped = if config.remember_skipped {
+        config.skipped_today()?
+    } else {
+        HashSet::new()
     };
+    tasks.retain(|task| !skipped.contains(&task.id));
 
     let empty_text = format!("No tasks for {flag}");
-    let success = format!("Successfully labeled {flag}");
 
     if tasks.is_empty() {
         return Ok(color::green_string(&empty_text));
     }
 
-    let tasks = tasks::sort(tasks, config, sort);
+    let tasks = tasks::sort(tasks, config, sort, reverse);
+    let (tasks, limit_note) = apply_limit(tasks, limit);
+    let success = format!("Successfully processed {flag}{limit_note}");
+    let total = tasks.len() as i32;
+    let mut task_count = total;
+    let mut tasks_with_comments = comments_as_ready(tasks, config);
     let mut handles = Vec::new();
-    for task in tasks {
+    let mut num_skipped = 0;
+    while let Some((task, comments)) = tasks_with_comments.next().await {
+        let (comments, with_project) = match comments {
+            Ok(comments) => (comments, with_project),
+            Err(Error {
+                message, source, ..
+            }) => {
+                println!("Could not fetch comments from {source}: {message}");
+                (Vec::new(), false)
+            }
+        };
+        // A task only comes up once per run, even when it is listed twice
+        if skipped.contains(&task.id) {
+            task_count -= 1;
+            continue;
+        }
         println!();
-        let future = tasks::label_task(config, task, labels).await?;
-        handles.push(future);
+        maybe_print_progress(total, task_count);
+        let id = task.id.clone();
+        match tasks::process_task(
+            comments,
+            &config.reload().await?,
+            task,
+            &mut task_count,
+            with_project,
+        )
+        .await?
+        {
+            TaskAction::Changed(handle) => handles.push(handle),
+            TaskAction::Completed(handle) => {
+                handles.push(handle);
+                // The break follows finishing a task, other actions move straight on
+                if let Some(duration) = focus_break {
+                    if task_count > 0 && !count_down(duration).await {
+                        future::join_all(handles).await;
+                        save_skipped(config, &skipped).await?;
+                        return Ok(color::green_string("Exited"));
+                    }
+                }
+            }
+            TaskAction::Unchanged => {
+                skipped.insert(id);
+                num_skipped += 1;
+            }
+            TaskAction::Quit => {
+                save_skipped(config, &skipped).await?;
+                return Ok(color::green_string("Exited"));
+            }
+        }
     }
     future::join_all(handles).await;
-    Ok(color::green_string(&success))
+    save_skipped(config, &skipped).await?;
+    let skipped_note = match num_skipped {
+        0 => String::new(),
+        1 => String::from("\nSkipped 1 task"),
+        n => format!("\nSkipped {n} tasks"),
+    };
+    Ok(color::green_string(&format!("{success}{skipped_note}")))
 }
 
-pub async fn import(config: &Config, file_path: &str) -> Result<String, Error> {
-    let mut lines = String::new();
-    fs::File::open(file_path)
-        .await?
-        .read_to_string(&mut lines)
-        .await?;
-
-    let lines: Vec<String> = lines
-        .split('\n')
-        .map(|s| s.to_owned())
-        .filter(|s| !s.is_empty())
-        .collect();
-    for line in lines {
-        todoist::quick_create_task(config, &line, None).await?;
+/// Saves the tasks skipped today so that later runs leave them out, when remember_skipped is set
+async fn save_skipped(config: &Config, skipped: &HashSet<String>) -> Result<(), Error> {
+    if config.remember_skipped {
+        config.reload().await?.with_skipped(skipped)?.save().await?;
     }
-
-    Ok("✓".into())
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Counts down between tasks in process, returns false when interrupted with Ctrl-C
+async fn count_down(total: Duration) -> bool {
+    let countdown = async {
+        let mut remaining = total;
+        while !remaining.is_zero() {
+            let seconds = remaining.as_secs();
+            print!("\rNext task in {:02}:{:02} ", seconds / 60, seconds % 60);
+            let _ = std::io::stdout().flush();
+            let step = remaining.min(Duration::from_secs(1));
+            tokio::time::sleep(step).await;
+            remaining -= step;
+        }
+        println!();
+    };
+
+    tokio::select! {
+        _ = countdown => true,
+        _ = ctrl_c() => false,
+    }
+}
+
+/// Ctrl-C presses, sent to whatever is waiting in ctrl_c
+static CTRL_C: Lazy<broadcast::Sender<()>> = Lazy::new(|| {
+    let (sender, _) = broadcast::channel(1);
+    let listener = sender.clone();
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            // Listening replaces the default handler for the rest of the run,
+            // so when nothing is waiting Ctrl-C still exits like it normally would
+            if listener.send(()).is_err() {
+                std::process::exit(130);
+            }
+        }
+    });
+    sender
+});
+
+/// Resolves on the next Ctrl-C, the one place that listens for it
+async fn ctrl_c() {
+    let _ = CTRL_C.subscribe().recv().await;
+}
+
+/// Keeps the first `limit` tasks. The note is for appending to messages when tasks were left out.
+fn apply_limit(mut tasks: Vec<Task>, limit: Option<usize>) -> (Vec<Task>, String) {
+    match limit {
+        Some(limit) if limit < tasks.len() => {
+            let note = format!(" (limited to {limit} of {} tasks)", tasks.len());
+            tasks.truncate(limit);
+            (tasks, note)
+        }
+        _ => (tasks, String::new()),
+    }
+}
+
+/// Prints the position of the current task, i.e. "[3/14]", when there is more than one task
+fn maybe_print_progress(total: i32, remaining: i32) {
+    if let Some(progress) = progress(total, remaining) {
+        println!("{progress}");
+    }
+}
+
+fn progress(total: i32, remaining: i32) -> Option<String> {
+    if total <= 1 {
+        return None;
+    }
+
+    Some(format!("[{}/{total}]", total - remaining + 1))
+}
+
+/// Fetches comments for each task. A fetch that fails, even from a panic, becomes an error next to its task so the task is not lost.
+async fn fetch_comments_for_tasks(
+    tasks: Vec<Task>,
+    config: &Config,
+) -> Vec<(Task, Result<Vec<Comment>, Error>)> {
+    comments_as_ready(tasks, config).collect().await
+}
+
+/// Like fetch_comments_for_tasks, but yields each task as soon as its comments and those of every task before it
+/// have arrived, so the first one can be shown while the rest are still loading. Tasks keep their sorted order.
+fn comments_as_ready(
+    tasks: Vec<Task>,
+    config: &Config,
+) -> impl Stream<Item = (Task, Result<Vec<Comment>, Error>)> + Unpin {
+    let semaphore = Arc::new(Semaphore::new(config.comment_fetch_concurrency()));
+    tasks
+        .into_iter()
+        .map(|task| {
+            let handle = spawn_comment_fetch(config, &semaphore, &task);
+            async move { (task, fetched_comments(handle.await)) }
+        })
+        .collect::<FuturesOrdered<_>>()
+}
+
+fn spawn_comment_fetch(
+    config: &Config,
+    semaphore: &Arc<Semaphore>,
+    task: &Task,
+) -> JoinHandle<Result<Vec<Comment>, Error>> {
+    let config = config.clone();
+    let semaphore = semaphore.clone();
+    let task = task.clone();
+    tokio::spawn(async move {
+        // Limits how many requests are in flight so large lists don't get rate limited
+        let _permit = semaphore
+            .acquire_owned()
+            .await
+            .expect("Semaphore should not be closed");
+        todoist::all_comments(&config, &task, None, None).await
+    })
+}
+
+/// The comments from a fetch, where a fetch that panicked becomes an error so its task is still shown
+fn fetched_comments(
+    result: Result<Result<Vec<Comment>, Error>, JoinError>,
+) -> Result<Vec<Comment>, Error> {
+    result.unwrap_or_else(|e| Err(Error::from(e)))
+}
+
+/// Puts labels on tasks
+pub async fn label(
+    config: &Config,
+    flag: Flag,
+    labels: &Vec<String>,
+    sort: &SortOrder,
+    reverse: bool,
+    dry_run: bool,
+    remove: bool,
+) -> Result<OperationOutcome, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+
+    let empty_text = format!("No tasks for {flag}");
+    let success = match (dry_run, remove) {
+        (true, false) => format!("Dry run: would have labeled {flag}"),
+        (false, false) => format!("Successfully labeled {flag}"),
+        (true, true) => format!("Dry run: would have removed labels from {flag}"),
+        (false, true) => format!("Successfully removed labels from {flag}"),
+    };
+
+    if tasks.is_empty() {
+        return Ok(OperationOutcome::new(empty_text, 0));
+    }
+
+    let tasks = tasks::sort(tasks, config, sort, reverse);
+    let mut sender = UpdateSender::new(config, tasks.len());
+    let mut changed = 0;
+    let mut prompted = Ok(());
+    for task in tasks {
+        let update = if remove {
+            Ok(tasks::remove_task_labels(task, labels, dry_run))
+        } else {
+            println!();
+            tasks::label_task(config, task, labels, dry_run).await
+        };
+        match update {
+            Ok(Some(update)) => {
+                changed += 1;
+                sender.push(update);
+            }
+            Ok(None) => (),
+            Err(e) => {
+                prompted = Err(e);
+                break;
+            }
+        }
+    }
+    let sent = sender.finish().await;
+    prompted?;
+    sent?;
+    Ok(OperationOutcome::new(success, changed))
+}
+
+/// Sends updates that were worked out without prompting, i.e. by bump-priority
+async fn send_updates(config: &Config, updates: Vec<TaskUpdate>) -> Result<(), Error> {
+    let mut sender = UpdateSender::new(config, updates.len());
+    for update in updates {
+        sender.push(update);
+    }
+    sender.finish().await
+}
+
+/// Sends task updates while the prompts are still going, so that answers already given are kept
+/// when a later prompt fails or is quit. With fewer than BATCH_THRESHOLD tasks each update is sent
+/// as soon as it is pushed, otherwise every BATCH_THRESHOLD updates go out together in one request.
+struct UpdateSender {
+    config: Config,
+    batch: bool,
+    pending: Vec<TaskUpdate>,
+    handles: Vec<JoinHandle<()>>,
+    /// Number of updates sent so far, including ones still in flight
+    sent: usize,
+    completed: Arc<AtomicUsize>,
+}
+
+impl UpdateSender {
+    /// num_tasks is how many updates could be pushed at most, it decides whether they are batched
+    fn new(config: &Config, num_tasks: usize) -> UpdateSender {
+        UpdateSender {
+            config: config.clone(),
+            batch: num_tasks >= BATCH_THRESHOLD,
+            pending: Vec::new(),
+            handles: Vec::new(),
+            sent: 0,
+            completed: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn push(&mut self, update: TaskUpdate) {
+        self.pending.push(update);
+        if !self.batch || self.pending.len() >= BATCH_THRESHOLD {
+            self.flush();
+        }
+    }
+
+    /// Starts sending the pending updates without waiting for them
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let updates = std::mem::take(&mut self.pending);
+        let count = updates.len();
+        self.sent += count;
+
+        if !self.batch {
+            for update in updates {
+                let config = self.config.clone();
+                let completed = self.completed.clone();
+                self.handles.push(tokio::spawn(async move {
+                    match update.send(&config).await {
+                        Ok(_) => {
+                            completed.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Err(e) => config.tx().send(e).unwrap(),
+                    }
+                }));
+            }
+            return;
+        }
+
+        let config = self.config.clone();
+        let completed = self.completed.clone();
+        self.handles.push(tokio::spawn(async move {
+            match todoist::batch_update(&config, updates).await {
+                Ok(()) => {
+                    completed.fetch_add(count, Ordering::SeqCst);
+                }
+                Err(e) => config.tx().send(e).unwrap(),
+            }
+        }));
+    }
+
+    /// Sends what is left and waits for every update. Ctrl-C stops the updates still in flight.
+    async fn finish(self) -> Result<(), Error> {
+        self.finish_until(ctrl_c()).await
+    }
+
+    /// Like finish, but stops when abort finishes first.
+    /// The error then says how many updates completed before the rest were aborted.
+    async fn finish_until(mut self, abort: impl Future<Output = ()>) -> Result<(), Error> {
+        self.flush();
+        let abort_handles = self
+            .handles
+            .iter()
+            .map(|handle| handle.abort_handle())
+            .collect::<Vec<AbortHandle>>();
+
+        tokio::select! {
+            _ = future::join_all(self.handles) => Ok(()),
+            _ = abort => {
+                for handle in abort_handles {
+                    handle.abort();
+                }
+                let completed = self.completed.load(Ordering::SeqCst);
+                Err(interrupted(completed, self.sent, self.batch))
+            }
+        }
+    }
+}
+
+/// A batch request can be applied by Todoist, or fall back to single updates, after we stop waiting for it.
+/// So with batches only the updates that are confirmed are certain, the rest are unknown.
+fn interrupted(completed: usize, total: usize, batch: bool) -> Error {
+    let mut message =
+        format!("Interrupted, {completed} of {total} updates completed before aborting");
+    if batch && completed < total {
+        message.push_str(&format!(
+            ", the other {} may or may not have been applied",
+            total - completed
+        ));
+    }
+    Error::new("send_updates", &message)
+}
+
+/// Tasks whose content contains the pattern, ignoring case. With regex the pattern is used as a regular expression instead.
+pub async fn search(
+    config: &Config,
+    flag: Flag,
+    pattern: &str,
+    regex: bool,
+) -> Result<String, Error> {
+    let matcher = if regex {
+        let compiled = Regex::new(pattern).map_err(|e| {
+            Error::new("search", &format!("Invalid regex '{pattern}': {e}"))
+                .with_kind(ErrorKind::Parse)
+        })?;
+        Some(compiled)
+    } else {
+        None
+    };
+    let needle = pattern.to_lowercase();
+
+    let tasks = all_tasks(config, &flag)
+        .await?
+        .into_iter()
+        .filter(|task| match &matcher {
+            Some(regex) => regex.is_match(&task.content),
+            None => task.content.to_lowercase().contains(&needle),
+        })
+        .collect::<Vec<Task>>();
+
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!(
+            "No tasks in {flag} match {pattern}"
+        )));
+    }
+
+    let mut buffer = color::green_string(&format!("Tasks in {flag} matching {pattern}"));
+    buffer.push('\n');
+    for text in format_tasks(&tasks, config, FormatType::List, false).await? {
+        buffer.push('\n');
+        buffer.push_str(&text);
+    }
+    Ok(buffer)
+}
+
+/// Add the same comment to every task, i.e. to leave a note on everything in a filter
+pub async fn comment_all(
+    config: &Config,
+    flag: Flag,
+    text: &str,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let desc = format!("Comment on {} tasks?", tasks.len());
+    if !confirmed(config, &desc, input::COMMENT, tasks.len())? {
+        return Ok("Cancelled".into());
+    }
+    let handles = tasks::sort(tasks, config, sort, false)
+        .into_iter()
+        .map(|task| tasks::spawn_comment_task(config.clone(), task, text.to_string()))
+        .collect::<Vec<JoinHandle<()>>>();
+    let added = handles.len();
+    future::join_all(handles).await;
+
+    Ok(color::green_string(&format!(
+        "Added a comment to {added} tasks in {flag}"
+    )))
+}
+
+/// Swap the label `from` for `to` on every task that has it, leaving other tasks alone
+pub async fn rename_label(
+    config: &Config,
+    flag: Flag,
+    from: &str,
+    to: &str,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    let tasks = tasks::sort(tasks, config, sort, false);
+
+    let mut updates = Vec::new();
+    for task in tasks {
+        if !task.labels.iter().any(|label| label == from) {
+            continue;
+        }
+        let mut labels = Vec::new();
+        for label in &task.labels {
+            let label = if label == from { to } else { label.as_str() };
+            if !labels.iter().any(|l| l == label) {
+                labels.push(label.to_string());
+            }
+        }
+        updates.push((task, labels));
+    }
+
+    if updates.is_empty() {
+        return Ok(color::green_string(&format!(
+            "No tasks in {flag} have the label {from}"
+        )));
+    }
+    let renamed = updates.len();
+    let desc = format!("Rename {from} to {to} on {renamed} tasks?");
+    if !confirmed(config, &desc, input::RENAME, renamed)? {
+        return Ok("Cancelled".into());
+    }
+
+    let handles = updates
+        .into_iter()
+        .map(|(task, labels)| tasks::spawn_update_task_labels(config.clone(), task, labels))
+        .collect::<Vec<_>>();
+    future::join_all(handles).await;
+    Ok(color::green_string(&format!(
+        "Renamed {from} to {to} on {renamed} tasks in {flag}"
+    )))
+}
+
+/// A table of the number of tasks in each project, largest first, with a total at the bottom
+pub async fn summary(config: &Config) -> Result<String, Error> {
+    let projects = config.projects().await?;
+    if projects.is_empty() {
+        return Ok("No projects found".into());
+    }
+
+    let mut counts = project_task_counts(config, projects)
+        .await?
+        .into_iter()
+        .map(|(project, count)| (project.name, count))
+        .collect::<Vec<(String, usize)>>();
+    counts.sort_by(|(name_a, count_a), (name_b, count_b)| {
+        count_b.cmp(count_a).then_with(|| name_a.cmp(name_b))
+    });
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+
+    let mut buffer = String::new();
+    buffer.push_str(&color::green_string(
+        &"Projects".pad_to_width(PAD_WIDTH + 3),
+    ));
+    buffer.push_str(&color::green_string("# Tasks"));
+    for (name, count) in counts {
+        buffer.push_str(&format!("\n - {}{count}", name.pad_to_width(PAD_WIDTH)));
+    }
+    buffer.push_str(&format!(
+        "\n   {}{total}",
+        color::green_string(&"Total".pad_to_width(PAD_WIDTH))
+    ));
+    Ok(buffer)
+}
+
+/// Lists the projects in config without active tasks, then offers to delete them from Todoist and config.
+/// The inbox and projects with sub-projects are never offered, Todoist deletes sub-projects and their tasks along with the parent.
+pub async fn empty_projects(config: &Config) -> Result<String, Error> {
+    let projects = config.projects().await?;
+    if projects.is_empty() {
+        return Ok("No projects found".into());
+    }
+
+    let parent_ids = todoist::all_projects(config, None)
+        .await?
+        .into_iter()
+        .filter_map(|project| project.parent_id)
+        .collect::<HashSet<String>>();
+    let projects = projects
+        .into_iter()
+        .filter(|project| project.inbox_project != Some(true))
+        .filter(|project| !parent_ids.contains(&project.id))
+        .collect::<Vec<Project>>();
+
+    let empty = project_task_counts(config, projects)
+        .await?
+        .into_iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(project, _)| project)
+        .collect::<Vec<Project>>();
+    if empty.is_empty() {
+        return Ok(color::green_string("No empty projects"));
+    }
+
+    let names = empty
+        .iter()
+        .map(|project| format!(" - {}", project.name))
+        .collect::<Vec<String>>()
+        .join("\n");
+    println!("{}\n{names}", color::green_string("Projects without tasks"));
+
+    let desc = format!("Delete {} empty projects from Todoist?", empty.len());
+    let options = vec![input::CANCEL, input::DELETE];
+    if input::select(&desc, options, config.mock_select)? == input::CANCEL {
+        return Ok(format!("Found {} empty projects:\n{names}", empty.len()));
+    }
+
+    // Projects already deleted in Todoist are removed from config even when a later delete fails
+    let mut saved = config.reload().await?;
+    let mut deleted = 0;
+    let mut failure = None;
+    for project in &empty {
+        match todoist::delete_project(config, project, false).await {
+            Ok(_) => {
+                saved.remove_project(project);
+                deleted += 1;
+            }
+            Err(e) => {
+                failure = Some(e);
+                break;
+            }
+        }
+    }
+    saved.save().await?;
+
+    match failure {
+        None => Ok(color::green_string(&format!(
+            "Deleted {deleted} empty projects"
+        ))),
+        Some(e) => Err(Error::new(
+            "empty_projects",
+            &format!(
+                "Deleted {deleted} of {} empty projects before failing: {}",
+                empty.len(),
+                e.message
+            ),
+        )),
+    }
+}
+
+/// Number of active tasks in each project, fetched concurrently
+async fn project_task_counts(
+    config: &Config,
+    projects: Vec<Project>,
+) -> Result<Vec<(Project, usize)>, Error> {
+    let mut handles = Vec::new();
+    for project in projects {
+        let config = config.clone();
+        let handle = tokio::spawn(async move {
+            let count = todoist::all_tasks_by_project(&config, &project, None, false)
+                .await
+                .map(|tasks| tasks.len());
+            (project, count)
+        });
+        handles.push(handle);
+    }
+
+    let mut counts = Vec::new();
+    for result in future::join_all(handles).await {
+        let (project, count) = result?;
+        counts.push((project, count?));
+    }
+    Ok(counts)
+}
+
+/// A table of how many tasks carry each label, most used first. Tasks can have several labels so percentages can add up to more than 100.
+pub async fn label_stats(config: &Config, flag: Flag) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let counts = label_counts(&tasks);
+    let total = tasks.len();
+
+    let mut buffer = String::new();
+    buffer.push_str(&color::green_string(&"Labels".pad_to_width(PAD_WIDTH + 3)));
+    buffer.push_str(&color::green_string("# Tasks"));
+    for (label, count) in counts {
+        let percent = (count * 100 + total / 2) / total;
+        buffer.push_str(&format!(
+            "\n - {}{count} ({percent}%)",
+            label.pad_to_width(PAD_WIDTH)
+        ));
+    }
+    buffer.push_str(&format!(
+        "\n   {}{total}",
+        color::green_string(&"Total".pad_to_width(PAD_WIDTH))
+    ));
+    Ok(buffer)
+}
+
+/// A bar of # for each priority, highest first, sized by how many tasks have it
+pub async fn histogram(config: &Config, flag: Flag) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let mut buffer = color::green_string(&format!("Priorities for {flag}"));
+    for (priority, count) in priority_counts(&tasks) {
+        let bar = "#".repeat(bar_width(count, tasks.len()));
+        buffer.push_str(&format!(
+            "\n{}{} {count}",
+            priority.name().pad_to_width(PAD_WIDTH),
+            color::priority_string(&priority, &bar)
+        ));
+    }
+    Ok(buffer)
+}
+
+/// Number of tasks at each priority, highest first, including priorities with no tasks
+fn priority_counts(tasks: &[Task]) -> Vec<(Priority, usize)> {
+    [
+        Priority::High,
+        Priority::Medium,
+        Priority::Low,
+        Priority::None,
+    ]
+    .into_iter()
+    .map(|priority| {
+        let count = tasks
+            .iter()
+            .filter(|task| task.priority == priority)
+            .count();
+        (priority, count)
+    })
+    .collect()
+}
+
+/// Bars are one # per task until the total would not fit, then they are scaled down to HISTOGRAM_WIDTH
+fn bar_width(count: usize, total: usize) -> usize {
+    if total <= HISTOGRAM_WIDTH {
+        count
+    } else {
+        (count * HISTOGRAM_WIDTH).div_ceil(total)
+    }
+}
+
+/// Number of tasks with each label, sorted by count then name
+fn label_counts(tasks: &[Task]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for task in tasks {
+        if task.labels.is_empty() {
+            *counts.entry(UNLABELED.to_string()).or_default() += 1;
+        }
+        for label in &task.labels {
+            *counts.entry(label.clone()).or_default() += 1;
+        }
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|(label_a, count_a), (label_b, count_b)| {
+        count_b.cmp(count_a).then_with(|| label_a.cmp(label_b))
+    });
+    counts
+}
+
+/// Report tasks whose project is missing from config, which otherwise show up as "Project not in config"
+pub async fn verify(config: &Config, flag: Flag) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    let project_ids = config
+        .projects()
+        .await?
+        .into_iter()
+        .map(|project| project.id)
+        .collect::<HashSet<String>>();
+
+    let orphaned = tasks
+        .into_iter()
+        .filter(|task| !project_ids.contains(&task.project_id))
+        .collect::<Vec<Task>>();
+
+    if orphaned.is_empty() {
+        return Ok(color::green_string(&format!(
+            "All tasks in {flag} belong to a project in config"
+        )));
+    }
+
+    let mut buffer = color::red_string(&format!(
+        "Found {} tasks in {flag} with a project not in config",
+        orphaned.len()
+    ));
+    for task in orphaned {
+        buffer.push_str(&format!(
+            "\n - {} ({}) in project {}",
+            task.content, task.id, task.project_id
+        ));
+    }
+    let command = color::cyan_string("tod project import --auto");
+    buffer.push_str(&format!("\nUse {command} to import missing projects"));
+    Ok(buffer)
+}
+
+/// Prompts before acting on more than confirm_threshold tasks, smaller batches go ahead without asking
+fn confirmed(config: &Config, desc: &str, action: &str, num_tasks: usize) -> Result<bool, Error> {
+    if num_tasks <= config.confirm_threshold {
+        return Ok(true);
+    }
+    let options = vec![input::CANCEL, action];
+    Ok(input::select(desc, options, config.mock_select)? != input::CANCEL)
+}
+
+/// Complete every task after a single confirmation
+/// With pick, a checkbox prompt chooses which of the tasks to complete instead of confirming all of them.
+pub async fn complete_all(
+    config: &Config,
+    flag: Flag,
+    sort: &SortOrder,
+    pick: bool,
+) -> Result<OperationOutcome, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    let tasks = reject_subtasks_of_listed_parents(tasks);
+
+    if tasks.is_empty() {
+        return Ok(OperationOutcome::new(format!("No tasks for {flag}"), 0));
+    }
+
+    let tasks = tasks::sort(tasks, config, sort, false);
+    let tasks = if pick {
+        let tasks = input::multi_select(input::SELECT_TASKS, tasks, config.mock_select)?;
+        if tasks.is_empty() {
+            return Ok(OperationOutcome::new("No tasks selected".into(), 0));
+        }
+        tasks
+    } else {
+        for task in &tasks {
+            let text = task.fmt(Vec::new(), config, FormatType::List, true).await?;
+            print!("{text}");
+        }
+
+        let desc = format!("Complete {} tasks?", tasks.len());
+        if !confirmed(config, &desc, input::COMPLETE, tasks.len())? {
+            return Ok(OperationOutcome::new("Cancelled".into(), 0));
+        }
+        tasks
+    };
+
+    let num_tasks = tasks.len();
+
+    let undo_entries = tasks.iter().cloned().map(UndoEntry::Reopen).collect();
+    let handles = tasks
+        .into_iter()
+        .map(|task| tasks::spawn_complete_task(config.clone(), task))
+        .collect::<Vec<_>>();
+    future::join_all(handles).await;
+    save_undo_log(config, Some(undo_entries)).await?;
+
+    Ok(OperationOutcome::new(
+        format!("Completed {num_tasks} tasks in {flag}"),
+        num_tasks,
+    ))
+}
+
+/// Completes a single task when its id is already known, i.e. from `list view --show-ids`
+pub async fn close_by_id(config: &Config, id: &str) -> Result<String, Error> {
+    todoist::close_task_by_id(config, id).await?;
+    Ok(color::green_string(&format!("Closed task {id}")))
+}
+
+/// Merges tasks into the first id after a confirmation. Each other task's content, description and comments,
+/// with links to their attachments, are posted as a single comment on the first task and then the other task is
+/// completed. The comment names the merged task's id, so running the merge again after a failure only completes
+/// the tasks that were already copied.
+pub async fn merge(config: &Config, ids: &[String]) -> Result<String, Error> {
+    let [survivor_id, others @ ..] = ids else {
+        return Err(Error::new("merge", "Provide the ids of the tasks to merge"));
+    };
+    if others.contains(survivor_id) {
+        return Err(Error::new(
+            "merge",
+            &format!("Cannot merge task {survivor_id} into itself"),
+        ));
+    }
+    let mut seen = HashSet::new();
+    let others: Vec<&String> = others.iter().filter(|id| seen.insert(*id)).collect();
+    if others.is_empty() {
+        return Err(Error::new(
+            "merge",
+            "Provide at least two task ids to merge",
+        ));
+    }
+
+    // Every id is looked up before anything changes, so a typo stops the merge before it starts
+    let survivor = todoist::get_task(config, survivor_id).await?;
+    let mut tasks = Vec::new();
+    for id in others {
+        tasks.push(todoist::get_task(config, id).await?);
+    }
+    for task in &tasks {
+        let text = task.fmt(Vec::new(), config, FormatType::List, true).await?;
+        print!("{text}");
+    }
+    let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+    let desc = format!("Merge {} {noun} into {}?", tasks.len(), survivor.content);
+    if !confirmed(config, &desc, input::MERGE, tasks.len())? {
+        return Ok(color::green_string("Cancelled"));
+    }
+
+    let existing = todoist::all_comments(config, &survivor, None, None).await?;
+    for task in &tasks {
+        let heading = merge_heading(task);
+        if !existing
+            .iter()
+            .any(|comment| comment.content.starts_with(&heading))
+        {
+            let comments = todoist::all_comments(config, task, None, None).await?;
+            let note = merge_note(heading, task, &comments);
+            todoist::create_comment(config, &survivor, note, false).await?;
+        }
+        todoist::close_task_by_id(config, &task.id).await?;
+    }
+
+    Ok(color::green_string(&format!(
+        "Merged {} {noun} into {}",
+        tasks.len(),
+        survivor.content
+    )))
+}
+
+/// The first line of the comment a merged task leaves on the task it was merged into
+fn merge_heading(task: &Task) -> String {
+    format!("Merged from: {} ({})", task.content, task.id)
+}
+
+/// The merged task's description and comments as one comment, attachments become links
+fn merge_note(heading: String, task: &Task, comments: &[Comment]) -> String {
+    let mut note = heading;
+    if !task.description.is_empty() {
+        note.push('\n');
+        note.push_str(&task.description);
+    }
+    for comment in comments {
+        note.push_str("\n\n");
+        note.push_str(&comment.content);
+        if let Some(attachment) = &comment.file_attachment {
+            note.push_str(&format!("\nAttachment: {}", attachment.url()));
+        }
+    }
+    note
+}
+
+/// Reopens a task completed by mistake, i.e. during `list process`
+pub async fn reopen_by_id(config: &Config, id: &str) -> Result<String, Error> {
+    todoist::reopen_task_by_id(config, id).await?;
+    Ok(color::green_string(&format!("Reopened task {id}")))
+}
+
+/// Reverses the last complete-all or prioritize, reopening completed tasks and restoring previous priorities
+pub async fn undo(config: &Config) -> Result<String, Error> {
+    let entries = config.last_operation.clone().unwrap_or_default();
+    if entries.is_empty() {
+        return Ok(color::green_string("Nothing to undo"));
+    }
+
+    let num_tasks = entries.len();
+    let handles = entries
+        .into_iter()
+        .map(|entry| spawn_undo(config.clone(), entry))
+        .collect::<Vec<_>>();
+    future::join_all(handles).await;
+    save_undo_log(config, None).await?;
+
+    Ok(color::green_string(&format!(
+        "Undid changes to {num_tasks} tasks"
+    )))
+}
+
+/// Reverses a single undo entry inside another thread
+fn spawn_undo(config: Config, entry: UndoEntry) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let result = match &entry {
+            UndoEntry::Reopen(task) => todoist::reopen_task(&config, task, false).await,
+            UndoEntry::RestorePriority(task) => {
+                todoist::update_task_priority(&config, task, &task.priority, false).await
+            }
+        };
+        if let Err(e) = result {
+            config.tx().send(e).unwrap();
+        }
+    })
+}
+
+/// Replaces the undo log in the config file, only the most recent operation is kept
+async fn save_undo_log(config: &Config, entries: Option<Vec<UndoEntry>>) -> Result<String, Error> {
+    let mut config = config.reload().await?;
+    config.last_operation = entries;
+    config.save().await
+}
+
+/// Moves every task due before today to today after a single confirmation.
+/// Recurring tasks are left alone so that their schedule is not broken, and so are tasks
+/// whose time has passed earlier today, as moving them to today would only drop their time.
+pub async fn reschedule_overdue(
+    config: &Config,
+    flag: Flag,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag)
+        .await?
+        .into_iter()
+        .filter(|task| {
+            !task.is_recurring()
+                && tasks::is_overdue(task, config)
+                && !task.is_today(config).unwrap_or_default()
+        })
+        .collect::<Vec<Task>>();
+
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No overdue tasks for {flag}")));
+    }
+
+    let tasks = tasks::sort(tasks, config, sort, false);
+    for task in &tasks {
+        let text = task.fmt(Vec::new(), config, FormatType::List, true).await?;
+        print!("{text}");
+    }
+
+    let num_tasks = tasks.len();
+    let desc = format!("Reschedule {num_tasks} tasks to today?");
+    if !confirmed(config, &desc, input::RESCHEDULE, num_tasks)? {
+        return Ok("Cancelled".into());
+    }
+
+    let handles = tasks
+        .into_iter()
+        .map(|task| tasks::spawn_update_task_due(config.clone(), task, "today".into(), None))
+        .collect::<Vec<_>>();
+    future::join_all(handles).await;
+
+    Ok(color::green_string(&format!(
+        "Rescheduled {num_tasks} tasks in {flag} to today"
+    )))
+}
+
+/// Moves every task to the next Saturday in the config timezone. Recurring tasks are skipped so their schedule is kept.
+pub async fn postpone_to_weekend(
+    config: &Config,
+    flag: Flag,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag)
+        .await?
+        .into_iter()
+        .filter(|task| !task.is_recurring())
+        .collect::<Vec<Task>>();
+
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let saturday = time::next_saturday(config)?
+        .format(time::FORMAT_DATE)
+        .to_string();
+    let desc = format!("Postpone {} tasks to Saturday {saturday}?", tasks.len());
+    if !confirmed(config, &desc, input::POSTPONE, tasks.len())? {
+        return Ok("Cancelled".into());
+    }
+    let handles = tasks::sort(tasks, config, sort, false)
+        .into_iter()
+        .map(|task| tasks::spawn_update_task_due(config.clone(), task, saturday.clone(), None))
+        .collect::<Vec<_>>();
+    let num_tasks = handles.len();
+    future::join_all(handles).await;
+
+    Ok(color::green_string(&format!(
+        "Postponed {num_tasks} tasks in {flag} to Saturday {saturday}"
+    )))
+}
+
+/// Moves the due date of every task later by a relative amount such as "3 hours" or "2 days".
+/// Tasks without a due date are skipped.
+pub async fn snooze(
+    config: &Config,
+    flag: Flag,
+    delta: &str,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let duration = time::duration_from_str(delta)?;
+    let tasks = all_tasks(config, &flag).await?;
+
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let mut updates = Vec::new();
+    for task in tasks::sort(tasks, config, sort, false) {
+        match task.snoozed_due_string(duration, config)? {
+            Some(due_string) => updates.push((task, due_string)),
+            None if task.due.is_none() => println!("Skipped {task}, it has no due date"),
+            None => println!("Skipped {task}, it has no due time to snooze by {delta}"),
+        }
+    }
+    let num_tasks = updates.len();
+    let desc = format!("Snooze {num_tasks} tasks by {delta}?");
+    if !confirmed(config, &desc, input::SNOOZE, num_tasks)? {
+        return Ok("Cancelled".into());
+    }
+    let handles = updates
+        .into_iter()
+        .map(|(task, due_string)| {
+            tasks::spawn_update_task_due(config.clone(), task, due_string, None)
+        })
+        .collect::<Vec<_>>();
+    future::join_all(handles).await;
+
+    Ok(color::green_string(&format!(
+        "Snoozed {num_tasks} tasks in {flag} by {delta}"
+    )))
+}
+
+/// Gives every task the same duration, i.e. "30m" or "1 hour".
+/// Tasks that already have a duration are skipped unless overwrite is set.
+pub async fn set_all_durations(
+    config: &Config,
+    flag: Flag,
+    duration: &str,
+    sort: &SortOrder,
+    overwrite: bool,
+) -> Result<String, Error> {
+    let duration = tasks::Duration::try_from(time::duration_from_str(duration)?)?;
+
+    let tasks = all_tasks(config, &flag).await?;
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let (tasks, with_duration): (Vec<Task>, Vec<Task>) = tasks
+        .into_iter()
+        .partition(|task| overwrite || task.duration.is_none());
+    let desc = format!("Set a duration of {duration} on {} tasks?", tasks.len());
+    if !confirmed(config, &desc, input::SET_DURATION, tasks.len())? {
+        return Ok("Cancelled".into());
+    }
+    let handles = tasks::sort(tasks, config, sort, false)
+        .into_iter()
+        .map(|task| tasks::spawn_update_task_duration(config.clone(), task, duration.clone()))
+        .collect::<Vec<_>>();
+    let num_tasks = handles.len();
+    future::join_all(handles).await;
+
+    let skipped_note = match with_duration.len() {
+        0 => String::new(),
+        skipped => format!(", skipped {skipped} that already had one"),
+    };
+    Ok(color::green_string(&format!(
+        "Set a duration of {duration} on {num_tasks} tasks in {flag}{skipped_note}"
+    )))
+}
+
+/// Assigns every task to the same collaborator, found by name or email in the projects the tasks belong to
+pub async fn assign_all(
+    config: &Config,
+    flag: Flag,
+    assignee: &str,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let mut project_ids: Vec<&str> = tasks.iter().map(|task| task.project_id.as_str()).collect();
+    project_ids.sort_unstable();
+    project_ids.dedup();
+
+    let mut collaborator = None;
+    for project_id in project_ids {
+        let collaborators = todoist::all_collaborators(config, project_id, None).await?;
+        collaborator = collaborators.into_iter().find(|c| c.matches(assignee));
+        if collaborator.is_some() {
+            break;
+        }
+    }
+    let Some(collaborator) = collaborator else {
+        return Err(Error::new(
+            "assign_all",
+            &format!("No collaborator named '{assignee}' in the projects for {flag}"),
+        ));
+    };
+    let desc = format!("Assign {} tasks to {}?", tasks.len(), collaborator.name);
+    if !confirmed(config, &desc, input::ASSIGN, tasks.len())? {
+        return Ok("Cancelled".into());
+    }
+
+    let handles = tasks::sort(tasks, config, sort, false)
+        .into_iter()
+        .map(|task| {
+            let config = config.clone();
+            let collaborator_id = collaborator.id.clone();
+            tokio::spawn(async move {
+                if let Err(e) =
+                    todoist::update_task_assignee(&config, &task, &collaborator_id, false).await
+                {
+                    config.tx().send(e).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    let num_tasks = handles.len();
+    future::join_all(handles).await;
+
+    Ok(color::green_string(&format!(
+        "Assigned {num_tasks} tasks in {flag} to {}",
+        collaborator.name
+    )))
+}
+
+/// Moves every task into the section with this name, looked up in the projects the tasks belong to.
+/// Subtasks of listed parents and tasks already in the section are left alone.
+pub async fn move_to_section(
+    config: &Config,
+    flag: Flag,
+    section: &str,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {flag}")));
+    }
+
+    let projects = config
+        .projects()
+        .await?
+        .into_iter()
+        .filter(|project| tasks.iter().any(|task| task.project_id == project.id));
+    let mut found = None;
+    for project in projects {
+        found = todoist::all_sections_by_project(config, &project, None)
+            .await?
+            .into_iter()
+            .find(|s| s.name == section);
+        if found.is_some() {
+            break;
+        }
+    }
+    let Some(section) = found else {
+        return Err(Error::new(
+            "move_to_section",
+            &format!("Could not find section '{section}' for {flag}"),
+        ));
+    };
+
+    let tasks = tasks::sort(
+        reject_subtasks_of_listed_parents(tasks),
+        config,
+        sort,
+        false,
+    )
+    .into_iter()
+    .filter(|task| task.section_id.as_deref() != Some(section.id.as_str()))
+    .collect::<Vec<Task>>();
+    let num_tasks = tasks.len();
+    let desc = format!("Move {num_tasks} tasks to {}?", section.name);
+    if !confirmed(config, &desc, input::MOVE, num_tasks)? {
+        return Ok("Cancelled".into());
+    }
+    let handles = tasks
+        .into_iter()
+        .map(|task| {
+            let config = config.clone();
+            let section = section.clone();
+            tokio::spawn(async move {
+                if let Err(e) = todoist::move_task_to_section(&config, &task, &section, false).await
+                {
+                    config.tx().send(e).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    future::join_all(handles).await;
+
+    Ok(color::green_string(&format!(
+        "Moved {num_tasks} tasks in {flag} to {}",
+        section.name
+    )))
+}
+
+/// Move every task in a project to another project after a single confirmation.
+/// Only parent tasks are moved, Todoist brings their subtasks along so the hierarchy is kept.
+pub async fn move_all(
+    config: &Config,
+    from: &Project,
+    to: &Project,
+    sort: &SortOrder,
+) -> Result<String, Error> {
+    if from.id == to.id {
+        return Err(Error::new(
+            "move_all",
+            "Cannot move tasks to the project they are already in",
+        ));
+    }
+
+    let tasks = todoist::all_tasks_by_project(config, from, None, false).await?;
+
+    if tasks.is_empty() {
+        return Ok(color::green_string(&format!("No tasks for {from}")));
+    }
+
+    let num_tasks = tasks.len();
+    let tasks = tasks::sort(tasks, config, sort, false);
+    for task in &tasks {
+        let text = task
+            .fmt(Vec::new(), config, FormatType::List, false)
+            .await?;
+        print!("{text}");
+    }
+
+    let desc = format!("Move {num_tasks} tasks to {}?", to.name);
+    if !confirmed(config, &desc, input::MOVE, num_tasks)? {
+        return Ok("Cancelled".into());
+    }
+
+    let handles = reject_subtasks_of_listed_parents(tasks)
+        .into_iter()
+        .map(|task| {
+            let config = config.clone();
+            let to = to.clone();
+            tokio::spawn(async move {
+                if let Err(e) = todoist::move_task_to_project(&config, &task, &to, false).await {
+                    config.tx().send(e).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    future::join_all(handles).await;
+
+    Ok(color::green_string(&format!(
+        "Moved {num_tasks} tasks from {} to {}",
+        from.name, to.name
+    )))
+}
+
+/// Completing a parent task completes its subtasks, so subtasks are dropped when their parent is also in the list.
+/// Tasks returned by more than one filter are only kept once.
+fn reject_subtasks_of_listed_parents(tasks: Vec<Task>) -> Vec<Task> {
+    let mut unique: Vec<Task> = Vec::new();
+    for task in tasks {
+        if !unique.iter().any(|t| t.id == task.id) {
+            unique.push(task);
+        }
+    }
+
+    let ids = unique
+        .iter()
+        .map(|task| task.id.clone())
+        .collect::<Vec<String>>();
+
+    unique
+        .into_iter()
+        .filter(|task| {
+            !task
+                .parent_id
+                .as_ref()
+                .is_some_and(|parent_id| ids.contains(parent_id))
+        })
+        .collect()
+}
+
+/// Find tasks in the same project with the same content and close all but the one chosen to keep
+pub async fn deduplicate(config: &Config, flag: Flag) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+
+    let groups = group_duplicates(tasks);
+
+    if groups.is_empty() {
+        return Ok(color::green_string(&format!(
+            "No duplicate tasks for {flag}"
+        )));
+    }
+
+    let mut handles = Vec::new();
+    for group in groups {
+        println!();
+        for task in &group {
+            let text = task.fmt(Vec::new(), config, FormatType::List, true).await?;
+            print!("{text}");
+        }
+        let keep = input::select("Select the task to keep", group.clone(), config.mock_select)?;
+        for task in group.into_iter().filter(|task| task.id != keep.id) {
+            handles.push(tasks::spawn_complete_task(config.clone(), task));
+        }
+    }
+
+    let closed = handles.len();
+    future::join_all(handles).await;
+    Ok(color::green_string(&format!(
+        "Closed {closed} duplicate tasks in {flag}"
+    )))
+}
+
+/// Groups tasks that share a project and content, ignoring case and surrounding whitespace.
+/// Only groups with more than one task are returned, in the order they were first seen.
+fn group_duplicates(tasks: Vec<Task>) -> Vec<Vec<Task>> {
+    let mut groups: Vec<((String, String), Vec<Task>)> = Vec::new();
+
+    for task in tasks {
+        let key = (task.project_id.clone(), normalize_content(&task.content));
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            // The same task can be returned by more than one filter
+            Some((_, group)) if group.iter().any(|t| t.id == task.id) => (),
+            Some((_, group)) => group.push(task),
+            None => groups.push((key, vec![task])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, group)| group)
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+fn normalize_content(content: &str) -> String {
+    content.trim().to_lowercase()
+}
+
+/// Creates a task for each line of the file, asking first unless yes is set.
+/// Lines that fail are written to a .failed file next to it so they can be imported again.
+pub async fn import(config: &Config, file_path: &str, yes: bool) -> Result<String, Error> {
+    if file_path == STDIN_PATH {
+        import_from_reader(config, tokio::io::stdin(), yes, None).await
+    } else {
+        let failed_path = format!("{file_path}.failed");
+        let file = fs::File::open(file_path).await?;
+        import_from_reader(config, file, yes, Some(&failed_path)).await
+    }
+}
+
+/// Creates a task for each non-empty line read from the reader.
+/// A line that fails does not stop the rest, the error lists the line numbers that failed.
+async fn import_from_reader<R: AsyncRead + Unpin>(
+    config: &Config,
+    mut reader: R,
+    yes: bool,
+    failed_path: Option<&str>,
+) -> Result<String, Error> {
+    let mut lines = String::new();
+    reader.read_to_string(&mut lines).await?;
+
+    // lines() also drops the \r of CRLF line endings
+    let lines: Vec<(usize, String)> = lines
+        .lines()
+        .enumerate()
+        .map(|(index, s)| (index + 1, s.trim().to_owned()))
+        .filter(|(_, s)| !s.is_empty())
+        .collect();
+
+    if !yes {
+        let options = vec![input::CANCEL, input::CREATE];
+        let desc = format!("This will create {} tasks, continue?", lines.len());
+        if input::select(&desc, options, config.mock_select)? == input::CANCEL {
+            return Ok("Cancelled".into());
+        }
+    }
+
+    let mut failures = Vec::new();
+    for (position, (line_number, line)) in lines.iter().enumerate() {
+        let result = match parse_import_line(line) {
+            ImportLine::Plain(text) => {
+                let key = import_key(position, line);
+                todoist::quick_create_task(config, &text, None, Some(&key))
+                    .await
+                    .map(|_| ())
+            }
+            ImportLine::Annotated {
+                content,
+                priority,
+                labels,
+                description,
+            } => todoist::create_inbox_task(
+                config,
+                &content,
+                priority.as_ref(),
+                &labels,
+                description.as_deref(),
+            )
+            .await
+            .map(|_| ()),
+        };
+        if let Err(e) = result {
+            failures.push((*line_number, line.clone(), e));
+        }
+    }
+
+    if failures.is_empty() {
+        return Ok("✓".into());
+    }
+
+    let mut message = format!(
+        "Created {} of {} tasks, failed lines:",
+        lines.len() - failures.len(),
+        lines.len()
+    );
+    for (line_number, _, e) in &failures {
+        message.push_str(&format!("\n  {line_number}: {}", e.message));
+    }
+    if let Some(path) = failed_path {
+        let failed_lines: String = failures
+            .iter()
+            .map(|(_, line, _)| format!("{line}\n"))
+            .collect();
+        fs::write(path, failed_lines).await?;
+        message.push_str(&format!("\nFailed lines written to {path}"));
+    }
+    Err(Error::new("import", &message))
+}
+
+/// Idempotency key for an imported line, the same line at the same position always gives the same key.
+/// FNV-1a so that the key does not change between runs or Rust versions.
+fn import_key(position: usize, line: &str) -> String {
+    let hash = format!("{position}:{line}")
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    format!("tod-import-{hash:016x}")
+}
+
+/// A line from an import file, annotated lines look like "Buy milk | p1 | @shopping | desc: 2 litres"
+#[derive(Debug, PartialEq)]
+enum ImportLine {
+    /// Passed to quick add as is
+    Plain(String),
+    Annotated {
+        content: String,
+        priority: Option<Priority>,
+        labels: Vec<String>,
+        description: Option<String>,
+    },
+}
+
+/// Splits a line on " | " and reads pN tokens as priority, @label tokens as labels and a "desc:" segment as the description.
+/// Segments that are not annotations are kept as part of the content, a line without any annotations stays plain.
+fn parse_import_line(line: &str) -> ImportLine {
+    let mut segments = line.split(IMPORT_SEPARATOR);
+    let first = segments.next().unwrap_or_default();
+    let rest = segments.collect::<Vec<&str>>();
+
+    if rest.is_empty() {
+        return ImportLine::Plain(line.to_string());
+    }
+
+    let mut content = vec![first.trim()];
+    let mut priority = None;
+    let mut labels = Vec::new();
+    let mut description = None;
+    for segment in rest {
+        if let Some(text) = segment.trim().strip_prefix(IMPORT_DESCRIPTION_PREFIX) {
+            description = Some(text.trim().to_string());
+            continue;
+        }
+
+        let tokens = segment.split_whitespace().collect::<Vec<&str>>();
+        let is_annotation = !tokens.is_empty()
+            && tokens
+                .iter()
+                .all(|token| token.starts_with('@') || import_priority(token).is_some());
+
+        if !is_annotation {
+            content.push(segment.trim());
+            continue;
+        }
+
+        for token in tokens {
+            match token.strip_prefix('@') {
+                Some(label) => labels.push(label.to_string()),
+                None => priority = import_priority(token),
+            }
+        }
+    }
+
+    // Without annotations the separator is part of the text, quick add still parses dates and projects
+    if priority.is_none() && labels.is_empty() && description.is_none() {
+        return ImportLine::Plain(line.to_string());
+    }
+
+    ImportLine::Annotated {
+        content: content.join(IMPORT_SEPARATOR),
+        priority,
+        labels,
+        description,
+    }
+}
+
+/// Maps Todoist's p1 to p4 onto priorities, p1 being the highest
+fn import_priority(token: &str) -> Option<Priority> {
+    match token {
+        "p1" => Some(Priority::High),
+        "p2" => Some(Priority::Medium),
+        "p3" => Some(Priority::Low),
+        "p4" => Some(Priority::None),
+        _ => None,
+    }
+}
+
+/// Write tasks to a file, one per line in quick add syntax so that the file can be imported again
+pub async fn export(config: &Config, flag: Flag, file_path: &str) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+
+    let lines = tasks
+        .iter()
+        .map(|task| format!("{}\n", quick_add_line(task)))
+        .collect::<String>();
+
+    fs::write(file_path, lines).await?;
+
+    Ok(color::green_string(&format!(
+        "Exported {} tasks from {flag} to {file_path}",
+        tasks.len()
+    )))
+}
+
+/// Writes every task with its comments nested under it to a JSON file, for keeping a full backup.
+/// Fails if the comments of any task could not be fetched, so that a backup is never missing some.
+pub async fn backup(config: &Config, flag: Flag, file_path: &str) -> Result<String, Error> {
+    let tasks = all_tasks(config, &flag).await?;
+
+    let mut entries = Vec::new();
+    let mut num_comments = 0;
+    for (task, comments) in fetch_comments_for_tasks(tasks, config).await {
+        let comments = comments?;
+        num_comments += comments.len();
+        let mut entry = serde_json::to_value(&task)?;
+        entry["comments"] = serde_json::to_value(&comments)?;
+        entries.push(entry);
+    }
+    let num_tasks = entries.len();
+    let document = serde_json::json!({
+        "source": flag.query(),
+        "tasks": entries,
+    });
+
+    fs::write(file_path, serde_json::to_string_pretty(&document)?).await?;
+
+    Ok(color::green_string(&format!(
+        "Backed up {num_tasks} tasks and {num_comments} comments from {flag} to {file_path}"
+    )))
+}
+
+/// Content followed by the priority and due string, i.e. "Buy milk p1 tomorrow"
+fn quick_add_line(task: &Task) -> String {
+    let priority = task.priority.shorthand();
+    let due = task.due.as_ref().map(|due| due.string.as_str());
+
+    [Some(task.content.trim()), priority, due]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
     use crate::test;
     use crate::test::responses::ResponseFromFile;
     use pretty_assertions::assert_eq;
 
     #[tokio::test]
-    // Test importing the import_tasks.txt file creates 14 tasks
-    /// This file is used to test the import functionality
-    async fn test_import_creates_14_tasks() {
+    // Test importing the import_tasks.txt file creates 14 tasks
+    /// This file is used to test the import functionality
+    async fn test_import_creates_14_tasks() {
+        let mut server = mockito::Server::new_async().await;
+        // File to import and quantity specified here - expects 14 items
+        let import_file = "tests/inputs/import_tasks.txt";
+        let import_qty = 14;
+
+        // Expect 14 POSTs to /api/v1/tasks/quick
+        let mock = server
+            .mock("POST", "/api/v1/tasks/quick")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(import_qty)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        assert_eq!(
+            import(&config, import_file, true).await,
+            Ok(String::from("✓"))
+        );
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_import_from_reader() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/tasks/quick")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let buffer: &[u8] = b"Buy milk\n\nWalk the dog tomorrow\nCall mom p1\n";
+
+        assert_eq!(
+            import_from_reader(&config, buffer, true, None).await,
+            Ok(String::from("✓"))
+        );
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_import_cancelled_creates_nothing() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/tasks/quick")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(0)
+            .create_async()
+            .await;
+
+        // Cancel is the first option
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0);
+        let buffer: &[u8] = b"Buy milk\nWalk the dog tomorrow\n";
+
+        assert_eq!(
+            import_from_reader(&config, buffer, false, None).await,
+            Ok(String::from("Cancelled"))
+        );
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_import_annotated_tasks() {
+        let mut server = mockito::Server::new_async().await;
+
+        let quick_mock = server
+            .mock("POST", "/api/v1/tasks/quick")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let milk_mock = server
+            .mock("POST", "/api/v1/tasks/")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "content": "Buy milk",
+                "auto_reminder": true,
+                "labels": ["shopping"],
+                "priority": 4
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let rent_mock = server
+            .mock("POST", "/api/v1/tasks/")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "content": "Pay rent",
+                "auto_reminder": true,
+                "labels": ["bills", "home"],
+                "priority": 2
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let passport_mock = server
+            .mock("POST", "/api/v1/tasks/")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "content": "Renew passport",
+                "auto_reminder": true,
+                "labels": [],
+                "description": "Bring two photos"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let import_file = "tests/inputs/import_annotated_tasks.txt";
+
+        assert_eq!(
+            import(&config, import_file, true).await,
+            Ok(String::from("✓"))
+        );
+
+        quick_mock.assert();
+        milk_mock.assert();
+        rent_mock.assert();
+        passport_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_import_crlf_line_endings() {
+        let mut server = mockito::Server::new_async().await;
+
+        let mut quick_mocks = Vec::new();
+        for text in ["Buy milk", "Call mom"] {
+            let mock = server
+                .mock("POST", "/api/v1/tasks/quick")
+                .match_body(mockito::Matcher::Json(serde_json::json!({
+                    "text": text,
+                    "auto_reminder": true,
+                    "reminder": null
+                })))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(ResponseFromFile::TodayTask.read().await)
+                .expect(1)
+                .create_async()
+                .await;
+            quick_mocks.push(mock);
+        }
+
+        let rent_mock = server
+            .mock("POST", "/api/v1/tasks/")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "content": "Pay rent",
+                "auto_reminder": true,
+                "labels": ["bills"],
+                "priority": 2
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let import_file = "tests/inputs/import_tasks_crlf.txt";
+
+        assert_eq!(
+            import(&config, import_file, true).await,
+            Ok(String::from("✓"))
+        );
+
+        for mock in quick_mocks {
+            mock.assert();
+        }
+        rent_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_import_continues_past_failed_line() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server
+            .mock("POST", "/api/v1/tasks/quick")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"text": "Walk the dog tomorrow"}),
+            ))
+            .with_status(500)
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        let succeeding = server
+            .mock("POST", "/api/v1/tasks/quick")
+            .match_body(mockito::Matcher::Regex("Buy milk|Call mom".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let dir = std::env::temp_dir().join(format!("tod-import-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let import_file = dir.join("tasks.txt");
+        std::fs::write(
+            &import_file,
+            "Buy milk\n\nWalk the dog tomorrow\nCall mom\n",
+        )
+        .unwrap();
+        let import_file = import_file.to_str().unwrap();
+
+        let error = import(&config, import_file, true).await.unwrap_err();
+
+        assert!(
+            error
+                .message
+                .starts_with("Created 2 of 3 tasks, failed lines:\n  3: ")
+        );
+        let failed_path = format!("{import_file}.failed");
+        assert!(
+            error
+                .message
+                .ends_with(&format!("Failed lines written to {failed_path}"))
+        );
+        assert_eq!(
+            std::fs::read_to_string(&failed_path).unwrap(),
+            "Walk the dog tomorrow\n"
+        );
+        failing.assert();
+        succeeding.assert();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_import_key() {
+        assert_eq!(import_key(0, "Buy milk"), import_key(0, "Buy milk"));
+        assert_ne!(import_key(0, "Buy milk"), import_key(1, "Buy milk"));
+        assert_ne!(import_key(0, "Buy milk"), import_key(0, "Buy bread"));
+        assert!(import_key(0, "Buy milk").starts_with("tod-import-"));
+    }
+
+    #[test]
+    fn test_progress() {
+        assert_eq!(progress(14, 14), Some(String::from("[1/14]")));
+        assert_eq!(progress(14, 12), Some(String::from("[3/14]")));
+        assert_eq!(progress(14, 1), Some(String::from("[14/14]")));
+        assert_eq!(progress(1, 1), None);
+    }
+
+    #[test]
+    fn test_parse_import_line() {
+        assert_eq!(
+            parse_import_line("Buy milk tomorrow p1"),
+            ImportLine::Plain(String::from("Buy milk tomorrow p1"))
+        );
+        assert_eq!(
+            parse_import_line("Buy milk | p1 | @shopping"),
+            ImportLine::Annotated {
+                content: String::from("Buy milk"),
+                priority: Some(Priority::High),
+                labels: vec![String::from("shopping")],
+                description: None,
+            }
+        );
+        assert_eq!(
+            parse_import_line("Read | chapter 3 | @books p4"),
+            ImportLine::Annotated {
+                content: String::from("Read | chapter 3"),
+                priority: Some(Priority::None),
+                labels: vec![String::from("books")],
+                description: None,
+            }
+        );
+        assert_eq!(
+            parse_import_line("Call mom tomorrow | 5pm #family"),
+            ImportLine::Plain(String::from("Call mom tomorrow | 5pm #family"))
+        );
+        assert_eq!(
+            parse_import_line("Renew passport | p2 | desc: Bring two photos"),
+            ImportLine::Annotated {
+                content: String::from("Renew passport"),
+                priority: Some(Priority::Medium),
+                labels: Vec::new(),
+                description: Some(String::from("Bring two photos")),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prioritize() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+        let mock2 = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1)
+            .create()
+            .await
+            .unwrap();
+
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+        let result = prioritize(
+            &config,
+            Flag::Filter(filter),
+            sort,
+            false,
+            None,
+            false,
+            None,
+        )
+        .await;
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from("Successfully prioritized 'today'"),
+                changed: 1,
+            })
+        );
+        mock.assert();
+        mock2.assert();
+    }
+    #[tokio::test]
+    async fn test_prioritize_undo_skips_unchanged_tasks() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .create_async()
+            .await;
+        let changed_mock = server
+            .mock(
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(7Yrhw5dxhxkXh0x9|8Zsix6eyiylYi1y0)$".into(),
+                ),
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let unchanged_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .expect(0)
+            .create_async()
+            .await;
+
+        // Every task is set to medium, which the first task already has
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(2)
+            .create()
+            .await
+            .unwrap();
+
+        let result = prioritize(
+            &config,
+            Flag::Filter(String::from("today")),
+            &SortOrder::Value,
+            false,
+            None,
+            false,
+            None,
+        )
+        .await;
+        assert_eq!(result.map(|outcome| outcome.changed), Ok(2));
+
+        let entries = config.reload().await.unwrap().last_operation.unwrap();
+        let ids = entries
+            .iter()
+            .map(|entry| match entry {
+                UndoEntry::RestorePriority(task) => task.id.as_str(),
+                UndoEntry::Reopen(task) => panic!("Unexpected reopen of {}", task.id),
+            })
+            .collect::<HashSet<&str>>();
+        assert_eq!(ids, HashSet::from(["7Yrhw5dxhxkXh0x9", "8Zsix6eyiylYi1y0"]));
+        mock.assert();
+        changed_mock.assert();
+        unchanged_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_schedule_only_undated_tasks() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::UndatedTasks.read().await)
+            .create_async()
+            .await;
+        let undated = server
+            .mock(
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(6Xqhv4cwxgjwG9w8|7Yrhw5dxhxkXh0x9)$".into(),
+                ),
+            )
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "due_string": "tomorrow"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let dated = server
+            .mock("POST", "/api/v1/tasks/8Zsix6eyiylYi1y0")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0)
+            .with_mock_string("tomorrow");
+
+        let result = schedule(&config, Flag::Filter("today".into()), &SortOrder::Value).await;
+        assert_eq!(result, Ok(String::from("Scheduled 2 tasks in 'today'")));
+        mock.assert();
+        undated.assert();
+        dated.assert();
+
+        let config = config.mock_select(2);
+        let result = schedule(&config, Flag::Filter("today".into()), &SortOrder::Value).await;
+        assert_eq!(result, Ok(String::from("Exited")));
+    }
+
+    #[tokio::test]
+    async fn test_set_all_durations() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedDurationTasks.read().await)
+            .create_async()
+            .await;
+        let without_duration = server
+            .mock(
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(6Xqhv4cwxgjwG9w8|7Yrhw5dxhxkXh0x9)$".into(),
+                ),
+            )
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "duration": 30,
+                "duration_unit": "minute"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let with_duration = server
+            .mock("POST", "/api/v1/tasks/8Zsix6eyiylYi1y0")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+
+        let result = set_all_durations(
+            &config,
+            Flag::Filter("today".into()),
+            "30m",
+            &SortOrder::Value,
+            false,
+        )
+        .await;
+        assert_eq!(
+            result,
+            Ok(String::from(
+                "Set a duration of 30 minutes on 2 tasks in 'today', skipped 1 that already had one"
+            ))
+        );
+        mock.assert();
+        without_duration.assert();
+        with_duration.assert();
+    }
+
+    #[tokio::test]
+    async fn test_next_shows_one_task() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .create_async()
+            .await;
+        let comments = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=8Zsix6eyiylYi1y0&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"results": [], "next_cursor": null}"#)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver")
+            .with_mock_url(server.url());
+
+        let output = next(&config, Flag::Filter("today".into()), &SortOrder::Value)
+            .await
+            .unwrap();
+
+        assert!(output.contains("High priority"));
+        assert!(!output.contains("Medium priority"));
+        assert!(!output.contains("No priority"));
+        mock.assert();
+        comments.assert();
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_unknown_projects() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::OrphanedTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let output = verify(&config, Flag::Filter("today".into())).await.unwrap();
+
+        assert_eq!(
+            output,
+            "Found 1 tasks in 'today' with a project not in config\n - Orphaned task (7Yrhw5dxhxkXh0x9) in project 6VRRxv8CM6GVmmgf\nUse tod project import --auto to import missing projects"
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_undo_restores_priority() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+        let prioritize_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(serde_json::json!({"priority": 2})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+        let restore_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(serde_json::json!({"priority": 3})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1)
+            .create()
+            .await
+            .unwrap();
+
+        let filter = String::from("today");
+        prioritize(
+            &config,
+            Flag::Filter(filter),
+            &SortOrder::Value,
+            false,
+            None,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let config = config.reload().await.unwrap();
+        assert_eq!(
+            undo(&config).await,
+            Ok(String::from("Undid changes to 1 tasks"))
+        );
+
+        let config = config.reload().await.unwrap();
+        assert_eq!(undo(&config).await, Ok(String::from("Nothing to undo")));
+        mock.assert();
+        prioritize_mock.assert();
+        restore_mock.assert();
+    }
+    #[tokio::test]
+    async fn test_timebox() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasksWithoutDuration.read().await)
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("POST", "/api/v1/tasks/999999")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .create_async()
+            .await;
+
+        let mock3 = server
+            .mock("GET", "/api/v1/id_mappings/projects/123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::Ids.read().await)
+            .create_async()
+            .await;
+
+        let mock4 = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1)
+            .with_mock_string("tod")
+            .create()
+            .await
+            .unwrap();
+
+        let binding = config.projects().await.unwrap();
+        let project = binding.first().unwrap().to_owned();
+        let sort = &SortOrder::Value;
+        let result = timebox(&config, Flag::Project(project), sort, false, false, None).await;
+        assert_matches!(result, Ok(x) if x.message.contains("Successfully timeboxed") && x.changed == 1);
+
+        let config = config.mock_select(2);
+
+        let binding = config.projects().await.unwrap();
+        let project = binding.first().unwrap().to_owned();
+        let result = timebox(&config, Flag::Project(project), sort, false, false, None).await;
+        assert_matches!(result, Ok(x) if x.message.contains("Successfully timeboxed"));
+
+        let config = config.mock_select(3);
+
+        let binding = config.projects().await.unwrap();
+        let project = binding.first().unwrap().to_owned();
+        let result = timebox(
+            &config,
+            Flag::Project(project.clone()),
+            sort,
+            false,
+            false,
+            None,
+        )
+        .await;
+        assert_matches!(result, Ok(x) if x.message.contains("Successfully timeboxed"));
+
+        let result = timebox(&config, Flag::Project(project), sort, false, false, None).await;
+        assert_matches!(result, Ok(x) if x.message.contains("Successfully timeboxed"));
+        mock.expect(2);
+        mock2.expect(2);
+        mock3.expect(1);
+        mock4.expect(1);
+    }
+
+    #[tokio::test]
+    async fn test_update_sender_sends_batches_before_finishing() {
+        let mut server = mockito::Server::new_async().await;
+        let sync_mock = server
+            .mock("POST", "/api/v1/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(sync_ok)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let task = test::fixtures::today_task().await;
+        let mut sender = UpdateSender::new(&config, BATCH_THRESHOLD * 2);
+        for _ in 0..BATCH_THRESHOLD + 1 {
+            sender.push(TaskUpdate::Priority(task.clone(), Priority::High));
+        }
+
+        // The first full batch is already on its way, only the last update waits for finish
+        assert_eq!(sender.sent, BATCH_THRESHOLD);
+        assert_eq!(sender.pending.len(), 1);
+        for handle in sender.handles.drain(..) {
+            handle.await.unwrap();
+        }
+        assert_eq!(sender.completed.load(Ordering::SeqCst), BATCH_THRESHOLD);
+
+        assert_eq!(sender.finish().await, Ok(()));
+        sync_mock.assert();
+    }
+
+    /// Answers the sync request for every command with ok
+    fn sync_ok(request: &mockito::Request) -> Vec<u8> {
+        let body: serde_json::Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+        let sync_status = body["commands"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|command| {
+                let uuid = command["uuid"].as_str().unwrap().to_string();
+                (uuid, serde_json::json!("ok"))
+            })
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+        serde_json::json!({ "sync_status": sync_status })
+            .to_string()
+            .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_send_updates_reports_partial_completion_when_aborted() {
+        let mut server = mockito::Server::new_async().await;
+        let fast = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .with_status(200)
+            .with_body(ResponseFromFile::Task.read().await)
+            .create_async()
+            .await;
+        // The slow update only gets its response once the test is over
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let released = std::sync::Mutex::new(released);
+        let _slow = server
+            .mock("POST", "/api/v1/tasks/7Yrhw5dxhxkXh0x9")
+            .with_status(200)
+            .with_chunked_body(move |writer| {
+                let _ = released.lock().unwrap().recv();
+                writer.write_all(b"{}")
+            })
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let task = test::fixtures::today_task().await;
+        let mut sender = UpdateSender::new(&config, 2);
+        for id in ["6Xqhv4cwxgjwG9w8", "7Yrhw5dxhxkXh0x9"] {
+            let task = Task {
+                id: id.to_string(),
+                ..task.clone()
+            };
+            sender.push(TaskUpdate::Priority(task, Priority::High));
+        }
+
+        // Aborts as soon as the fast update has completed
+        let (abort_tx, abort_rx) = tokio::sync::oneshot::channel::<()>();
+        let completed = sender.completed.clone();
+        tokio::spawn(async move {
+            while completed.load(Ordering::SeqCst) == 0 {
+                tokio::task::yield_now().await;
+            }
+            let _ = abort_tx.send(());
+        });
+        let abort = async {
+            let _ = abort_rx.await;
+        };
+
+        let result = sender.finish_until(abort).await;
+        drop(release);
+
+        assert_eq!(
+            result,
+            Err(Error::new(
+                "send_updates",
+                "Interrupted, 1 of 2 updates completed before aborting"
+            ))
+        );
+        fast.assert();
+    }
+
+    #[tokio::test]
+    async fn test_send_updates_reports_unknown_batch_when_aborted() {
+        let mut server = mockito::Server::new_async().await;
+        let (hit_tx, hit_rx) = tokio::sync::oneshot::channel::<()>();
+        let hit_tx = std::sync::Mutex::new(Some(hit_tx));
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let released = std::sync::Mutex::new(released);
+        let sync_mock = server
+            .mock("POST", "/api/v1/sync")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(move |request| {
+                if let Some(hit_tx) = hit_tx.lock().unwrap().take() {
+                    let _ = hit_tx.send(());
+                }
+                let _ = released.lock().unwrap().recv();
+                sync_ok(request)
+            })
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let task = test::fixtures::today_task().await;
+        let mut sender = UpdateSender::new(&config, BATCH_THRESHOLD);
+        for _ in 0..BATCH_THRESHOLD {
+            sender.push(TaskUpdate::Priority(task.clone(), Priority::High));
+        }
+
+        // Aborts once Todoist has the batch, but before it has answered
+        let abort = async {
+            let _ = hit_rx.await;
+        };
+        let result = sender.finish_until(abort).await;
+        drop(release);
+
+        assert_eq!(
+            result,
+            Err(Error::new(
+                "send_updates",
+                &format!(
+                    "Interrupted, 0 of {BATCH_THRESHOLD} updates completed before aborting, the other {BATCH_THRESHOLD} may or may not have been applied"
+                )
+            ))
+        );
+        sync_mock.assert();
+    }
+
+    /// Mocks the task to keep and a task to merge into it, whose second comment has a file attached.
+    /// The merged task's comments are only fetched when they are copied.
+    async fn merge_mocks(
+        server: &mut mockito::ServerGuard,
+        survivor_comments: &str,
+        copies: usize,
+    ) -> Vec<mockito::Mock> {
+        let mut other =
+            serde_json::from_str::<serde_json::Value>(&ResponseFromFile::Task.read().await)
+                .unwrap();
+        other["id"] = "7Yrhw5dxhxkXh0x9".into();
+        other["content"] = "Take out recycling".into();
+        other["description"] = "Blue bin".into();
+        let mut comments = serde_json::from_str::<serde_json::Value>(
+            &ResponseFromFile::CommentsMultipleAuthors.read().await,
+        )
+        .unwrap();
+        comments["results"][1]["file_attachment"] = serde_json::json!({
+            "file_name": "keys.jpg",
+            "file_type": "image/jpeg",
+            "file_url": "https://example.com/keys.jpg",
+            "resource_type": "file"
+        });
+
+        let survivor_mock = server
+            .mock("GET", "/api/v1/tasks/999999")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::Task.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+        let other_mock = server
+            .mock("GET", "/api/v1/tasks/7Yrhw5dxhxkXh0x9")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(other.to_string())
+            .expect(1)
+            .create_async()
+            .await;
+        let survivor_comments_mock = server
+            .mock("GET", "/api/v1/comments/?task_id=999999&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(survivor_comments)
+            .expect(1)
+            .create_async()
+            .await;
+        let comments_mock = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=7Yrhw5dxhxkXh0x9&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(comments.to_string())
+            .expect(copies)
+            .create_async()
+            .await;
+        let survivor_close_mock = server
+            .mock("POST", "/api/v1/tasks/999999/close")
+            .expect(0)
+            .create_async()
+            .await;
+        vec![
+            survivor_mock,
+            other_mock,
+            survivor_comments_mock,
+            comments_mock,
+            survivor_close_mock,
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_merge() {
+        let mut server = mockito::Server::new_async().await;
+        let mocks = merge_mocks(&mut server, r#"{"results": [], "next_cursor": null}"#, 1).await;
+        // The deleted comment is left out and the attachment becomes a link
+        let note_mock = server
+            .mock("POST", "/api/v1/comments/")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "task_id": "999999",
+                "content": "Merged from: Take out recycling (7Yrhw5dxhxkXh0x9)\nBlue bin\n\n\
+                    Picked up the keys\n\n\
+                    Left them at the front desk\nAttachment: https://example.com/keys.jpg\n\n\
+                    Thanks!"
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::Comment.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+        let close_mock = server
+            .mock("POST", "/api/v1/tasks/7Yrhw5dxhxkXh0x9/close")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+        // A repeated id is only merged once
+        let ids = vec![
+            String::from("999999"),
+            String::from("7Yrhw5dxhxkXh0x9"),
+            String::from("7Yrhw5dxhxkXh0x9"),
+        ];
+
+        assert_eq!(
+            merge(&config, &ids).await,
+            Ok(String::from("Merged 1 task into Put out recycling"))
+        );
+        note_mock.assert();
+        close_mock.assert();
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_only_closes_tasks_already_copied() {
+        let mut server = mockito::Server::new_async().await;
+        // A previous run posted the comment but failed before closing the task
+        let survivor_comments = serde_json::json!({
+            "results": [{
+                "id": "1",
+                "posted_uid": null,
+                "content": "Merged from: Take out recycling (7Yrhw5dxhxkXh0x9)\nBlue bin",
+                "uids_to_notify": null,
+                "is_deleted": false,
+                "posted_at": "2025-05-10T10:00:00.000000Z",
+                "reactions": null,
+                "item_id": "999999",
+                "file_attachment": null
+            }],
+            "next_cursor": null
+        });
+        let mocks = merge_mocks(&mut server, &survivor_comments.to_string(), 0).await;
+        let note_mock = server
+            .mock("POST", "/api/v1/comments/")
+            .expect(0)
+            .create_async()
+            .await;
+        let close_mock = server
+            .mock("POST", "/api/v1/tasks/7Yrhw5dxhxkXh0x9/close")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+        let ids = vec![String::from("999999"), String::from("7Yrhw5dxhxkXh0x9")];
+
+        assert_eq!(
+            merge(&config, &ids).await,
+            Ok(String::from("Merged 1 task into Put out recycling"))
+        );
+        note_mock.assert();
+        close_mock.assert();
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_cancelled() {
+        let mut server = mockito::Server::new_async().await;
+        let survivor_mock = server
+            .mock("GET", "/api/v1/tasks/999999")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::Task.read().await)
+            .create_async()
+            .await;
+        let other_mock = server
+            .mock("GET", "/api/v1/tasks/7Yrhw5dxhxkXh0x9")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::Task.read().await)
+            .create_async()
+            .await;
+        let changes = server
+            .mock("POST", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0);
+        let ids = vec![String::from("999999"), String::from("7Yrhw5dxhxkXh0x9")];
+
+        assert_eq!(merge(&config, &ids).await, Ok(String::from("Cancelled")));
+        survivor_mock.assert();
+        other_mock.assert();
+        changes.assert();
+    }
+
+    #[tokio::test]
+    async fn test_merge_needs_two_distinct_ids() {
+        let config = test::fixtures::config().await;
+
+        assert!(merge(&config, &[String::from("1")]).await.is_err());
+        let same = [String::from("1"), String::from("1")];
+        assert!(merge(&config, &same).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prioritize_tasks_with_no_tasks() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let binding = config.projects().await.unwrap();
+        let project = binding.first().unwrap().to_owned();
+        let sort = &SortOrder::Value;
+
+        let result = prioritize(
+            &config,
+            Flag::Project(project),
+            sort,
+            false,
+            None,
+            false,
+            None,
+        )
+        .await;
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from(
+                    "No tasks with priority NONE (P4) for myproject\nhttps://app.todoist.com/app/project/123"
+                ),
+                changed: 0,
+            })
+        );
+        mock.assert();
+    }
+    #[tokio::test]
+    async fn test_process_with_filter() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .create_async()
+            .await;
+
+        let mock3 = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .create_async()
+            .await;
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0)
+            .create()
+            .await
+            .unwrap();
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+
+        let result = process(
+            &config,
+            Flag::Filter(filter),
+            sort,
+            false,
+            None,
+            false,
+            None,
+        )
+        .await;
+        assert_eq!(result, Ok("Successfully processed 'today'".to_string()));
+        mock.assert();
+        mock2.assert();
+        mock3.assert();
+    }
+
+    #[tokio::test]
+    async fn test_process_skipped_task_comes_up_once() {
+        let mut server = mockito::Server::new_async().await;
+        let task =
+            serde_json::from_str::<serde_json::Value>(&ResponseFromFile::TodayTask.read().await)
+                .unwrap();
+        let body = serde_json::json!({"results": [task, task], "next_cursor": null}).to_string();
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .expect(2)
+            .create_async()
+            .await;
+        let comments_mock = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"results": [], "next_cursor": null}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        // Skip
+        let mut config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+        config.remember_skipped = true;
+        let config = config.create().await.unwrap();
+        let flag = Flag::Filter(String::from("today"));
+        let sort = &SortOrder::Value;
+
+        let result = process(&config, flag.clone(), sort, false, None, false, None).await;
+        assert_eq!(
+            result,
+            Ok(String::from(
+                "Successfully processed 'today'\nSkipped 1 task"
+            ))
+        );
+
+        // A later run the same day leaves the skipped task out
+        let config = config.reload().await.unwrap();
+        let result = process(&config, flag, sort, false, None, false, None).await;
+        assert_eq!(result, Ok(String::from("No tasks for 'today'")));
+        mock.assert();
+        comments_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_process_with_focus_minutes() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut mocks = Vec::new();
+        for id in ["6Xqhv4cwxgjwG9w8", "7Yrhw5dxhxkXh0x9", "8Zsix6eyiylYi1y0"] {
+            let close_mock = server
+                .mock("POST", format!("/api/v1/tasks/{id}/close").as_str())
+                .with_status(204)
+                .expect(1)
+                .create_async()
+                .await;
+            let comments_mock = server
+                .mock(
+                    "GET",
+                    format!("/api/v1/comments/?task_id={id}&limit=200").as_str(),
+                )
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"results": [], "next_cursor": null}"#)
+                .create_async()
+                .await;
+            mocks.push(close_mock);
+            mocks.push(comments_mock);
+        }
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0)
+            .create()
+            .await
+            .unwrap();
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+
+        let result = process(
+            &config,
+            Flag::Filter(filter),
+            sort,
+            false,
+            None,
+            false,
+            Some(Duration::from_millis(1)),
+        )
+        .await;
+        assert_eq!(result, Ok("Successfully processed 'today'".to_string()));
+        mock.assert();
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_skips_focus_break_without_completing() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .create_async()
+            .await;
+        let comments_mock = server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex(r"^/api/v1/comments/\?task_id=".into()),
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"results": [], "next_cursor": null}"#)
+            .expect(3)
+            .create_async()
+            .await;
+        // Selects skip for every task
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1)
+            .create()
+            .await
+            .unwrap();
+
+        // A break after a skip would outlast the timeout
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            process(
+                &config,
+                Flag::Filter(String::from("today")),
+                &SortOrder::Value,
+                false,
+                None,
+                false,
+                Some(Duration::from_secs(3600)),
+            ),
+        )
+        .await
+        .expect("Skipping a task should not start a focus break");
+        assert_eq!(
+            result,
+            Ok(String::from(
+                "Successfully processed 'today'\nSkipped 3 tasks"
+            ))
+        );
+        mock.assert();
+        comments_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_process_does_not_wait_for_slow_comments() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut mocks = Vec::new();
+        for id in ["6Xqhv4cwxgjwG9w8", "7Yrhw5dxhxkXh0x9", "8Zsix6eyiylYi1y0"] {
+            let close_mock = server
+                .mock("POST", format!("/api/v1/tasks/{id}/close").as_str())
+                .with_status(204)
+                .expect(1)
+                .create_async()
+                .await;
+            mocks.push(close_mock);
+        }
+        // Sorted by value the order is 8Z, 6X, 7Y. The first task's comments are only sent after the second's,
+        // and the last task's comments are held back until released
+        let (second_sent, second_received) = std::sync::mpsc::channel::<()>();
+        let second_received = std::sync::Mutex::new(second_received);
+        let first_comments = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=8Zsix6eyiylYi1y0&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_chunked_body(move |writer| {
+                second_received.lock().unwrap().recv().unwrap();
+                writer.write_all(br#"{"results": [], "next_cursor": null}"#)
+            })
+            .expect(2)
+            .create_async()
+            .await;
+        let second_comments = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_chunked_body(move |writer| {
+                writer.write_all(br#"{"results": [], "next_cursor": null}"#)?;
+                writer.flush()?;
+                second_sent.send(()).unwrap();
+                Ok(())
+            })
+            .expect(2)
+            .create_async()
+            .await;
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let released = std::sync::Mutex::new(released);
+        let last_comments = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=7Yrhw5dxhxkXh0x9&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_chunked_body(move |writer| {
+                released.lock().unwrap().recv().unwrap();
+                writer.write_all(br#"{"results": [], "next_cursor": null}"#)
+            })
+            .expect(2)
+            .create_async()
+            .await;
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0)
+            .create()
+            .await
+            .unwrap();
+
+        let flag = Flag::Filter(String::from("today"));
+        let tasks = tasks::sort(
+            all_tasks(&config, &flag).await.unwrap(),
+            &config,
+            &SortOrder::Value,
+            false,
+        );
+        let mut tasks_with_comments = comments_as_ready(tasks, &config);
+        let mut next_id = async || tasks_with_comments.next().await.unwrap().0.id;
+        assert_eq!(next_id().await, "8Zsix6eyiylYi1y0");
+        assert_eq!(next_id().await, "6Xqhv4cwxgjwG9w8");
+        release.send(()).unwrap();
+        assert_eq!(next_id().await, "7Yrhw5dxhxkXh0x9");
+
+        release.send(()).unwrap();
+        let result = process(&config, flag, &SortOrder::Value, false, None, false, None).await;
+
+        assert_eq!(result, Ok("Successfully processed 'today'".to_string()));
+        mock.assert();
+        first_comments.assert();
+        second_comments.assert();
+        last_comments.assert();
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_with_project() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .create_async()
+            .await;
+
+        let mock3 = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0)
+            .create()
+            .await
+            .unwrap();
+
+        let binding = config.projects().await.unwrap();
+        let project = binding.first().unwrap().to_owned();
+        let sort = &SortOrder::Value;
+
+        let result = process(
+            &config,
+            Flag::Project(project),
+            sort,
+            false,
+            None,
+            false,
+            None,
+        )
+        .await;
+        assert_eq!(
+            result,
+            Ok(
+                "Successfully processed myproject\nhttps://app.todoist.com/app/project/123"
+                    .to_string()
+            )
+        );
+        mock.assert();
+        mock2.assert();
+        mock3.assert();
+    }
+    #[tokio::test]
+    async fn test_label() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let config_dir = dirs::config_dir().expect("Could not find config directory");
+
+        let config_with_timezone = config
+            .with_timezone("US/Pacific")
+            .with_path(config_dir.join("test3"))
+            .with_mock_url(server.url())
+            .mock_select(0);
+
+        config_with_timezone.clone().create().await.unwrap();
+
+        let filter = String::from("today");
+        let labels = vec![String::from("thing")];
+        let sort = &SortOrder::Value;
+
+        assert_eq!(
+            label(
+                &config_with_timezone,
+                Flag::Filter(filter),
+                &labels,
+                sort,
+                false,
+                false,
+                false
+            )
+            .await,
+            Ok(OperationOutcome {
+                message: String::from("Successfully labeled 'today'"),
+                changed: 1,
+            })
+        );
+        mock.assert();
+        mock2.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let mut config_with_timezone = config
+            .with_timezone("US/Pacific")
+            .with_mock_url(server.url());
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+
+        let tasks = view(
+            &mut config_with_timezone,
+            Flag::Filter(filter),
+            sort,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(tasks.contains("Tasks for today"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_with_label_filter() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .with_timezone("US/Pacific")
+            .with_mock_url(server.url());
+        let filter = Flag::Filter(String::from("today"));
+        let sort = &SortOrder::Value;
+
+        let matching = view(
+            &mut config,
+            filter.clone(),
+            sort,
+            FormatType::List,
+            &ViewOptions {
+                label_filter: Some("computer"),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matching.contains("- TEST\n"));
+
+        let not_matching = view(
+            &mut config,
+            filter,
+            sort,
+            FormatType::List,
+            &ViewOptions {
+                label_filter: Some("errands"),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(not_matching.contains("Tasks for today"));
+        assert!(!not_matching.contains("- TEST\n"));
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_group_by_due() {
+        let mut server = mockito::Server::new_async().await;
+        let today_mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::OverdueTasks.read().await)
+            .create_async()
+            .await;
+        let no_date_mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=no%20date&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::UnscheduledTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver")
+            .with_mock_url(server.url());
+        let sort = &SortOrder::Datetime;
+
+        let output = view(
+            &mut config,
+            Flag::Filter(String::from("today,no date")),
+            sort,
+            FormatType::List,
+            &ViewOptions {
+                group_by_due: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let overdue = output
+            .find("\n2020-01-01\n")
+            .expect("Missing overdue heading");
+        let today = output.find("\nToday\n").expect("Missing today heading");
+        let no_date = output.find("\nNo date\n").expect("Missing no date heading");
+        assert!(overdue < today);
+        assert!(today < no_date);
+
+        today_mock.assert();
+        no_date_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_keeps_sort_order() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver")
+            .with_mock_url(server.url());
+        let sort = &SortOrder::Value;
+
+        let output = view(
+            &mut config,
+            Flag::Filter(String::from("today")),
+            sort,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // Highest priority first, not the order they came from the API
+        let high = output.find("High priority").unwrap();
+        let medium = output.find("Medium priority").unwrap();
+        let none = output.find("No priority").unwrap();
+        assert!(high < medium);
+        assert!(medium < none);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_with_filters_dedupes_tasks() {
+        let mut server = mockito::Server::new_async().await;
+        let today = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+        let tomorrow = server
+            .mock("GET", "/api/v1/tasks/filter?query=tomorrow&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver")
+            .with_mock_url(server.url());
+
+        let flag = Flag::from_filter("today, tomorrow");
+        assert!(matches!(&flag, Flag::Filters(filters) if filters == &["today", "tomorrow"]));
+        assert_eq!(flag.to_string(), "'today', 'tomorrow'");
+
+        let output = view(
+            &mut config,
+            flag,
+            &SortOrder::Value,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // The first task of tomorrow is also due today, so it is only listed under today
+        let today_heading = output.find("Tasks for today").unwrap();
+        let tomorrow_heading = output.find("Tasks for tomorrow").unwrap();
+        let test_task = output.find("TEST").unwrap();
+        assert!(today_heading < test_task && test_task < tomorrow_heading);
+        assert!(!output.contains("Buy milk"));
+        assert!(output.find("buy milk").unwrap() > tomorrow_heading);
+        assert!(output.find("Walk the dog").unwrap() > tomorrow_heading);
+        today.assert();
+        tomorrow.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_show_ids() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver")
+            .with_mock_url(server.url());
+        let sort = &SortOrder::Value;
+
+        let with_ids = view(
+            &mut config,
+            Flag::Filter(String::from("today")),
+            sort,
+            FormatType::List,
+            &ViewOptions {
+                show_ids: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let without_ids = view(
+            &mut config,
+            Flag::Filter(String::from("today")),
+            sort,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(with_ids.contains("[6Xqhv4cwxgjwG9w8] - TEST"));
+        assert!(!without_ids.contains("[6Xqhv4cwxgjwG9w8]"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_with_project() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let mut config_with_timezone = config
+            .with_timezone("US/Pacific")
+            .with_mock_url(server.url());
+
+        let binding = config_with_timezone.projects().await.unwrap();
+        let project = binding.first().unwrap().clone();
+        let sort = &SortOrder::Value;
+
+        let tasks = view(
+            &mut config_with_timezone,
+            Flag::Project(project),
+            sort,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(tasks.contains("Tasks for"));
+        assert!(tasks.contains("- TEST\n"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_deduplicate() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .create_async()
+            .await;
+
+        let mock2 = server
+            .mock("POST", "/api/v1/tasks/7Yrhw5dxhxkXh0x9/close")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0);
+
+        let binding = config.projects().await.unwrap();
+        let project = binding.first().unwrap().to_owned();
+
+        let result = deduplicate(&config, Flag::Project(project)).await;
+        assert_eq!(
+            result,
+            Ok(String::from(
+                "Closed 1 duplicate tasks in myproject\nhttps://app.todoist.com/app/project/123"
+            ))
+        );
+        mock.assert();
+        mock2.assert();
+    }
+
+    #[tokio::test]
+    async fn test_group_duplicates() {
+        let task = test::fixtures::today_task().await;
+        let duplicate = Task {
+            id: String::from("2"),
+            content: String::from("test "),
+            ..task.clone()
+        };
+        let other_project = Task {
+            id: String::from("3"),
+            project_id: String::from("other"),
+            ..task.clone()
+        };
+
+        let groups = group_duplicates(vec![
+            task.clone(),
+            duplicate.clone(),
+            other_project,
+            task.clone(),
+        ]);
+
+        assert_eq!(groups, vec![vec![task, duplicate]]);
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let dir = std::env::temp_dir().join(format!("tod-export-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("export.txt");
+        let file_path = file_path.to_str().unwrap();
+
+        let filter = String::from("today");
+        let result = export(&config, Flag::Filter(filter.clone()), file_path).await;
+        assert_eq!(
+            result,
+            Ok(format!("Exported 1 tasks from 'today' to {file_path}"))
+        );
+
+        let tasks = tasks::json_to_tasks_response(ResponseFromFile::TodayTasks.read().await)
+            .unwrap()
+            .results;
+        let contents = fs::read_to_string(file_path).await.unwrap();
+        assert_eq!(contents.lines().count(), tasks.len());
+
+        let today = crate::time::date_string_today(&config).unwrap();
+        assert_eq!(contents, format!("TEST p2 {today} 15:00\n"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_backup() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+        let comments_mock = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let dir = std::env::temp_dir().join(format!("tod-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("backup.json");
+        let file_path = file_path.to_str().unwrap();
+
+        let result = backup(&config, Flag::Filter(String::from("today")), file_path).await;
+        assert_eq!(
+            result,
+            Ok(format!(
+                "Backed up 1 tasks and 7 comments from 'today' to {file_path}"
+            ))
+        );
+
+        let contents = fs::read_to_string(file_path).await.unwrap();
+        let document: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(document["source"], "today");
+        let task = &document["tasks"][0];
+        assert_eq!(task["id"], "6Xqhv4cwxgjwG9w8");
+        assert_eq!(task["content"], "TEST");
+        assert_eq!(task["comments"].as_array().unwrap().len(), 7);
+        assert!(task["comments"][0]["content"].is_string());
+        mock.assert();
+        comments_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_label_flag_uses_label_filter() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=%40work&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let flag = Flag::Label(String::from("work"));
+
+        assert_eq!(flag.to_string(), "@work");
+        assert_eq!(flag.query(), "@work");
+        assert_eq!(
+            all_tasks(&config, &flag).await,
+            Ok(vec![test::fixtures::today_task().await])
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_stats_header() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let mut view_today = async |stats_header| {
+            view(
+                &mut config,
+                Flag::Filter(String::from("today")),
+                &SortOrder::Value,
+                FormatType::List,
+                &ViewOptions {
+                    stats_header,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+        };
+        let with_stats = view_today(true).await;
+        let without_stats = view_today(false).await;
+
+        assert!(with_stats.contains("Tasks for today\n3 tasks, 2 prioritized, 3 with due dates\n"));
+        assert!(!without_stats.contains("prioritized"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_only_mine() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::AssignedTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let user_mock = server
+            .mock("GET", "/api/v1/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::User.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .create()
+            .await
+            .unwrap();
+
+        let mut view_today = async |only_mine| {
+            view(
+                &mut config,
+                Flag::Filter(String::from("today")),
+                &SortOrder::Value,
+                FormatType::List,
+                &ViewOptions {
+                    only_mine,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+        };
+        let everyone = view_today(false).await;
+        let mine = view_today(true).await;
+
+        assert!(everyone.contains("Their task"));
+        assert!(mine.contains("Unassigned task"));
+        assert!(mine.contains("My task"));
+        assert!(!mine.contains("Their task"));
+        assert_eq!(
+            config.reload().await.unwrap().user_id,
+            Some(String::from("111111"))
+        );
+        mock.assert();
+        user_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_markdown() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::LabeledTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let output = view(
+            &mut config,
+            Flag::Filter(String::from("today")),
+            &SortOrder::Todoist,
+            FormatType::Markdown,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(output.contains("## Tasks for today\n"));
+        assert!(output.contains("- [ ] "));
+        assert!(output.contains(" @computer @errands\n- [ ] "));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_indents_subtasks() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::NestedTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let output = view(
+            &mut config,
+            Flag::Filter(String::from("today")),
+            &SortOrder::Todoist,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let task_lines = output
+            .lines()
+            .filter(|line| line.trim_start().starts_with("- "))
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            task_lines,
+            vec![
+                "- Plan the trip",
+                "  - Book flights",
+                "    - Compare prices",
+                "- Pack the bags",
+            ]
+        );
+        // Details under a subtask are indented along with it
+        assert!(output.contains("\n    # myproject"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_shows_descriptions() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DescribedTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let output = view(
+            &mut config,
+            Flag::Filter(String::from("today")),
+            &SortOrder::Todoist,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(output.contains("- Renew passport\n  Bring two photos\n  Form is on the desk\n"));
+        assert!(output.contains("- Water the plants\n  # myproject"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_eta_line() {
+        let config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver");
+        let task = test::fixtures::today_task().await;
+        let long_task = Task {
+            duration: Some(tasks::Duration {
+                amount: 210,
+                unit: tasks::Unit::Minute,
+            }),
+            ..task.clone()
+        };
+        let without_duration = Task {
+            duration: None,
+            ..task.clone()
+        };
+
+        let tasks = vec![task, long_task, without_duration.clone()];
+        assert_eq!(
+            eta_line(&tasks, &config),
+            Ok(Some(String::from("Total: 3h 45m, finishing ~06:45")))
+        );
+        assert_eq!(eta_line(&[without_duration], &config), Ok(None));
+    }
+
+    #[tokio::test]
+    async fn test_filter_due_between() {
+        let config = test::fixtures::config().await;
+        let date = time::date_string_today(&config).unwrap();
+        // 05:00 in Vancouver
+        let early = test::fixtures::today_task().await;
+        let morning = Task {
+            content: "morning".into(),
+            due: Some(tasks::DateInfo {
+                // 09:30 in Vancouver
+                date: format!("{date}T16:30:00Z"),
+                ..early.due.clone().unwrap()
+            }),
+            ..early.clone()
+        };
+        let untimed = Task {
+            content: "untimed".into(),
+            due: Some(tasks::DateInfo {
+                date: date.clone(),
+                ..early.due.clone().unwrap()
+            }),
+            ..early.clone()
+        };
+        let undated = Task {
+            content: "undated".into(),
+            due: None,
+            ..early.clone()
+        };
+        let tasks = vec![early, morning, untimed, undated];
+
+        let (start, end) = parse_due_window("08:00-12:00").unwrap();
+        let filtered = filter_due_between(tasks.clone(), &config, start, end);
+        let contents: Vec<&str> = filtered.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(contents, vec!["morning"]);
+
+        let (start, end) = parse_due_window("05:00-09:30").unwrap();
+        let filtered = filter_due_between(tasks, &config, start, end);
+        let contents: Vec<&str> = filtered.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(contents, vec!["TEST", "morning"]);
+
+        assert!(parse_due_window("12:00-08:00").is_err());
+        assert!(parse_due_window("morning").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_view_plain() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::LabeledTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        let flag = Flag::Filter(String::from("today"));
+        let tasks = all_tasks(&config, &flag).await.unwrap();
+        let contents: Vec<String> = tasks::sort(tasks, &config, &SortOrder::Todoist, false)
+            .into_iter()
+            .map(|task| task.content)
+            .collect();
+
+        let output = view(
+            &mut config,
+            flag,
+            &SortOrder::Todoist,
+            FormatType::Plain,
+            &ViewOptions {
+                group_by_due: true,
+                stats_header: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(output.lines().collect::<Vec<&str>>(), contents);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_writes_to_output_file() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        let dir = std::env::temp_dir().join(format!("tod-view-output-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("view_output.txt");
+        let file_path = file_path.to_str().unwrap();
+
+        let result = view(
+            &mut config,
+            Flag::Filter(String::from("today")),
+            &SortOrder::Value,
+            FormatType::List,
+            &ViewOptions {
+                output: Some(file_path),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(
+            result,
+            Ok(format!("Wrote 1 tasks from 'today' to {file_path}"))
+        );
+
+        let contents = fs::read_to_string(file_path).await.unwrap();
+        assert!(contents.contains("Tasks for today"));
+        assert!(contents.contains("- TEST"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_json() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+
+        let output = view(
+            &mut config,
+            Flag::Filter(filter),
+            sort,
+            FormatType::Json,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let tasks = json.as_array().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0]["id"], "6Xqhv4cwxgjwG9w8");
+        assert_eq!(tasks[0]["content"], "TEST");
+        assert_eq!(tasks[0]["labels"], serde_json::json!(["computer"]));
+        assert!(!output.contains('\u{1b}'));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_comments_for_tasks_with_concurrency_of_one() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.comment_fetch_concurrency = Some(1);
+        let task = test::fixtures::today_task().await;
+        let tasks = vec![task.clone(), task.clone(), task];
+
+        let results = fetch_comments_for_tasks(tasks, &config).await;
+
+        assert_eq!(results.len(), 3);
+        for result in results {
+            assert_matches!(result, (_, Ok(comments)) if !comments.is_empty());
+        }
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fetched_comments_keeps_tasks_that_panicked() {
+        // Both fetch_comments_for_tasks and the stream process reads from pair each task with this result
+        let join_error = tokio::spawn(async { panic!("comment fetch panicked") })
+            .await
+            .unwrap_err();
+
+        assert_matches!(
+            fetched_comments(Err(join_error)),
+            Err(Error { source, message, .. }) if source == "Join on future" && message.contains("comment fetch panicked")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_completed_report() {
+        let mut server = mockito::Server::new_async().await;
+        let mut body = serde_json::from_str::<serde_json::Value>(
+            &ResponseFromFile::CompletedTasks.read().await,
+        )
+        .unwrap();
+        let mut third = body["items"][0].clone();
+        third["id"] = "third".into();
+        third["content"] = "Water plants".into();
+        third["completed_at"] = "2025-05-06T20:00:00.000000Z".into();
+        body["items"][0]["completed_at"] = "2025-05-03T18:00:00.000000Z".into();
+        // Outside the window even though the server returned it
+        body["items"][1]["completed_at"] = "2025-05-09T18:00:00.000000Z".into();
+        body["items"].as_array_mut().unwrap().push(third);
+
+        let mock = server
+            .mock("GET", "/api/v1/tasks/completed/by_completion_date")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("project_id".into(), "123".into()),
+                mockito::Matcher::UrlEncoded("since".into(), "2025-05-01T07:00:00Z".into()),
+                mockito::Matcher::UrlEncoded("until".into(), "2025-05-08T06:59:59Z".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let project = test::fixtures::project();
+
+        let report = completed_report(&config, &project, "2025-05-01", Some("2025-05-07"))
+            .await
+            .unwrap();
+
+        assert!(report.starts_with("Completed tasks for myproject from 2025-05-01 to 2025-05-07"));
+        assert!(report.contains("Buy milk"));
+        assert!(report.contains("Water plants"));
+        assert!(!report.contains("Call mom"));
+        let first_day = report.find("2025-05-03").unwrap();
+        let second_day = report.find("2025-05-06").unwrap();
+        assert!(first_day < report.find("Buy milk").unwrap());
+        assert!(second_day < report.find("Water plants").unwrap());
+        assert!(first_day < second_day);
+
+        let backwards = completed_report(&config, &project, "2025-05-07", Some("2025-05-01")).await;
+        assert_eq!(
+            backwards.unwrap_err().message,
+            "2025-05-01 is before 2025-05-07"
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_complete_all() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut close_mocks = Vec::new();
+        for id in ["6Xqhv4cwxgjwG9w8", "7Yrhw5dxhxkXh0x9", "8Zsix6eyiylYi1y0"] {
+            let close_mock = server
+                .mock("POST", format!("/api/v1/tasks/{id}/close").as_str())
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(ResponseFromFile::TodayTask.read().await)
+                .expect(1)
+                .create_async()
+                .await;
+            close_mocks.push(close_mock);
+        }
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1)
+            .create()
+            .await
+            .unwrap();
+
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+        let result = complete_all(&config, Flag::Filter(filter), sort, false).await;
+
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from("Completed 3 tasks in 'today'"),
+                changed: 3,
+            })
+        );
+        mock.assert();
+        for close_mock in close_mocks {
+            close_mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_all_confirm_threshold() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        let close_mock = server
+            .mock(
+                "POST",
+                mockito::Matcher::Regex(r"^/api/v1/tasks/\w+/close$".into()),
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(3)
+            .create_async()
+            .await;
+
+        // Selecting the first option cancels whenever the prompt is shown
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0)
+            .create()
+            .await
+            .unwrap();
+        let filter = Flag::Filter(String::from("today"));
+        let sort = &SortOrder::Value;
+
+        let low = config.with_confirm_threshold(1);
+        let result = complete_all(&low, filter.clone(), sort, false).await;
+        assert_eq!(result, Ok(OperationOutcome::new("Cancelled".into(), 0)));
+
+        let high = config.with_confirm_threshold(3);
+        let result = complete_all(&high, filter, sort, false).await;
+        assert_eq!(
+            result,
+            Ok(OperationOutcome::new(
+                String::from("Completed 3 tasks in 'today'"),
+                3
+            ))
+        );
+        mock.assert();
+        close_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_complete_all_picked_tasks() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut close_mocks = Vec::new();
+        for (id, expected) in [
+            ("6Xqhv4cwxgjwG9w8", 0),
+            ("7Yrhw5dxhxkXh0x9", 1),
+            ("8Zsix6eyiylYi1y0", 0),
+        ] {
+            let close_mock = server
+                .mock("POST", format!("/api/v1/tasks/{id}/close").as_str())
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(ResponseFromFile::TodayTask.read().await)
+                .expect(expected)
+                .create_async()
+                .await;
+            close_mocks.push(close_mock);
+        }
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1)
+            .create()
+            .await
+            .unwrap();
+
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+        let result = complete_all(&config, Flag::Filter(filter), sort, true).await;
+
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from("Completed 1 tasks in 'today'"),
+                changed: 1,
+            })
+        );
+        mock.assert();
+        for close_mock in close_mocks {
+            close_mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_subtasks_of_listed_parents() {
+        let parent = test::fixtures::today_task().await;
+        let subtask = Task {
+            id: String::from("2"),
+            parent_id: Some(parent.id.clone()),
+            ..parent.clone()
+        };
+        let orphan = Task {
+            id: String::from("3"),
+            parent_id: Some(String::from("not listed")),
+            ..parent.clone()
+        };
+
+        let result = reject_subtasks_of_listed_parents(vec![
+            parent.clone(),
+            subtask,
+            orphan.clone(),
+            parent.clone(),
+        ]);
+
+        assert_eq!(result, vec![parent, orphan]);
+    }
+
+    #[tokio::test]
+    async fn test_bump_priority() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let body = ResponseFromFile::TodayTask.read().await;
+        let mut update = |id: &str, priority: u8| {
+            server
+                .mock("POST", format!("/api/v1/tasks/{id}").as_str())
+                .match_body(mockito::Matcher::Json(
+                    serde_json::json!({"priority": priority}),
+                ))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(&body)
+                .expect(1)
+        };
+        // Up one: medium and none move, high is already at the top
+        let medium_up = update("6Xqhv4cwxgjwG9w8", 4).create_async().await;
+        let none_up = update("7Yrhw5dxhxkXh0x9", 2).create_async().await;
+        // Down one: medium and high move, none is already at the bottom
+        let medium_down = update("6Xqhv4cwxgjwG9w8", 2).create_async().await;
+        let high_down = update("8Zsix6eyiylYi1y0", 3).create_async().await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1)
+            .create()
+            .await
+            .unwrap();
+        let project = config.projects().await.unwrap().first().unwrap().clone();
+        let sort = &SortOrder::Value;
+
+        let flag = Flag::Project(project);
+        let expected =
+            format!("Changed the priority of 2 tasks in {flag}, skipped 1 already at the limit");
+
+        let result = bump_priority(&config, flag.clone(), 1, sort).await;
+        assert_eq!(result, Ok(expected.clone()));
+
+        let result = bump_priority(&config, flag, -1, sort).await;
+        assert_eq!(result, Ok(expected));
+        mock.assert();
+        medium_up.assert();
+        none_up.assert();
+        medium_down.assert();
+        high_down.assert();
+    }
+
+    #[tokio::test]
+    async fn test_prioritize_from_priority() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let medium = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+        let others = server
+            .mock(
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(7Yrhw5dxhxkXh0x9|8Zsix6eyiylYi1y0)$".into(),
+                ),
+            )
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(3)
+            .create()
+            .await
+            .unwrap();
+
+        let binding = config.projects().await.unwrap();
+        let project = binding.first().unwrap().to_owned();
+        let sort = &SortOrder::Value;
+
+        let result = prioritize(
+            &config,
+            Flag::Project(project.clone()),
+            sort,
+            false,
+            Some(Priority::Medium),
+            false,
+            None,
+        )
+        .await;
+        assert_matches!(result, Ok(x) if x.message.contains("Successfully prioritized") && x.changed == 1);
+
+        let result = prioritize(
+            &config,
+            Flag::Project(project),
+            sort,
+            false,
+            Some(Priority::Low),
+            false,
+            None,
+        )
+        .await;
+        assert_matches!(result, Ok(x) if x.message.contains("No tasks with priority LOW (P3) for myproject"));
+
+        mock.assert();
+        medium.assert();
+        others.assert();
+    }
+
+    #[tokio::test]
+    async fn test_view_today() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("query".into(), "today | overdue".into()),
+                mockito::Matcher::UrlEncoded("limit".into(), "200".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        let sort = &SortOrder::Value;
+
+        let tasks = view(
+            &mut config,
+            Flag::Today,
+            sort,
+            FormatType::List,
+            &ViewOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(tasks.contains("Tasks for today | overdue"));
+        assert!(tasks.contains("- TEST\n"));
+        assert_eq!(Flag::Today.to_string(), "today");
+        assert_eq!(Flag::Overdue.query(), "overdue");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_summary() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+        let mock2 = server
+            .mock("GET", "/api/v1/tasks/?project_id=456&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .create_async()
+            .await;
+
+        let other_project = Project {
+            id: String::from("456"),
+            name: String::from("otherproject"),
+            ..test::fixtures::project()
+        };
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .with_projects(vec![test::fixtures::project(), other_project]);
+
+        let result = summary(&config).await.unwrap();
+
+        let lines = result.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1],
+            format!(" - {}3", "otherproject".pad_to_width(PAD_WIDTH))
+        );
+        assert_eq!(
+            lines[2],
+            format!(" - {}1", "myproject".pad_to_width(PAD_WIDTH))
+        );
+        assert_eq!(lines[3], format!("   {}4", "Total".pad_to_width(PAD_WIDTH)));
+        mock.assert();
+        mock2.assert();
+    }
+
+    #[tokio::test]
+    async fn test_empty_projects() {
+        let mut server = mockito::Server::new_async().await;
+        let full_mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let empty_mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=456&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"results": [], "next_cursor": null}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let delete_mock = server
+            .mock("DELETE", "/api/v1/projects/456")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let empty_project = Project {
+            id: String::from("456"),
+            name: String::from("otherproject"),
+            ..test::fixtures::project()
+        };
+        let projects_mock = server
+            .mock("GET", "/api/v1/projects?limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(projects_response(&[
+                test::fixtures::project(),
+                empty_project.clone(),
+            ]))
+            .expect(2)
+            .create_async()
+            .await;
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .with_projects(vec![test::fixtures::project(), empty_project])
+            .create()
+            .await
+            .unwrap();
+
+        // Cancel only reports the empty project
+        let result = empty_projects(&config.clone().mock_select(0)).await;
+        assert_eq!(
+            result,
+            Ok(String::from("Found 1 empty projects:\n - otherproject"))
+        );
+
+        let result = empty_projects(&config.clone().mock_select(1)).await;
+        assert_eq!(result, Ok(String::from("Deleted 1 empty projects")));
+        let remaining = config.reload().await.unwrap().projects().await.unwrap();
+        assert_eq!(remaining, vec![test::fixtures::project()]);
+        full_mock.assert();
+        empty_mock.assert();
+        delete_mock.assert();
+        projects_mock.assert();
+    }
+
+    fn projects_response(projects: &[Project]) -> String {
+        serde_json::json!({"results": projects, "next_cursor": null}).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_empty_projects_skips_parents_and_inbox() {
+        let mut server = mockito::Server::new_async().await;
+        let parent = Project {
+            id: String::from("456"),
+            name: String::from("parent"),
+            ..test::fixtures::project()
+        };
+        let child = Project {
+            id: String::from("789"),
+            name: String::from("child"),
+            parent_id: Some(String::from("456")),
+            ..test::fixtures::project()
+        };
+        let inbox = Project {
+            id: String::from("111"),
+            name: String::from("Inbox"),
+            inbox_project: Some(true),
+            ..test::fixtures::project()
+        };
+        let projects = vec![parent, child, inbox];
+
+        let projects_mock = server
+            .mock("GET", "/api/v1/projects?limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(projects_response(&projects))
+            .create_async()
+            .await;
+        let child_mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=789&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+        let other_mock = server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex("project_id=(456|111)".into()),
+            )
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .with_projects(projects)
+            .mock_select(1);
+
+        let result = empty_projects(&config).await;
+        assert_eq!(result, Ok(String::from("No empty projects")));
+        projects_mock.assert();
+        child_mock.assert();
+        other_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_empty_projects_saves_deletes_before_a_failure() {
         let mut server = mockito::Server::new_async().await;
-        // File to import and quantity specified here - expects 14 items
-        let import_file = "tests/inputs/import_tasks.txt";
-        let import_qty = 14;
+        let first = Project {
+            id: String::from("456"),
+            name: String::from("first"),
+            ..test::fixtures::project()
+        };
+        let second = Project {
+            id: String::from("789"),
+            name: String::from("second"),
+            ..test::fixtures::project()
+        };
+        let projects = vec![first, second.clone()];
 
-        // Expect 14 POSTs to /api/v1/tasks/quick
+        let projects_mock = server
+            .mock("GET", "/api/v1/projects?limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(projects_response(&projects))
+            .create_async()
+            .await;
+        let tasks_mock = server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex("^/api/v1/tasks/\\?project_id=(456|789)&limit=200$".into()),
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"results": [], "next_cursor": null}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let first_delete = server
+            .mock("DELETE", "/api/v1/projects/456")
+            .with_status(204)
+            .create_async()
+            .await;
+        let second_delete = server
+            .mock("DELETE", "/api/v1/projects/789")
+            .with_status(400)
+            .with_body("Bad request")
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .with_projects(projects)
+            .mock_select(1)
+            .create()
+            .await
+            .unwrap();
+
+        let result = empty_projects(&config).await;
+        assert!(
+            result
+                .unwrap_err()
+                .message
+                .starts_with("Deleted 1 of 2 empty projects before failing")
+        );
+        let remaining = config.reload().await.unwrap().projects().await.unwrap();
+        assert_eq!(remaining, vec![second]);
+        projects_mock.assert();
+        tasks_mock.assert();
+        first_delete.assert();
+        second_delete.assert();
+    }
+
+    #[tokio::test]
+    async fn test_label_stats() {
+        let mut server = mockito::Server::new_async().await;
         let mock = server
-            .mock("POST", "/api/v1/tasks/quick")
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTask.read().await)
-            .expect(import_qty)
+            .with_body(ResponseFromFile::LabeledTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let result = label_stats(&config, Flag::Filter(String::from("today")))
+            .await
+            .unwrap();
+        let lines = result.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[1],
+            format!(" - {}2 (67%)", "errands".pad_to_width(PAD_WIDTH))
+        );
+        assert_eq!(
+            lines[2],
+            format!(" - {}1 (33%)", "(unlabeled)".pad_to_width(PAD_WIDTH))
+        );
+        assert_eq!(
+            lines[3],
+            format!(" - {}1 (33%)", "computer".pad_to_width(PAD_WIDTH))
+        );
+        assert_eq!(lines[4], format!("   {}3", "Total".pad_to_width(PAD_WIDTH)));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_histogram() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let result = histogram(&config, Flag::Filter(String::from("today")))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.lines().collect::<Vec<&str>>(),
+            vec![
+                String::from("Priorities for 'today'"),
+                format!("{}# 1", "HIGH (P1)".pad_to_width(PAD_WIDTH)),
+                format!("{}# 1", "MEDIUM (P2)".pad_to_width(PAD_WIDTH)),
+                format!("{} 0", "LOW (P3)".pad_to_width(PAD_WIDTH)),
+                format!("{}# 1", "NONE (P4)".pad_to_width(PAD_WIDTH)),
+            ]
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn test_bar_width_scales_past_histogram_width() {
+        assert_eq!(bar_width(3, 10), 3);
+        assert_eq!(bar_width(100, 200), 25);
+        assert_eq!(bar_width(1, 200), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prioritize_dry_run() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock2 = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+
+        let filter = String::from("today");
+        let sort = &SortOrder::Value;
+        let result = prioritize(
+            &config,
+            Flag::Filter(filter.clone()),
+            sort,
+            false,
+            None,
+            true,
+            None,
+        )
+        .await;
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from("Dry run: would have prioritized 'today'"),
+                changed: 0,
+            })
+        );
+
+        let labels = vec![String::from("thing")];
+        let result = label(
+            &config,
+            Flag::Filter(filter),
+            &labels,
+            sort,
+            false,
+            true,
+            false,
+        )
+        .await;
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from("Dry run: would have labeled 'today'"),
+                changed: 0,
+            })
+        );
+
+        mock.assert();
+        mock2.assert();
+    }
+
+    #[tokio::test]
+    async fn test_search_substring() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let output = search(&config, Flag::Filter("today".into()), "HIGH", false)
+            .await
+            .unwrap();
+
+        assert!(output.starts_with("Tasks in 'today' matching HIGH"));
+        assert!(output.contains("High priority"));
+        assert!(!output.contains("Medium priority"));
+        assert!(!output.contains("No priority"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_search_regex() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let output = search(&config, Flag::Filter("today".into()), "^(High|No) ", true)
+            .await
+            .unwrap();
+        assert!(output.contains("High priority"));
+        assert!(output.contains("No priority"));
+        assert!(!output.contains("Medium priority"));
+
+        let error = search(&config, Flag::Filter("today".into()), "(", true)
+            .await
+            .unwrap_err();
+        assert!(error.message.starts_with("Invalid regex '('"));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_comment_all_confirm_threshold() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let comment_mock = server
+            .mock("POST", "/api/v1/comments/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::Comment.read().await)
+            .expect(3)
+            .create_async()
+            .await;
+
+        // Selecting the first option cancels whenever the prompt is shown
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0);
+        let flag = Flag::Filter(String::from("today"));
+        let sort = &SortOrder::Value;
+
+        let low = config.with_confirm_threshold(2);
+        let result = comment_all(&low, flag.clone(), "Reviewed", sort).await;
+        assert_eq!(result, Ok(String::from("Cancelled")));
+
+        let high = config.with_confirm_threshold(3);
+        let result = comment_all(&high, flag, "Reviewed", sort).await;
+        assert_eq!(
+            result,
+            Ok(String::from("Added a comment to 3 tasks in 'today'"))
+        );
+        mock.assert();
+        comment_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_comment_all() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::MixedPriorityTasks.read().await)
+            .create_async()
+            .await;
+        let comment_mock = server
+            .mock("POST", "/api/v1/comments/")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"content": "Reviewed"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::Comment.read().await)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+
+        let result = comment_all(
+            &config,
+            Flag::Filter("today".into()),
+            "Reviewed",
+            &SortOrder::Value,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Ok(String::from("Added a comment to 3 tasks in 'today'"))
+        );
+        mock.assert();
+        comment_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_rename_label() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::LabeledTasks.read().await)
+            .create_async()
+            .await;
+
+        let first_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"labels": ["computer", "chores"]}),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let unlabeled_mock = server
+            .mock("POST", "/api/v1/tasks/7Yrhw5dxhxkXh0x9")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let last_mock = server
+            .mock("POST", "/api/v1/tasks/8Zsix6eyiylYi1y0")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"labels": ["chores"]}),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+
+        let result = rename_label(
+            &config,
+            Flag::Filter(String::from("today")),
+            "errands",
+            "chores",
+            &SortOrder::Value,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Ok(String::from(
+                "Renamed errands to chores on 2 tasks in 'today'"
+            ))
+        );
+        mock.assert();
+        first_mock.assert();
+        unlabeled_mock.assert();
+        last_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_label_remove() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::LabeledTasks.read().await)
+            .create_async()
+            .await;
+
+        let first_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"labels": ["computer"]}),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let unlabeled_mock = server
+            .mock("POST", "/api/v1/tasks/7Yrhw5dxhxkXh0x9")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let last_mock = server
+            .mock("POST", "/api/v1/tasks/8Zsix6eyiylYi1y0")
+            .match_body(mockito::Matcher::Json(serde_json::json!({"labels": []})))
+            .with_status(200)
+            .expect(1)
             .create_async()
             .await;
 
         let config = test::fixtures::config().await.with_mock_url(server.url());
 
-        assert_eq!(import(&config, import_file).await, Ok(String::from("✓")));
+        let filter = String::from("today");
+        let labels = vec![String::from("errands")];
+        let sort = &SortOrder::Value;
+        let result = label(
+            &config,
+            Flag::Filter(filter),
+            &labels,
+            sort,
+            false,
+            false,
+            true,
+        )
+        .await;
 
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from("Successfully removed labels from 'today'"),
+                changed: 2,
+            })
+        );
         mock.assert();
+        first_mock.assert();
+        unlabeled_mock.assert();
+        last_mock.assert();
     }
 
     #[tokio::test]
-    async fn test_prioritize() {
+    async fn test_snooze() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
             .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
@@ -314,324 +6075,446 @@ mod tests {
             .with_body(ResponseFromFile::TodayTasks.read().await)
             .create_async()
             .await;
-        let mock2 = server
-            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
-            .with_status(200)
-            .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
-            .create_async()
-            .await;
 
         let config = test::fixtures::config()
             .await
+            .with_timezone("America/Vancouver")
             .with_mock_url(server.url())
             .mock_select(1);
 
-        let filter = String::from("today");
-        let sort = &SortOrder::Value;
-        let result = prioritize(&config, Flag::Filter(filter), sort).await;
-        assert_eq!(result, Ok(String::from("Successfully prioritized 'today'")));
-        mock.assert();
-        mock2.assert();
-    }
-    #[tokio::test]
-    async fn test_timebox() {
-        let mut server = mockito::Server::new_async().await;
-        let mock = server
-            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
-            .with_status(200)
-            .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasksWithoutDuration.read().await)
-            .create_async()
-            .await;
+        let task = test::fixtures::today_task().await;
+        let due = task.due.unwrap().date;
+        let expected =
+            time::datetime_from_str(&due, chrono_tz::Tz::UTC).unwrap() + chrono::Duration::hours(3);
+        let expected = expected
+            .with_timezone(&chrono_tz::America::Vancouver)
+            .format(time::FORMAT_DATE_AND_TIME)
+            .to_string();
 
-        let mock2 = server
-            .mock("POST", "/api/v1/tasks/999999")
+        let update_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"due_string": expected}),
+            ))
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
             .create_async()
             .await;
 
-        let mock3 = server
-            .mock("GET", "/api/v1/id_mappings/projects/123")
+        let sort = &SortOrder::Value;
+        let result = snooze(
+            &config,
+            Flag::Filter(String::from("today")),
+            "3 hours",
+            sort,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Ok(String::from("Snoozed 1 tasks in 'today' by 3 hours"))
+        );
+        mock.assert();
+        update_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_prioritize_with_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::Ids.read().await)
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
             .create_async()
             .await;
-
-        let mock4 = server
+        let update_mock = server
             .mock(
-                "GET",
-                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+                "POST",
+                mockito::Matcher::Regex(String::from(r"^/api/v1/tasks/\w+$")),
             )
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(2)
             .create_async()
             .await;
 
         let config = test::fixtures::config()
             .await
             .with_mock_url(server.url())
-            .mock_select(1)
-            .with_mock_string("tod")
+            .mock_select(3)
             .create()
             .await
             .unwrap();
 
-        let binding = config.projects().await.unwrap();
-        let project = binding.first().unwrap().to_owned();
+        let filter = String::from("today");
         let sort = &SortOrder::Value;
-        let result = timebox(&config, Flag::Project(project), sort).await;
-        assert_matches!(result, Ok(x) if x.contains("Successfully timeboxed"));
-
-        let config = config.mock_select(2);
-
-        let binding = config.projects().await.unwrap();
-        let project = binding.first().unwrap().to_owned();
-        let result = timebox(&config, Flag::Project(project), sort).await;
-        assert_matches!(result, Ok(x) if x.contains("Successfully timeboxed"));
-
-        let config = config.mock_select(3);
-
-        let binding = config.projects().await.unwrap();
-        let project = binding.first().unwrap().to_owned();
-        let result = timebox(&config, Flag::Project(project.clone()), sort).await;
-        assert_matches!(result, Ok(x) if x.contains("Successfully timeboxed"));
+        let result = prioritize(
+            &config,
+            Flag::Filter(filter),
+            sort,
+            false,
+            None,
+            false,
+            Some(2),
+        )
+        .await;
 
-        let result = timebox(&config, Flag::Project(project), sort).await;
-        assert_matches!(result, Ok(x) if x.contains("Successfully timeboxed"));
-        mock.expect(2);
-        mock2.expect(2);
-        mock3.expect(1);
-        mock4.expect(1);
+        assert_eq!(
+            result,
+            Ok(OperationOutcome {
+                message: String::from("Successfully prioritized 'today' (limited to 2 of 3 tasks)"),
+                changed: 2,
+            })
+        );
+        mock.assert();
+        update_mock.assert();
     }
 
     #[tokio::test]
-    async fn test_prioritize_tasks_with_no_tasks() {
+    async fn test_move_all() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
             .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
             .create_async()
             .await;
 
-        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let mut move_mocks = Vec::new();
+        for id in ["6Xqhv4cwxgjwG9w8", "7Yrhw5dxhxkXh0x9", "8Zsix6eyiylYi1y0"] {
+            let move_mock = server
+                .mock("POST", format!("/api/v1/tasks/{id}/move").as_str())
+                .match_body(mockito::Matcher::Json(
+                    serde_json::json!({"project_id": "456"}),
+                ))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(ResponseFromFile::TodayTask.read().await)
+                .expect(1)
+                .create_async()
+                .await;
+            move_mocks.push(move_mock);
+        }
 
-        let binding = config.projects().await.unwrap();
-        let project = binding.first().unwrap().to_owned();
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+
+        let from = test::fixtures::project();
+        let to = Project {
+            id: String::from("456"),
+            name: String::from("otherproject"),
+            ..test::fixtures::project()
+        };
         let sort = &SortOrder::Value;
+        let result = move_all(&config, &from, &to, sort).await;
 
-        let result = prioritize(&config, Flag::Project(project), sort).await;
         assert_eq!(
             result,
-            Ok(String::from(
-                "No tasks for myproject\nhttps://app.todoist.com/app/project/123"
-            ))
+            Ok(String::from("Moved 3 tasks from myproject to otherproject"))
         );
         mock.assert();
+        for move_mock in move_mocks {
+            move_mock.assert();
+        }
     }
+
     #[tokio::test]
-    async fn test_process_with_filter() {
+    async fn test_assign_all() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
             .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .expect(2)
             .create_async()
             .await;
-
-        let mock2 = server
-            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+        let collaborators = server
+            .mock(
+                "GET",
+                "/api/v1/projects/6VRRxv8CM6GVmmgf/collaborators?limit=200",
+            )
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTask.read().await)
+            .with_body(ResponseFromFile::Collaborators.read().await)
+            .expect(2)
             .create_async()
             .await;
-
-        let mock3 = server
+        let updates = server
             .mock(
-                "GET",
-                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(6Xqhv4cwxgjwG9w8|7Yrhw5dxhxkXh0x9|8Zsix6eyiylYi1y0)$".into(),
+                ),
             )
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"responsible_uid": "333333"}),
+            ))
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(3)
             .create_async()
             .await;
+
         let config = test::fixtures::config()
             .await
             .with_mock_url(server.url())
-            .mock_select(0)
-            .create()
-            .await
-            .unwrap();
-        let filter = String::from("today");
+            .mock_select(1);
+        let flag = Flag::Filter("today".into());
         let sort = &SortOrder::Value;
 
-        let result = process(&config, Flag::Filter(filter), sort).await;
-        assert_eq!(result, Ok("Successfully processed 'today'".to_string()));
+        let result = assign_all(&config, flag.clone(), "teammate@GMAIL.com", sort).await;
+        assert_eq!(
+            result,
+            Ok(String::from("Assigned 3 tasks in 'today' to Teammate"))
+        );
+
+        let result = assign_all(&config, flag, "Nobody", sort).await;
+        assert_eq!(
+            result,
+            Err(Error::new(
+                "assign_all",
+                "No collaborator named 'Nobody' in the projects for 'today'"
+            ))
+        );
         mock.assert();
-        mock2.assert();
-        mock3.assert();
+        collaborators.assert();
+        updates.assert();
     }
 
     #[tokio::test]
-    async fn test_process_with_project() {
+    async fn test_move_to_section() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
-            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .with_body(ResponseFromFile::DuplicateTasks.read().await)
+            .expect(2)
             .create_async()
             .await;
-
-        let mock2 = server
-            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+        let sections = server
+            .mock(
+                "GET",
+                "/api/v1/sections?project_id=6VRRxv8CM6GVmmgf&limit=200",
+            )
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTask.read().await)
+            .with_body(ResponseFromFile::Sections.read().await)
+            .expect(2)
             .create_async()
             .await;
-
-        let mock3 = server
+        let moves = server
             .mock(
-                "GET",
-                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(6Xqhv4cwxgjwG9w8|7Yrhw5dxhxkXh0x9|8Zsix6eyiylYi1y0)/move$"
+                        .into(),
+                ),
             )
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"section_id": "1234"}),
+            ))
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::CommentsAllTypes.read().await)
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(3)
             .create_async()
             .await;
 
+        let groceries = Project {
+            id: String::from("6VRRxv8CM6GVmmgf"),
+            name: String::from("Groceries"),
+            ..test::fixtures::project()
+        };
         let config = test::fixtures::config()
             .await
             .with_mock_url(server.url())
-            .mock_select(0)
-            .create()
-            .await
-            .unwrap();
-
-        let binding = config.projects().await.unwrap();
-        let project = binding.first().unwrap().to_owned();
+            .mock_select(1)
+            .with_projects(vec![test::fixtures::project(), groceries]);
+        let flag = Flag::Filter("today".into());
         let sort = &SortOrder::Value;
 
-        let result = process(&config, Flag::Project(project), sort).await;
+        let result = move_to_section(&config, flag.clone(), "Bread", sort).await;
         assert_eq!(
             result,
-            Ok(
-                "Successfully processed myproject\nhttps://app.todoist.com/app/project/123"
-                    .to_string()
-            )
+            Ok(String::from("Moved 3 tasks in 'today' to Bread"))
+        );
+
+        let result = move_to_section(&config, flag, "Cheese", sort).await;
+        assert_eq!(
+            result,
+            Err(Error::new(
+                "move_to_section",
+                "Could not find section 'Cheese' for 'today'"
+            ))
         );
         mock.assert();
-        mock2.assert();
-        mock3.assert();
+        sections.assert();
+        moves.assert();
     }
+
     #[tokio::test]
-    async fn test_label() {
+    async fn test_postpone_to_weekend() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
-            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .mock(
+                "GET",
+                "/api/v1/tasks/filter?query=today%20%7C%20overdue&limit=200",
+            )
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .with_body(ResponseFromFile::OverdueTasks.read().await)
             .create_async()
             .await;
 
-        let mock2 = server
-            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
-            .with_status(200)
-            .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
+        let mut postponed_mocks = Vec::new();
+        for id in ["6Xqhv4cwxgjwG9w8", "8Zsix6eyiylYi1y0"] {
+            let postponed_mock = server
+                .mock("POST", format!("/api/v1/tasks/{id}").as_str())
+                .match_body(mockito::Matcher::Json(
+                    serde_json::json!({"due_string": "2025-05-17"}),
+                ))
+                .with_status(200)
+                .expect(1)
+                .create_async()
+                .await;
+            postponed_mocks.push(postponed_mock);
+        }
+        let recurring_mock = server
+            .mock("POST", "/api/v1/tasks/7Yrhw5dxhxkXh0x9")
+            .expect(0)
             .create_async()
             .await;
 
-        let config = test::fixtures::config().await.with_mock_url(server.url());
-
-        let config_dir = dirs::config_dir().expect("Could not find config directory");
-
-        let config_with_timezone = config
-            .with_timezone("US/Pacific")
-            .with_path(config_dir.join("test3"))
+        // The fixed clock is Saturday 2025-05-10
+        let config = test::fixtures::config()
+            .await
             .with_mock_url(server.url())
-            .mock_select(0);
-
-        config_with_timezone.clone().create().await.unwrap();
+            .mock_select(1);
 
-        let filter = String::from("today");
-        let labels = vec![String::from("thing")];
-        let sort = &SortOrder::Value;
+        let result = postpone_to_weekend(&config, Flag::Today, &SortOrder::Value).await;
 
         assert_eq!(
-            label(&config_with_timezone, Flag::Filter(filter), &labels, sort).await,
-            Ok(String::from("Successfully labeled 'today'"))
+            result,
+            Ok(String::from(
+                "Postponed 2 tasks in today to Saturday 2025-05-17"
+            ))
         );
         mock.assert();
-        mock2.assert();
+        for postponed_mock in postponed_mocks {
+            postponed_mock.assert();
+        }
+        recurring_mock.assert();
     }
 
     #[tokio::test]
-    async fn test_view() {
+    async fn test_reschedule_overdue() {
         let mut server = mockito::Server::new_async().await;
         let mock = server
-            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .mock(
+                "GET",
+                "/api/v1/tasks/filter?query=today%20%7C%20overdue&limit=200",
+            )
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .with_body(ResponseFromFile::OverdueTasks.read().await)
             .create_async()
             .await;
 
-        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let overdue_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"due_string": "today"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
 
-        let mut config_with_timezone = config
-            .with_timezone("US/Pacific")
-            .with_mock_url(server.url());
-        let filter = String::from("today");
-        let sort = &SortOrder::Value;
+        let mut untouched_mocks = Vec::new();
+        for id in ["7Yrhw5dxhxkXh0x9", "8Zsix6eyiylYi1y0"] {
+            let untouched_mock = server
+                .mock("POST", format!("/api/v1/tasks/{id}").as_str())
+                .expect(0)
+                .create_async()
+                .await;
+            untouched_mocks.push(untouched_mock);
+        }
 
-        let tasks = view(&mut config_with_timezone, Flag::Filter(filter), sort)
+        let config = test::fixtures::config()
             .await
-            .unwrap();
+            .with_mock_url(server.url())
+            .mock_select(1);
 
-        assert!(tasks.contains("Tasks for today"));
+        let sort = &SortOrder::Value;
+        let result = reschedule_overdue(&config, Flag::Today, sort).await;
+
+        assert_eq!(
+            result,
+            Ok(String::from("Rescheduled 1 tasks in today to today"))
+        );
         mock.assert();
+        overdue_mock.assert();
+        for untouched_mock in untouched_mocks {
+            untouched_mock.assert();
+        }
     }
 
     #[tokio::test]
-    async fn test_view_with_project() {
+    async fn test_reschedule_overdue_leaves_tasks_due_earlier_today() {
         let mut server = mockito::Server::new_async().await;
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(1);
+
+        // Due an hour before the fixed time of tests, so overdue but still today
+        let earlier_today = format!("{}T09:00:00Z", time::date_string_today(&config).unwrap());
+        let mut task =
+            serde_json::from_str::<serde_json::Value>(&ResponseFromFile::Task.read().await)
+                .unwrap();
+        task["id"] = serde_json::json!("9Aaaa0bbbbbbB0b0");
+        task["due"] = serde_json::json!({
+            "date": earlier_today,
+            "is_recurring": false,
+            "string": "today 9am",
+            "lang": "en",
+            "timezone": null
+        });
+        let parsed = tasks::json_to_task(task.to_string()).unwrap();
+        assert!(tasks::is_overdue(&parsed, &config));
+
         let mock = server
-            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .mock(
+                "GET",
+                "/api/v1/tasks/filter?query=today%20%7C%20overdue&limit=200",
+            )
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .with_body(serde_json::json!({"results": [task], "next_cursor": null}).to_string())
+            .create_async()
+            .await;
+        let update_mock = server
+            .mock("POST", "/api/v1/tasks/9Aaaa0bbbbbbB0b0")
+            .expect(0)
             .create_async()
             .await;
 
-        let config = test::fixtures::config().await.with_mock_url(server.url());
-
-        let mut config_with_timezone = config
-            .with_timezone("US/Pacific")
-            .with_mock_url(server.url());
-
-        let binding = config_with_timezone.projects().await.unwrap();
-        let project = binding.first().unwrap().clone();
-        let sort = &SortOrder::Value;
-
-        let tasks = view(&mut config_with_timezone, Flag::Project(project), sort)
-            .await
-            .unwrap();
+        let result = reschedule_overdue(&config, Flag::Today, &SortOrder::Value).await;
 
-        assert!(tasks.contains("Tasks for"));
-        assert!(tasks.contains("- TEST\n"));
+        assert_eq!(result, Ok(String::from("No overdue tasks for today")));
         mock.assert();
+        update_mock.assert();
     }
 }
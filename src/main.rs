@@ -9,15 +9,16 @@ extern crate clap;
 use cargo::Version;
 use clap::{Parser, Subcommand};
 use config::Config;
-use errors::Error;
+use errors::{Error, ErrorKind};
 use input::DateTimeInput;
-use lists::Flag;
+use lists::{Flag, ViewOptions};
 use shell::Shell;
 use std::fmt::Display;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tasks::priority::Priority;
-use tasks::{SortOrder, TaskAttribute, priority};
+use tasks::{FormatType, SortOrder, TaskAttribute, priority};
 use tokio::sync::mpsc::UnboundedSender;
 use walkdir::WalkDir;
 
@@ -83,6 +84,10 @@ struct Cli {
     /// Time to wait for a response from API in seconds. Defaults to 30.
     timeout: Option<u64>,
 
+    #[arg(long, default_value_t = false)]
+    /// Print errors as JSON objects on stderr, the TOD_JSON_ERRORS environment variable does the same
+    json_errors: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -278,6 +283,18 @@ enum TaskCommands {
     #[clap(alias = "m")]
     /// (m) Add a comment to the last task fetched with the next command
     Comment(TaskComment),
+
+    #[clap(alias = "x")]
+    /// (x) Complete a task by its id
+    Close(TaskClose),
+
+    #[clap(alias = "r")]
+    /// (r) Reopen a completed task by its id
+    Reopen(TaskReopen),
+
+    #[clap(alias = "g")]
+    /// (g) Merge tasks into the first one after a confirmation, copying their content and comments into a comment and completing the rest
+    Merge(TaskMerge),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -344,6 +361,27 @@ struct TaskNext {
 #[derive(Parser, Debug, Clone)]
 struct TaskComplete {}
 
+#[derive(Parser, Debug, Clone)]
+struct TaskClose {
+    #[arg(short, long)]
+    /// The id of the task to complete, shown by list view --show-ids
+    id: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct TaskMerge {
+    #[arg(short, long, num_args(2..), required = true)]
+    /// The ids of the tasks to merge, shown by list view --show-ids. The first task is kept
+    ids: Vec<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct TaskReopen {
+    #[arg(short, long)]
+    /// The id of the completed task to reopen
+    id: String,
+}
+
 #[derive(Parser, Debug, Clone)]
 struct TaskComment {
     #[arg(short, long)]
@@ -385,7 +423,108 @@ enum ListCommands {
 
     #[clap(alias = "i")]
     /// (i) Create tasks from a text file, one per line using natural language. Skips empty lines.
+    /// Lines can be annotated with priority, labels and a description, i.e. "Buy milk | p1 | @shopping | desc: 2 litres"
     Import(ListImport),
+
+    #[clap(alias = "u")]
+    /// (u) Find tasks with the same content in the same project and choose which one to keep. The rest are completed.
+    Deduplicate(ListDeduplicate),
+
+    #[clap(alias = "e")]
+    /// (e) Write tasks to a text file, one per line, in a format that can be imported
+    Export(ListExport),
+
+    #[clap(alias = "bk")]
+    /// (bk) Write tasks and their comments to a JSON file as a backup
+    Backup(ListBackup),
+
+    #[clap(alias = "a")]
+    /// (a) Complete all tasks at once after a single confirmation
+    CompleteAll(ListCompleteAll),
+
+    #[clap(alias = "y")]
+    /// (y) Show the number of tasks in each project
+    Summary(ListSummary),
+
+    #[clap(alias = "ep")]
+    /// (ep) List projects without active tasks and offer to delete them
+    EmptyProjects(ListEmptyProjects),
+
+    #[clap(alias = "m")]
+    /// (m) Move all tasks from one project to another
+    Move(ListMove),
+
+    #[clap(alias = "n")]
+    /// (n) Count the tasks matching a filter without listing them
+    Count(ListCount),
+
+    #[clap(alias = "f")]
+    /// (f) Show only the first task to work on, with its comments
+    Next(ListNext),
+
+    #[clap(alias = "o")]
+    /// (o) Reschedule all overdue tasks to today, leaving recurring tasks untouched
+    RescheduleOverdue(ListRescheduleOverdue),
+
+    #[clap(alias = "w")]
+    /// (w) Push the due date of all tasks later by a relative amount, i.e. "3 hours" or "2 days"
+    Snooze(ListSnooze),
+
+    #[clap(alias = "h")]
+    /// (h) Give every task the same duration, i.e. "30m" or "1 hour"
+    SetDuration(ListSetDuration),
+
+    #[clap(alias = "as")]
+    /// (as) Assign every task to a collaborator in a shared project
+    AssignAll(ListAssignAll),
+
+    #[clap(alias = "ms")]
+    /// (ms) Move every task into a section of its project
+    MoveToSection(ListMoveToSection),
+
+    #[clap(alias = "x")]
+    /// (x) List tasks in a project that have been completed
+    Completed(ListCompleted),
+
+    #[clap(alias = "cr")]
+    /// (cr) List tasks in a project completed between two dates, grouped by day
+    CompletedReport(ListCompletedReport),
+
+    #[clap(alias = "b")]
+    /// (b) Show how many tasks carry each label
+    LabelStats(ListLabelStats),
+
+    #[clap(alias = "hg")]
+    /// (hg) Show a bar for how many tasks have each priority
+    Histogram(ListHistogram),
+
+    #[clap(alias = "bp")]
+    /// (bp) Raise or lower the priority of every task by one or more levels
+    BumpPriority(ListBumpPriority),
+
+    #[clap(alias = "r")]
+    /// (r) Undo the last complete-all or prioritize
+    Undo(ListUndo),
+
+    #[clap(alias = "g")]
+    /// (g) Find tasks whose project is missing from config
+    Verify(ListVerify),
+
+    #[clap(alias = "j")]
+    /// (j) Replace one label with another on every task that has it
+    RenameLabel(ListRenameLabel),
+
+    #[clap(alias = "k")]
+    /// (k) Find tasks whose content matches some text or a regex
+    Search(ListSearch),
+
+    #[clap(alias = "p")]
+    /// (p) Add the same comment to every task
+    CommentAll(ListCommentAll),
+
+    #[clap(alias = "q")]
+    /// (q) Move all tasks to next Saturday, leaving recurring tasks untouched
+    PostponeToWeekend(ListPostponeToWeekend),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -398,9 +537,81 @@ struct ListView {
     /// The filter containing the tasks. Can add multiple filters separated by commas.
     filter: Option<String>,
 
-    #[arg(short = 't', long, default_value_t = SortOrder::Datetime)]
-    /// Choose how results should be sorted
-    sort: SortOrder,
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then datetime
+    sort: Option<SortOrder>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Output tasks as a JSON array instead of a colored list
+    json: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "json")]
+    /// Output tasks as a Markdown checklist instead of a colored list
+    markdown: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "markdown"])]
+    /// Output only the content of each task, one per line
+    content_only: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "markdown", "content_only", "show_ids"])]
+    /// Output tasks as aligned columns of content, priority, due date and labels
+    table: bool,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Reverse the sort order, tasks that tie keep their order
+    reverse: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use tasks that are due today or overdue instead of a project or filter
+    today: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use overdue tasks instead of a project or filter
+    overdue: bool,
+
+    #[arg(long)]
+    /// Only show tasks in the project or filter that have this label. Use --label for every task with a label
+    label_filter: Option<String>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Group tasks under headings by due date, with undated tasks last
+    group_by_due: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Show the Todoist id before each task
+    show_ids: bool,
+
+    #[arg(short, long)]
+    /// Write the tasks to this file instead of printing them
+    output: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["filter", "today", "overdue"])]
+    /// Only use tasks in this section of the project
+    section: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["project", "filter", "today", "overdue", "section", "label_filter"])]
+    /// Use every task with this label instead of a project or filter, i.e. work or @work. Use --label-filter to narrow a project or filter by label
+    label: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Leave out tasks assigned to someone else in shared projects
+    only_mine: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with_all = ["filter", "today", "overdue", "section"])]
+    /// Also include tasks from sub-projects of the project, grouped by project
+    subprojects: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "json")]
+    /// Show how many tasks are prioritized and have due dates before each group of tasks
+    stats_header: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "json")]
+    /// Show the total duration of each group of tasks and when they would be finished
+    show_eta: bool,
+
+    #[arg(long)]
+    /// Only show tasks due at a time within this window, i.e. 08:00-12:00. Tasks without a due time are left out
+    due_between: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -413,9 +624,41 @@ struct ListProcess {
     /// The filter containing the tasks. Can add multiple filters separated by commas.
     filter: Option<String>,
 
-    #[arg(short = 't', long, default_value_t = SortOrder::Value)]
-    /// Choose how results should be sorted
-    sort: SortOrder,
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Reverse the sort order, tasks that tie keep their order
+    reverse: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use tasks that are due today or overdue instead of a project or filter
+    today: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use overdue tasks instead of a project or filter
+    overdue: bool,
+
+    #[arg(long)]
+    /// Only act on the first N tasks after sorting
+    limit: Option<usize>,
+
+    #[arg(long, conflicts_with_all = ["filter", "today", "overdue"])]
+    /// Only use tasks in this section of the project
+    section: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["project", "filter", "today", "overdue", "section"])]
+    /// Use tasks with this label instead of a project or filter, i.e. work or @work
+    label: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Leave out tasks assigned to someone else in shared projects
+    only_mine: bool,
+
+    #[arg(long)]
+    /// Count down this many minutes after completing a task, Ctrl-C during the countdown exits
+    focus_minutes: Option<u32>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -428,9 +671,29 @@ struct ListTimebox {
     /// The filter containing the tasks, does not filter out tasks with durations unless specified in filter. Can add multiple filters separated by commas.
     filter: Option<String>,
 
-    #[arg(short = 't', long, default_value_t = SortOrder::Value)]
-    /// Choose how results should be sorted
-    sort: SortOrder,
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Reverse the sort order, tasks that tie keep their order
+    reverse: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use tasks that are due today or overdue instead of a project or filter
+    today: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use overdue tasks instead of a project or filter
+    overdue: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Show the changes that would be made without making them
+    dry_run: bool,
+
+    #[arg(long)]
+    /// Only act on the first N tasks after sorting
+    limit: Option<usize>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -443,9 +706,33 @@ struct ListPrioritize {
     /// The filter containing the tasks. Can add multiple filters separated by commas.
     filter: Option<String>,
 
-    #[arg(short = 't', long, default_value_t = SortOrder::Value)]
-    /// Choose how results should be sorted
-    sort: SortOrder,
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Reverse the sort order, tasks that tie keep their order
+    reverse: bool,
+
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=4))]
+    /// Only prioritize tasks that currently have this priority, from 1 (without priority) to 4 (highest). Defaults to 1 for projects and all tasks for filters.
+    from: Option<u8>,
+
+    #[arg(long, default_value_t = false)]
+    /// Use tasks that are due today or overdue instead of a project or filter
+    today: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use overdue tasks instead of a project or filter
+    overdue: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Show the changes that would be made without making them
+    dry_run: bool,
+
+    #[arg(long)]
+    /// Only act on the first N tasks after sorting
+    limit: Option<usize>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -462,9 +749,29 @@ struct ListLabel {
     /// Labels to select from, if left blank this will be fetched from API
     label: Vec<String>,
 
-    #[arg(short = 't', long, default_value_t = SortOrder::Value)]
-    /// Choose how results should be sorted
-    sort: SortOrder,
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Reverse the sort order, tasks that tie keep their order
+    reverse: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use tasks that are due today or overdue instead of a project or filter
+    today: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Use overdue tasks instead of a project or filter
+    overdue: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Show the changes that would be made without making them
+    dry_run: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Remove the labels from every task that has them instead of adding
+    remove: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -485,9 +792,13 @@ struct ListSchedule {
     /// Only schedule overdue tasks
     overdue: bool,
 
-    #[arg(short = 't', long, default_value_t = SortOrder::Value)]
-    /// Choose how results should be sorted
-    sort: SortOrder,
+    #[arg(short, long, default_value_t = false, conflicts_with_all = ["overdue", "skip_recurring"])]
+    /// Only schedule tasks without a due date, with the option to quit part way through
+    undated: bool,
+
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -500,137 +811,518 @@ struct ListDeadline {
     /// The filter containing the tasks. Can add multiple filters separated by commas.
     filter: Option<String>,
 
-    #[arg(short = 't', long, default_value_t = SortOrder::Value)]
-    /// Choose how results should be sorted
-    sort: SortOrder,
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
 }
 
 #[derive(Parser, Debug, Clone)]
 struct ListImport {
     #[arg(short, long)]
-    /// The file or directory to fuzzy find in
+    /// The file or directory to fuzzy find in. Use - to read from stdin.
     path: Option<String>,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Create the tasks without asking first, for scripts
+    yes: bool,
 }
 
-// -- CONFIG --
+#[derive(Parser, Debug, Clone)]
+struct ListDeduplicate {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
 
-#[derive(Subcommand, Debug, Clone)]
-enum ConfigCommands {
-    #[clap(alias = "v")]
-    /// (v) Check to see if tod is on the latest version, returns exit code 1 if out of date. Does not need a configuration file.
-    CheckVersion(ConfigCheckVersion),
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+}
 
-    #[clap(alias = "r")]
-    /// (r) Deletes the configuration file (if present). Errors if the file does not exist.
-    Reset(ConfigReset),
+#[derive(Parser, Debug, Clone)]
+struct ListExport {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
 
-    #[clap(alias = "tz")]
-    /// (tz) Change the timezone in the configuration file
-    SetTimezone(ConfigSetTimezone),
-}
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
 
-#[derive(Subcommand, Debug, Clone)]
-enum AuthCommands {
-    #[clap(alias = "l")]
-    /// (l) Log into Todoist using OAuth
-    Login(AuthLogin),
+    #[arg(short = 'o', long)]
+    /// The file to write the tasks to
+    path: Option<String>,
 }
 
-#[derive(Subcommand, Debug, Clone)]
-enum ShellCommands {
-    #[clap(alias = "b")]
-    /// (b) Generate shell completions for various shells. Does not need a configuration file
-    Completions(ShellCompletions),
-}
+#[derive(Parser, Debug, Clone)]
+struct ListBackup {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
 
-#[derive(Subcommand, Debug, Clone)]
-enum TestCommands {
-    #[clap(alias = "a")]
-    /// (a) Hit all API endpoints
-    All(TestAll),
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(short = 'o', long)]
+    /// The JSON file to write the backup to
+    path: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
-struct ConfigCheckVersion {}
+struct ListCompleteAll {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
 
-#[derive(Parser, Debug, Clone)]
-struct TestAll {}
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
 
-#[derive(Parser, Debug, Clone)]
-struct ConfigReset {
-    /// Skip confirmation and force deletion
-    #[arg(long)]
-    force: bool,
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+
+    #[arg(long, default_value_t = false)]
+    /// Pick which tasks to complete from a checkbox list instead of completing all of them
+    pick: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
-struct ConfigSetTimezone {
+struct ListRescheduleOverdue {
     #[arg(short, long)]
-    /// TimeZone to add, i.e. "Canada/Pacific"
-    timezone: Option<String>,
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
 }
 
 #[derive(Parser, Debug, Clone)]
-struct AuthLogin {}
+struct ListSnooze {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
 
-#[derive(Parser, Debug, Clone)]
-struct ShellCompletions {
-    shell: Shell,
-}
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
 
-enum FlagOptions {
-    Project,
-    Filter,
-}
+    #[arg(short, long)]
+    /// How much later to make each task, i.e. "30 minutes", "3 hours", "2 days" or "1 week"
+    delta: String,
 
-impl Display for FlagOptions {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FlagOptions::Project => write!(f, "Project"),
-            FlagOptions::Filter => write!(f, "Filter"),
-        }
-    }
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
 }
 
-#[tokio::main]
-async fn main() {
-    let cli = Cli::parse();
+#[derive(Parser, Debug, Clone)]
+struct ListSetDuration {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
 
-    // Channel for sending errors from async processes
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Error>();
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
 
-    let (bell_success, bell_error, result) = select_command(cli, tx).await;
-    while let Some(e) = rx.recv().await {
-        eprintln!("Error from async process: {e}");
-    }
+    #[arg(short, long)]
+    /// The duration to give each task, i.e. "30m", "45 minutes", "1 hour" or "2d". A plain number is minutes
+    duration: String,
 
-    match result {
-        Ok(text) => {
-            if bell_success {
-                terminal_bell()
-            }
-            println!("{text}");
-            std::process::exit(0);
-        }
-        Err(e) => {
-            if bell_error {
-                terminal_bell()
-            }
-            eprintln!("\n\n{e}");
-            std::process::exit(1);
-        }
-    }
-}
+    #[arg(short, long, default_value_t = false)]
+    /// Also replace durations that tasks already have
+    overwrite: bool,
 
-fn terminal_bell() {
-    print!("\x07");
-    std::io::stdout().flush().unwrap();
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
 }
 
-async fn select_command(
-    cli: Cli,
-    tx: UnboundedSender<Error>,
-) -> (bool, bool, Result<String, Error>) {
+#[derive(Parser, Debug, Clone)]
+struct ListAssignAll {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(short, long)]
+    /// Name or email of the collaborator to assign the tasks to
+    assignee: String,
+
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListMoveToSection {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(long)]
+    /// Name of the section to move the tasks to
+    section: String,
+
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListCompleted {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// Only show tasks completed on or after this date, i.e. 2025-01-31. Defaults to the last 30 days.
+    since: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListCompletedReport {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// First day of the report, i.e. 2025-01-31
+    since: String,
+
+    #[arg(short, long)]
+    /// Last day of the report, i.e. 2025-02-06. Defaults to today.
+    until: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListLabelStats {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListHistogram {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListBumpPriority {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(short, long, default_value_t = 1, allow_negative_numbers = true)]
+    /// How many levels to raise the priority by, negative to lower it
+    delta: i8,
+
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListSummary {}
+
+#[derive(Parser, Debug, Clone)]
+struct ListEmptyProjects {}
+
+#[derive(Parser, Debug, Clone)]
+struct ListUndo {}
+
+#[derive(Parser, Debug, Clone)]
+struct ListPostponeToWeekend {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Use tasks that are due today or overdue instead of a project or filter
+    today: bool,
+
+    #[arg(short = 't', long)]
+    /// Choose the order tasks are updated in. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListCommentAll {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(short, long)]
+    /// The comment to add to each task
+    content: String,
+
+    #[arg(short = 't', long)]
+    /// Choose the order comments are added in. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListSearch {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    /// The text to look for in task content, case is ignored
+    pattern: String,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Treat the pattern as a regular expression
+    regex: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListRenameLabel {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(long)]
+    /// The label to replace
+    from: String,
+
+    #[arg(long)]
+    /// The label to use instead
+    to: String,
+
+    #[arg(short = 't', long)]
+    /// Choose the order tasks are updated in. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListVerify {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListCount {
+    #[arg(short, long)]
+    /// The filter to count tasks for
+    filter: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListNext {
+    #[arg(short, long)]
+    /// The project containing the tasks
+    project: Option<String>,
+
+    #[arg(short, long)]
+    /// The filter containing the tasks. Can add multiple filters separated by commas.
+    filter: Option<String>,
+
+    #[arg(long, default_value_t = false)]
+    /// Use tasks that are due today or overdue instead of a project or filter
+    today: bool,
+
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ListMove {
+    #[arg(long)]
+    /// The project to move tasks from
+    from: Option<String>,
+
+    #[arg(long)]
+    /// The project to move tasks to
+    to: Option<String>,
+
+    #[arg(short = 't', long)]
+    /// Choose how results should be sorted. Defaults to default_sort in config, then value
+    sort: Option<SortOrder>,
+}
+
+// -- CONFIG --
+
+#[derive(Subcommand, Debug, Clone)]
+enum ConfigCommands {
+    #[clap(alias = "v")]
+    /// (v) Check to see if tod is on the latest version, returns exit code 1 if out of date. Does not need a configuration file.
+    CheckVersion(ConfigCheckVersion),
+
+    #[clap(alias = "r")]
+    /// (r) Deletes the configuration file (if present). Errors if the file does not exist.
+    Reset(ConfigReset),
+
+    #[clap(alias = "tz")]
+    /// (tz) Change the timezone in the configuration file
+    SetTimezone(ConfigSetTimezone),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum AuthCommands {
+    #[clap(alias = "l")]
+    /// (l) Log into Todoist using OAuth
+    Login(AuthLogin),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ShellCommands {
+    #[clap(alias = "b")]
+    /// (b) Generate shell completions for various shells. Does not need a configuration file
+    Completions(ShellCompletions),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum TestCommands {
+    #[clap(alias = "a")]
+    /// (a) Hit all API endpoints
+    All(TestAll),
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ConfigCheckVersion {}
+
+#[derive(Parser, Debug, Clone)]
+struct TestAll {}
+
+#[derive(Parser, Debug, Clone)]
+struct ConfigReset {
+    /// Skip confirmation and force deletion
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct ConfigSetTimezone {
+    #[arg(short, long)]
+    /// TimeZone to add, i.e. "Canada/Pacific"
+    timezone: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+struct AuthLogin {}
+
+#[derive(Parser, Debug, Clone)]
+struct ShellCompletions {
+    shell: Shell,
+}
+
+enum FlagOptions {
+    Project,
+    Filter,
+}
+
+impl Display for FlagOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlagOptions::Project => write!(f, "Project"),
+            FlagOptions::Filter => write!(f, "Filter"),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let json_errors = cli.json_errors
+        || std::env::var_os("TOD_JSON_ERRORS").is_some_and(|value| !value.is_empty());
+
+    // Channel for sending errors from async processes
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Error>();
+
+    let (bell_success, bell_error, result) = select_command(cli, tx).await;
+    while let Some(e) = rx.recv().await {
+        if json_errors {
+            eprintln!("{}", e.json());
+        } else if e.kind == ErrorKind::Warning {
+            eprintln!("{}", color::yellow_string(&e.message));
+        } else {
+            eprintln!("Error from async process: {e}");
+        }
+    }
+
+    match result {
+        Ok(text) => {
+            if bell_success {
+                terminal_bell()
+            }
+            println!("{text}");
+            std::process::exit(0);
+        }
+        Err(e) => {
+            if bell_error {
+                terminal_bell()
+            }
+            if json_errors {
+                eprintln!("{}", e.json());
+            } else {
+                eprintln!("\n\n{e}");
+                if e.is_retryable() {
+                    eprintln!("This may be temporary, try again in a moment");
+                }
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn terminal_bell() {
+    print!("\x07");
+    std::io::stdout().flush().unwrap();
+}
+
+async fn select_command(
+    cli: Cli,
+    tx: UnboundedSender<Error>,
+) -> (bool, bool, Result<String, Error>) {
     match &cli.command {
         // Project
         Commands::Project(ProjectCommands::Create(args)) => {
@@ -641,10 +1333,77 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                project_create(config, args).await,
+                project_create(config, args).await,
+            )
+        }
+        Commands::Project(ProjectCommands::List(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                project_list(config, args).await,
+            )
+        }
+        Commands::Project(ProjectCommands::Remove(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                project_remove(config, args).await,
+            )
+        }
+        Commands::Project(ProjectCommands::Rename(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                project_rename(config, args).await,
+            )
+        }
+        Commands::Project(ProjectCommands::Import(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                project_import(config, args).await,
+            )
+        }
+        Commands::Project(ProjectCommands::Empty(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                project_empty(&config, args).await,
+            )
+        }
+        Commands::Project(ProjectCommands::Delete(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                project_delete(config, args).await,
             )
         }
-        Commands::Project(ProjectCommands::List(args)) => {
+
+        Commands::Section(SectionCommands::Create(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -652,10 +1411,12 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                project_list(config, args).await,
+                section_create(config, args).await,
             )
         }
-        Commands::Project(ProjectCommands::Remove(args)) => {
+
+        // Task
+        Commands::Task(TaskCommands::QuickAdd(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -663,10 +1424,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                project_remove(config, args).await,
+                task_quick_add(config, args).await,
             )
         }
-        Commands::Project(ProjectCommands::Rename(args)) => {
+        Commands::Task(TaskCommands::Create(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -674,10 +1435,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                project_rename(config, args).await,
+                task_create(config, args).await,
             )
         }
-        Commands::Project(ProjectCommands::Import(args)) => {
+        Commands::Task(TaskCommands::Edit(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -685,10 +1446,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                project_import(config, args).await,
+                task_edit(config, args).await,
             )
         }
-        Commands::Project(ProjectCommands::Empty(args)) => {
+        Commands::Task(TaskCommands::Next(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -696,10 +1457,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                project_empty(&config, args).await,
+                task_next(config, args).await,
             )
         }
-        Commands::Project(ProjectCommands::Delete(args)) => {
+        Commands::Task(TaskCommands::Complete(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -707,11 +1468,254 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                project_delete(config, args).await,
+                task_complete(config, args).await,
+            )
+        }
+        Commands::Task(TaskCommands::Close(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                task_close(config, args).await,
+            )
+        }
+        Commands::Task(TaskCommands::Reopen(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                task_reopen(config, args).await,
+            )
+        }
+        Commands::Task(TaskCommands::Merge(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                task_merge(config, args).await,
+            )
+        }
+        Commands::Task(TaskCommands::Comment(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                task_comment(config, args).await,
             )
         }
 
-        Commands::Section(SectionCommands::Create(args)) => {
+        // List
+        Commands::List(ListCommands::View(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_view(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Process(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_process(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Prioritize(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_prioritize(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Label(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_label(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Schedule(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_schedule(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Deadline(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_deadline(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Timebox(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_timebox(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Import(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_import(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Deduplicate(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_deduplicate(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Export(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_export(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Backup(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_backup(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::CompleteAll(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_complete_all(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Summary(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_summary(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::EmptyProjects(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_empty_projects(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Undo(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_undo(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Move(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_move(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::Count(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_count(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::PostponeToWeekend(args)) => {
+            let config = match fetch_config(&cli, &tx).await {
+                Ok(config) => config,
+                Err(e) => return (true, true, Err(e)),
+            };
+            (
+                config.bell_on_success,
+                config.bell_on_failure,
+                list_postpone_to_weekend(config, args).await,
+            )
+        }
+        Commands::List(ListCommands::CommentAll(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -719,12 +1723,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                section_create(config, args).await,
+                list_comment_all(config, args).await,
             )
         }
-
-        // Task
-        Commands::Task(TaskCommands::QuickAdd(args)) => {
+        Commands::List(ListCommands::Search(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -732,10 +1734,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                task_quick_add(config, args).await,
+                list_search(config, args).await,
             )
         }
-        Commands::Task(TaskCommands::Create(args)) => {
+        Commands::List(ListCommands::RenameLabel(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -743,10 +1745,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                task_create(config, args).await,
+                list_rename_label(config, args).await,
             )
         }
-        Commands::Task(TaskCommands::Edit(args)) => {
+        Commands::List(ListCommands::Verify(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -754,10 +1756,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                task_edit(config, args).await,
+                list_verify(config, args).await,
             )
         }
-        Commands::Task(TaskCommands::Next(args)) => {
+        Commands::List(ListCommands::Next(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -765,10 +1767,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                task_next(config, args).await,
+                list_next(config, args).await,
             )
         }
-        Commands::Task(TaskCommands::Complete(args)) => {
+        Commands::List(ListCommands::RescheduleOverdue(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -776,10 +1778,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                task_complete(config, args).await,
+                list_reschedule_overdue(config, args).await,
             )
         }
-        Commands::Task(TaskCommands::Comment(args)) => {
+        Commands::List(ListCommands::Snooze(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -787,12 +1789,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                task_comment(config, args).await,
+                list_snooze(config, args).await,
             )
         }
-
-        // List
-        Commands::List(ListCommands::View(args)) => {
+        Commands::List(ListCommands::SetDuration(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -800,10 +1800,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_view(config, args).await,
+                list_set_duration(config, args).await,
             )
         }
-        Commands::List(ListCommands::Process(args)) => {
+        Commands::List(ListCommands::AssignAll(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -811,10 +1811,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_process(config, args).await,
+                list_assign_all(config, args).await,
             )
         }
-        Commands::List(ListCommands::Prioritize(args)) => {
+        Commands::List(ListCommands::MoveToSection(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -822,10 +1822,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_prioritize(config, args).await,
+                list_move_to_section(config, args).await,
             )
         }
-        Commands::List(ListCommands::Label(args)) => {
+        Commands::List(ListCommands::Completed(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -833,10 +1833,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_label(config, args).await,
+                list_completed(config, args).await,
             )
         }
-        Commands::List(ListCommands::Schedule(args)) => {
+        Commands::List(ListCommands::CompletedReport(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -844,10 +1844,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_schedule(config, args).await,
+                list_completed_report(config, args).await,
             )
         }
-        Commands::List(ListCommands::Deadline(args)) => {
+        Commands::List(ListCommands::LabelStats(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -855,10 +1855,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_deadline(config, args).await,
+                list_label_stats(config, args).await,
             )
         }
-        Commands::List(ListCommands::Timebox(args)) => {
+        Commands::List(ListCommands::Histogram(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -866,10 +1866,10 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_timebox(config, args).await,
+                list_histogram(config, args).await,
             )
         }
-        Commands::List(ListCommands::Import(args)) => {
+        Commands::List(ListCommands::BumpPriority(args)) => {
             let config = match fetch_config(&cli, &tx).await {
                 Ok(config) => config,
                 Err(e) => return (true, true, Err(e)),
@@ -877,7 +1877,7 @@ async fn select_command(
             (
                 config.bell_on_success,
                 config.bell_on_failure,
-                list_import(config, args).await,
+                list_bump_priority(config, args).await,
             )
         }
 
@@ -966,7 +1966,7 @@ async fn task_quick_add(config: Config, args: &TaskQuickAdd) -> Result<String, E
     } else {
         (content, None)
     };
-    todoist::quick_create_task(&config, &content, reminder).await?;
+    todoist::quick_create_task(&config, &content, reminder, None).await?;
     Ok(color::green_string("✓"))
 }
 
@@ -1105,14 +2105,14 @@ async fn task_edit(config: Config, args: &TaskEdit) -> Result<String, Error> {
     let TaskEdit { project, filter } = args;
     match fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await? {
         Flag::Project(project) => projects::edit_task(&config, &project).await,
-        Flag::Filter(filter) => filters::edit_task(&config, filter).await,
+        flag => filters::edit_task(&config, flag.query()).await,
     }
 }
 async fn task_next(config: Config, args: &TaskNext) -> Result<String, Error> {
     let TaskNext { project, filter } = args;
     match fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await? {
         Flag::Project(project) => projects::next_task(config, &project).await,
-        Flag::Filter(filter) => filters::next_task(&config, &filter).await,
+        flag => filters::next_task(&config, &flag.query()).await,
     }
 }
 
@@ -1130,6 +2130,21 @@ async fn task_complete(config: Config, _args: &TaskComplete) -> Result<String, E
     }
 }
 
+async fn task_close(config: Config, args: &TaskClose) -> Result<String, Error> {
+    let TaskClose { id } = args;
+    lists::close_by_id(&config, id).await
+}
+
+async fn task_reopen(config: Config, args: &TaskReopen) -> Result<String, Error> {
+    let TaskReopen { id } = args;
+    lists::reopen_by_id(&config, id).await
+}
+
+async fn task_merge(config: Config, args: &TaskMerge) -> Result<String, Error> {
+    let TaskMerge { ids } = args;
+    lists::merge(&config, ids).await
+}
+
 async fn task_comment(config: Config, args: &TaskComment) -> Result<String, Error> {
     let TaskComment { content } = args;
     match config.next_task() {
@@ -1198,7 +2213,7 @@ async fn project_delete(config: Config, args: &ProjectDelete) -> Result<String,
             Flag::Project(project) => project,
             _ => unreachable!(),
         };
-        let tasks = todoist::all_tasks_by_project(&config, &project, None).await?;
+        let tasks = todoist::all_tasks_by_project(&config, &project, None, false).await?;
 
         if !tasks.is_empty() {
             println!();
@@ -1272,10 +2287,72 @@ async fn list_view(config: Config, args: &ListView) -> Result<String, Error> {
         project,
         filter,
         sort,
+        json,
+        markdown,
+        content_only,
+        table,
+        reverse,
+        today,
+        overdue,
+        label_filter,
+        group_by_due,
+        show_ids,
+        output,
+        section,
+        label,
+        only_mine,
+        subprojects,
+        stats_header,
+        show_eta,
+        due_between,
     } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Datetime);
 
-    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
-    lists::view(&mut config, flag, sort).await
+    if output.is_some() || *markdown || *content_only || *table {
+        color::disable();
+    }
+
+    let format = if *json {
+        FormatType::Json
+    } else if *markdown {
+        FormatType::Markdown
+    } else if *content_only {
+        FormatType::Plain
+    } else if *table {
+        FormatType::Table
+    } else {
+        FormatType::List
+    };
+    let flag = match (section, label) {
+        (Some(section), _) => fetch_section(project.as_deref(), section, &config).await?,
+        (None, Some(label)) => Flag::Label(label.trim_start_matches('@').to_string()),
+        (None, None) => {
+            fetch_list_flag(
+                project.as_deref(),
+                filter.as_deref(),
+                *today,
+                *overdue,
+                &config,
+            )
+            .await?
+        }
+    };
+    let flag = match flag {
+        Flag::Project(project) if *subprojects => Flag::ProjectTree(project),
+        flag => flag,
+    };
+    let options = ViewOptions {
+        reverse: *reverse,
+        label_filter: label_filter.as_deref(),
+        group_by_due: *group_by_due,
+        show_ids: *show_ids,
+        output: output.as_deref(),
+        only_mine: *only_mine,
+        stats_header: *stats_header,
+        show_eta: *show_eta,
+        due_window: due_between.as_deref(),
+    };
+    lists::view(&mut config, flag, sort, format, &options).await
 }
 
 async fn list_label(config: Config, args: &ListLabel) -> Result<String, Error> {
@@ -1284,10 +2361,31 @@ async fn list_label(config: Config, args: &ListLabel) -> Result<String, Error> {
         project,
         label: labels,
         sort,
+        reverse,
+        today,
+        overdue,
+        dry_run,
+        remove,
     } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    if *remove && labels.is_empty() {
+        return Err(Error::new(
+            "list_label",
+            "Provide the labels to remove with --label",
+        ));
+    }
     let labels = maybe_fetch_labels(&config, labels).await?;
-    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
-    lists::label(&config, flag, &labels, sort).await
+    let flag = fetch_list_flag(
+        project.as_deref(),
+        filter.as_deref(),
+        *today,
+        *overdue,
+        &config,
+    )
+    .await?;
+    lists::label(&config, flag, &labels, sort, *reverse, *dry_run, *remove)
+        .await
+        .map(|outcome| outcome.to_string())
 }
 
 async fn list_process(config: Config, args: &ListProcess) -> Result<String, Error> {
@@ -1295,9 +2393,40 @@ async fn list_process(config: Config, args: &ListProcess) -> Result<String, Erro
         project,
         filter,
         sort,
+        reverse,
+        today,
+        overdue,
+        limit,
+        section,
+        label,
+        only_mine,
+        focus_minutes,
     } = args;
-    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
-    lists::process(&config, flag, sort).await
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = match (section, label) {
+        (Some(section), _) => fetch_section(project.as_deref(), section, &config).await?,
+        (None, Some(label)) => Flag::Label(label.trim_start_matches('@').to_string()),
+        (None, None) => {
+            fetch_list_flag(
+                project.as_deref(),
+                filter.as_deref(),
+                *today,
+                *overdue,
+                &config,
+            )
+            .await?
+        }
+    };
+    lists::process(
+        &config,
+        flag,
+        sort,
+        *reverse,
+        *limit,
+        *only_mine,
+        focus_minutes.map(|minutes| Duration::from_secs(u64::from(minutes) * 60)),
+    )
+    .await
 }
 
 async fn list_timebox(config: Config, args: &ListTimebox) -> Result<String, Error> {
@@ -1305,9 +2434,24 @@ async fn list_timebox(config: Config, args: &ListTimebox) -> Result<String, Erro
         project,
         filter,
         sort,
+        reverse,
+        today,
+        overdue,
+        dry_run,
+        limit,
     } = args;
-    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
-    lists::timebox(&config, flag, sort).await
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_list_flag(
+        project.as_deref(),
+        filter.as_deref(),
+        *today,
+        *overdue,
+        &config,
+    )
+    .await?;
+    lists::timebox(&config, flag, sort, *reverse, *dry_run, *limit)
+        .await
+        .map(|outcome| outcome.to_string())
 }
 
 async fn list_prioritize(config: Config, args: &ListPrioritize) -> Result<String, Error> {
@@ -1315,15 +2459,306 @@ async fn list_prioritize(config: Config, args: &ListPrioritize) -> Result<String
         project,
         filter,
         sort,
+        reverse,
+        from,
+        today,
+        overdue,
+        dry_run,
+        limit,
     } = args;
-    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
-    lists::prioritize(&config, flag, sort).await
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_list_flag(
+        project.as_deref(),
+        filter.as_deref(),
+        *today,
+        *overdue,
+        &config,
+    )
+    .await?;
+    let from = priority::from_integer(from);
+    lists::prioritize(&config, flag, sort, *reverse, from, *dry_run, *limit)
+        .await
+        .map(|outcome| outcome.to_string())
 }
 async fn list_import(config: Config, args: &ListImport) -> Result<String, Error> {
-    let ListImport { path } = args;
+    let ListImport { path, yes } = args;
+    let path = fetch_string(path.as_deref(), &config, input::PATH)?;
+    let file_path = if path == lists::STDIN_PATH {
+        path
+    } else {
+        select_file(path, &config)?
+    };
+    lists::import(&config, &file_path, *yes).await
+}
+
+async fn list_deduplicate(config: Config, args: &ListDeduplicate) -> Result<String, Error> {
+    let ListDeduplicate { project, filter } = args;
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::deduplicate(&config, flag).await
+}
+
+async fn list_export(config: Config, args: &ListExport) -> Result<String, Error> {
+    let ListExport {
+        project,
+        filter,
+        path,
+    } = args;
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    let path = fetch_string(path.as_deref(), &config, input::PATH)?;
+    lists::export(&config, flag, &path).await
+}
+
+async fn list_backup(config: Config, args: &ListBackup) -> Result<String, Error> {
+    let ListBackup {
+        project,
+        filter,
+        path,
+    } = args;
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
     let path = fetch_string(path.as_deref(), &config, input::PATH)?;
-    let file_path = select_file(path, &config)?;
-    lists::import(&config, &file_path).await
+    lists::backup(&config, flag, &path).await
+}
+
+async fn list_complete_all(config: Config, args: &ListCompleteAll) -> Result<String, Error> {
+    let ListCompleteAll {
+        project,
+        filter,
+        sort,
+        pick,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::complete_all(&config, flag, sort, *pick)
+        .await
+        .map(|outcome| outcome.to_string())
+}
+
+async fn list_reschedule_overdue(
+    config: Config,
+    args: &ListRescheduleOverdue,
+) -> Result<String, Error> {
+    let ListRescheduleOverdue {
+        project,
+        filter,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::reschedule_overdue(&config, flag, sort).await
+}
+
+async fn list_snooze(config: Config, args: &ListSnooze) -> Result<String, Error> {
+    let ListSnooze {
+        project,
+        filter,
+        delta,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::snooze(&config, flag, delta, sort).await
+}
+
+async fn list_next(config: Config, args: &ListNext) -> Result<String, Error> {
+    let ListNext {
+        project,
+        filter,
+        today,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_list_flag(
+        project.as_deref(),
+        filter.as_deref(),
+        *today,
+        false,
+        &config,
+    )
+    .await?;
+    lists::next(&config, flag, sort).await
+}
+
+async fn list_set_duration(config: Config, args: &ListSetDuration) -> Result<String, Error> {
+    let ListSetDuration {
+        project,
+        filter,
+        duration,
+        overwrite,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::set_all_durations(&config, flag, duration, sort, *overwrite).await
+}
+
+async fn list_assign_all(config: Config, args: &ListAssignAll) -> Result<String, Error> {
+    let ListAssignAll {
+        project,
+        filter,
+        assignee,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::assign_all(&config, flag, assignee, sort).await
+}
+
+async fn list_move_to_section(config: Config, args: &ListMoveToSection) -> Result<String, Error> {
+    let ListMoveToSection {
+        project,
+        filter,
+        section,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::move_to_section(&config, flag, section, sort).await
+}
+
+async fn list_completed(config: Config, args: &ListCompleted) -> Result<String, Error> {
+    let ListCompleted { project, since } = args;
+    let project = match fetch_project(project.as_deref(), &config).await? {
+        Flag::Project(project) => project,
+        _ => unreachable!(),
+    };
+    lists::completed(&config, &project, since.as_deref()).await
+}
+
+async fn list_completed_report(
+    config: Config,
+    args: &ListCompletedReport,
+) -> Result<String, Error> {
+    let ListCompletedReport {
+        project,
+        since,
+        until,
+    } = args;
+    let project = match fetch_project(project.as_deref(), &config).await? {
+        Flag::Project(project) => project,
+        _ => unreachable!(),
+    };
+    lists::completed_report(&config, &project, since, until.as_deref()).await
+}
+
+async fn list_label_stats(config: Config, args: &ListLabelStats) -> Result<String, Error> {
+    let ListLabelStats { project, filter } = args;
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::label_stats(&config, flag).await
+}
+
+async fn list_histogram(config: Config, args: &ListHistogram) -> Result<String, Error> {
+    let ListHistogram { project, filter } = args;
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::histogram(&config, flag).await
+}
+
+async fn list_bump_priority(config: Config, args: &ListBumpPriority) -> Result<String, Error> {
+    let ListBumpPriority {
+        project,
+        filter,
+        delta,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::bump_priority(&config, flag, *delta, sort).await
+}
+
+async fn list_postpone_to_weekend(
+    config: Config,
+    args: &ListPostponeToWeekend,
+) -> Result<String, Error> {
+    let ListPostponeToWeekend {
+        project,
+        filter,
+        today,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_list_flag(
+        project.as_deref(),
+        filter.as_deref(),
+        *today,
+        false,
+        &config,
+    )
+    .await?;
+    lists::postpone_to_weekend(&config, flag, sort).await
+}
+
+async fn list_comment_all(config: Config, args: &ListCommentAll) -> Result<String, Error> {
+    let ListCommentAll {
+        project,
+        filter,
+        content,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::comment_all(&config, flag, content, sort).await
+}
+
+async fn list_search(config: Config, args: &ListSearch) -> Result<String, Error> {
+    let ListSearch {
+        project,
+        filter,
+        pattern,
+        regex,
+    } = args;
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::search(&config, flag, pattern, *regex).await
+}
+
+async fn list_rename_label(config: Config, args: &ListRenameLabel) -> Result<String, Error> {
+    let ListRenameLabel {
+        project,
+        filter,
+        from,
+        to,
+        sort,
+    } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::rename_label(&config, flag, from, to, sort).await
+}
+
+async fn list_verify(config: Config, args: &ListVerify) -> Result<String, Error> {
+    let ListVerify { project, filter } = args;
+    let flag = fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await?;
+    lists::verify(&config, flag).await
+}
+
+async fn list_undo(config: Config, _args: &ListUndo) -> Result<String, Error> {
+    lists::undo(&config).await
+}
+
+async fn list_summary(config: Config, _args: &ListSummary) -> Result<String, Error> {
+    lists::summary(&config).await
+}
+
+async fn list_empty_projects(config: Config, _args: &ListEmptyProjects) -> Result<String, Error> {
+    lists::empty_projects(&config).await
+}
+
+async fn list_move(config: Config, args: &ListMove) -> Result<String, Error> {
+    let ListMove { from, to, sort } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
+    let from = match fetch_project(from.as_deref(), &config).await? {
+        Flag::Project(project) => project,
+        _ => unreachable!(),
+    };
+    let to = match fetch_project(to.as_deref(), &config).await? {
+        Flag::Project(project) => project,
+        _ => unreachable!(),
+    };
+    lists::move_all(&config, &from, &to, sort).await
+}
+
+async fn list_count(config: Config, args: &ListCount) -> Result<String, Error> {
+    let ListCount { filter } = args;
+    let filter = todoist::resolve_filter_names(&config, filter).await;
+    let count = todoist::count_tasks_by_filter(&config, &filter).await?;
+    Ok(count.to_string())
 }
 
 fn select_file(path_or_file: String, config: &Config) -> Result<String, Error> {
@@ -1343,10 +2778,10 @@ fn select_file(path_or_file: String, config: &Config) -> Result<String, Error> {
     } else if Path::is_file(path) {
         Ok(path_or_file)
     } else {
-        Err(Error {
-            source: "select_file".to_string(),
-            message: format!("{path_or_file} is neither a file nor a directory"),
-        })
+        Err(Error::new(
+            "select_file",
+            &format!("{path_or_file} is neither a file nor a directory"),
+        ))
     }
 }
 
@@ -1364,10 +2799,12 @@ async fn list_schedule(config: Config, args: &ListSchedule) -> Result<String, Er
         filter,
         skip_recurring,
         overdue,
+        undated,
         sort,
     } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
     match fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await? {
-        Flag::Filter(filter) => filters::schedule(&config, &filter, sort).await,
+        flag if *undated => lists::schedule(&config, flag, sort).await,
         Flag::Project(project) => {
             let task_filter = if *overdue {
                 projects::TaskFilter::Overdue
@@ -1377,6 +2814,7 @@ async fn list_schedule(config: Config, args: &ListSchedule) -> Result<String, Er
 
             projects::schedule(&config, &project, task_filter, *skip_recurring, sort).await
         }
+        flag => filters::schedule(&config, &flag.query(), sort).await,
     }
 }
 
@@ -1386,9 +2824,10 @@ async fn list_deadline(config: Config, args: &ListDeadline) -> Result<String, Er
         filter,
         sort,
     } = args;
+    let sort = &config.sort_order(sort.as_ref(), SortOrder::Value);
     match fetch_project_or_filter(project.as_deref(), filter.as_deref(), &config).await? {
-        Flag::Filter(filter) => filters::deadline(&config, &filter, sort).await,
         Flag::Project(project) => projects::deadline(&config, &project, sort).await,
+        flag => filters::deadline(&config, &flag.query(), sort).await,
     }
 }
 
@@ -1426,6 +2865,7 @@ async fn fetch_config(cli: &Cli, tx: &UnboundedSender<Error>) -> Result<Config,
         verbose,
         config: config_path,
         timeout,
+        json_errors: _,
         command: _,
     } = cli;
 
@@ -1439,7 +2879,9 @@ async fn fetch_config(cli: &Cli, tx: &UnboundedSender<Error>) -> Result<Config,
 
     tokio::spawn(async move { async_config.check_for_latest_version().await });
 
-    config.maybe_set_timezone().await
+    let config = config.maybe_set_timezone().await?;
+    color::apply_config(&config);
+    Ok(config)
 }
 
 fn fetch_string(
@@ -1480,14 +2922,41 @@ async fn fetch_project(project_name: Option<&str>, config: &Config) -> Result<Fl
     }
 }
 
-fn fetch_filter(filter: Option<&str>, config: &Config) -> Result<Flag, Error> {
-    match filter {
-        Some(string) => Ok(Flag::Filter(string.to_owned())),
-        None => {
-            let string = input::string(input::FILTER, config.mock_string.clone())?;
-            Ok(Flag::Filter(string))
-        }
-    }
+/// Looks up a section by name in the project, selecting the project first if it was not given
+async fn fetch_section(
+    project: Option<&str>,
+    section_name: &str,
+    config: &Config,
+) -> Result<Flag, Error> {
+    let project = match fetch_project(project, config).await? {
+        Flag::Project(project) => project,
+        _ => unreachable!(),
+    };
+
+    todoist::all_sections_by_project(config, &project, None)
+        .await?
+        .into_iter()
+        .find(|section| section.name == section_name)
+        .map(Flag::Section)
+        .ok_or_else(|| {
+            Error::new(
+                "fetch_section",
+                &format!(
+                    "Could not find section '{section_name}' in {}",
+                    project.name
+                ),
+            )
+        })
+}
+
+/// A filter flag from a query or the name of a saved filter
+async fn fetch_filter(filter: Option<&str>, config: &Config) -> Result<Flag, Error> {
+    let string = match filter {
+        Some(string) => string.to_string(),
+        None => input::string(input::FILTER, config.mock_string.clone())?,
+    };
+    let filter = todoist::resolve_filter_names(config, &string).await;
+    Ok(Flag::from_filter(&filter))
 }
 
 async fn fetch_project_or_filter(
@@ -1497,7 +2966,7 @@ async fn fetch_project_or_filter(
 ) -> Result<Flag, Error> {
     match (project, filter) {
         (Some(_), None) => fetch_project(project, config).await,
-        (None, Some(_)) => fetch_filter(filter, config),
+        (None, Some(_)) => fetch_filter(filter, config).await,
         (Some(_), Some(_)) => Err(Error::new(
             "project_or_filter",
             "Must select project OR filter",
@@ -1506,12 +2975,31 @@ async fn fetch_project_or_filter(
             let options = vec![FlagOptions::Project, FlagOptions::Filter];
             match input::select(input::OPTION, options, config.mock_select)? {
                 FlagOptions::Project => fetch_project(project, config).await,
-                FlagOptions::Filter => fetch_filter(filter, config),
+                FlagOptions::Filter => fetch_filter(filter, config).await,
             }
         }
     }
 }
 
+/// Same as fetch_project_or_filter, with shortcuts for common filters
+async fn fetch_list_flag(
+    project: Option<&str>,
+    filter: Option<&str>,
+    today: bool,
+    overdue: bool,
+    config: &Config,
+) -> Result<Flag, Error> {
+    match (project, filter, today, overdue) {
+        (_, _, false, false) => fetch_project_or_filter(project, filter, config).await,
+        (None, None, true, false) => Ok(Flag::Today),
+        (None, None, false, true) => Ok(Flag::Overdue),
+        _ => Err(Error::new(
+            "fetch_list_flag",
+            "Must select only one of project, filter, today or overdue",
+        )),
+    }
+}
+
 fn fetch_priority(priority: &Option<u8>, config: &Config) -> Result<Priority, Error> {
     match priority::from_integer(priority) {
         Some(priority) => Ok(priority),
@@ -1551,3 +3039,63 @@ fn verify_cmd() {
     Cli::try_parse().err();
     Cli::command().debug_assert();
 }
+
+#[test]
+fn label_conflicts_with_label_filter() {
+    let result = Cli::try_parse_from([
+        "tod",
+        "list",
+        "view",
+        "--label",
+        "work",
+        "--label-filter",
+        "computer",
+    ]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn undated_conflicts_with_overdue_and_skip_recurring() {
+    for flag in ["--overdue", "--skip-recurring"] {
+        let result = Cli::try_parse_from(["tod", "list", "schedule", "--undated", flag]);
+        assert!(result.is_err());
+    }
+}
+
+#[tokio::test]
+async fn list_count_resolves_saved_filters() {
+    let mut server = mockito::Server::new_async().await;
+    let filters = serde_json::json!({
+        "filters": [
+            {"id": "4638878", "name": "Deep work", "query": "@focus & today", "is_deleted": false}
+        ],
+        "sync_token": "abc",
+        "full_sync": true
+    });
+    let filters_mock = server
+        .mock("POST", "/api/v1/sync")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(filters.to_string())
+        .create_async()
+        .await;
+    let tasks_mock = server
+        .mock(
+            "GET",
+            "/api/v1/tasks/filter?query=%40focus%20%26%20today&limit=200",
+        )
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(test::responses::ResponseFromFile::TodayTasks.read().await)
+        .create_async()
+        .await;
+
+    let config = test::fixtures::config().await.with_mock_url(server.url());
+    let args = ListCount {
+        filter: String::from("Deep work"),
+    };
+
+    assert_eq!(list_count(config, &args).await, Ok(String::from("1")));
+    filters_mock.assert();
+    tasks_mock.assert();
+}
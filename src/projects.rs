@@ -143,8 +143,8 @@ async fn project_name_with_count(config: &Config, project: &Project) -> String {
 
 /// Gets the number of tasks for a project that are not in the future
 async fn count_processable_tasks(config: &Config, project: &Project) -> Result<u8, Error> {
-    let all_tasks = todoist::all_tasks_by_project(config, project, None).await?;
-    let count = tasks::filter_not_in_future(all_tasks, config)?.len();
+    let all_tasks = todoist::all_tasks_by_project(config, project, None, false).await?;
+    let count = tasks::filter_not_in_future(all_tasks, config, 0)?.len();
 
     Ok(count as u8)
 }
@@ -186,7 +186,7 @@ pub async fn rename(config: Config, project: &Project) -> Result<String, Error>
 pub async fn next_task(config: Config, project: &Project) -> Result<String, Error> {
     match fetch_next_task(&config, project).await {
         Ok(Some((task, remaining))) => {
-            let comments = todoist::all_comments(&config, &task, None).await?;
+            let comments = todoist::all_comments(&config, &task, None, None).await?;
             config.set_next_task(task.clone()).save().await?;
             let task_string = task
                 .fmt(comments, &config, FormatType::Single, false)
@@ -202,8 +202,8 @@ async fn fetch_next_task(
     config: &Config,
     project: &Project,
 ) -> Result<Option<(Task, usize)>, Error> {
-    let tasks = todoist::all_tasks_by_project(config, project, None).await?;
-    let filtered_tasks = tasks::filter_not_in_future(tasks, config)?;
+    let tasks = todoist::all_tasks_by_project(config, project, None, false).await?;
+    let filtered_tasks = tasks::filter_not_in_future(tasks, config, 0)?;
     let tasks = tasks::sort_by_value(filtered_tasks, config);
 
     Ok(tasks.first().map(|task| (task.to_owned(), tasks.len())))
@@ -330,7 +330,7 @@ async fn maybe_add_project(
 }
 
 pub async fn edit_task(config: &Config, project: &Project) -> Result<String, Error> {
-    let project_tasks = todoist::all_tasks_by_project(config, project, None).await?;
+    let project_tasks = todoist::all_tasks_by_project(config, project, None, false).await?;
 
     let task = input::select(
         "Choose a task of the project:",
@@ -343,10 +343,7 @@ pub async fn edit_task(config: &Config, project: &Project) -> Result<String, Err
     let selections = input::multi_select("Choose attributes to edit", options, config.mock_select)?;
 
     if selections.is_empty() {
-        return Err(Error {
-            message: "Nothing selected".to_string(),
-            source: "edit_task".to_string(),
-        });
+        return Err(Error::new("edit_task", "Nothing selected"));
     }
 
     let mut handles = Vec::new();
@@ -364,7 +361,7 @@ pub async fn edit_task(config: &Config, project: &Project) -> Result<String, Err
 
 /// Empty a project by sending tasks to other projects one at a time
 pub async fn empty(config: &mut Config, project: &Project) -> Result<String, Error> {
-    let tasks = todoist::all_tasks_by_project(config, project, None).await?;
+    let tasks = todoist::all_tasks_by_project(config, project, None, false).await?;
 
     if tasks.is_empty() {
         Ok(color::green_string(&format!(
@@ -402,8 +399,8 @@ pub async fn schedule(
     skip_recurring: bool,
     sort: &SortOrder,
 ) -> Result<String, Error> {
-    let tasks = todoist::all_tasks_by_project(config, project, None).await?;
-    let tasks = tasks::sort(tasks, config, sort);
+    let tasks = todoist::all_tasks_by_project(config, project, None, false).await?;
+    let tasks = tasks::sort(tasks, config, sort, false);
 
     let filtered_tasks: Vec<Task> = if skip_recurring {
         tasks
@@ -444,8 +441,8 @@ pub async fn deadline(
     project: &Project,
     sort: &SortOrder,
 ) -> Result<String, Error> {
-    let tasks = todoist::all_tasks_by_project(config, project, None).await?;
-    let tasks = tasks::sort(tasks, config, sort);
+    let tasks = todoist::all_tasks_by_project(config, project, None, false).await?;
+    let tasks = tasks::sort(tasks, config, sort, false);
 
     let filtered_tasks: Vec<Task> = tasks
         .into_iter()
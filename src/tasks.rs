@@ -1,8 +1,11 @@
 use chrono::DateTime;
 use chrono::NaiveDate;
+use chrono::NaiveTime;
+use chrono::TimeZone;
 use chrono_tz::Tz;
 use futures::future;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::cmp::Reverse;
 use std::cmp::max;
 use std::fmt::Display;
@@ -20,7 +23,8 @@ use crate::input::DateTimeInput;
 use crate::projects;
 use crate::tasks;
 use crate::tasks::priority::Priority;
-use crate::{input, time, todoist};
+use crate::todoist::TaskUpdate;
+use crate::{color, input, time, todoist};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Task {
@@ -57,6 +61,26 @@ pub struct TaskResponse {
     pub next_cursor: Option<String>,
 }
 
+/// Completed tasks come back under items instead of results
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CompletedTaskResponse {
+    pub items: Vec<Task>,
+    pub next_cursor: Option<String>,
+}
+
+/// What happened to a task after prompting for it
+#[derive(Debug)]
+pub enum TaskAction {
+    /// A change was spawned, await the handle for it to finish
+    Changed(JoinHandle<()>),
+    /// Like Changed, but the change completes the task
+    Completed(JoinHandle<()>),
+    /// Skipped, or only printed because of a dry run
+    Unchanged,
+    /// Stop prompting for the remaining tasks
+    Quit,
+}
+
 // Update task_attributes fn when adding here
 #[derive(Eq, PartialEq)]
 pub enum TaskAttribute {
@@ -135,6 +159,51 @@ pub struct Duration {
     pub unit: Unit,
 }
 
+impl Duration {
+    /// Length in minutes, where a day is 24 hours
+    pub fn minutes(&self) -> u32 {
+        match self.unit {
+            Unit::Minute => self.amount,
+            Unit::Day => self.amount * 24 * 60,
+        }
+    }
+}
+
+/// Whole days use Todoist's day unit, anything else is given in minutes
+impl TryFrom<chrono::Duration> for Duration {
+    type Error = Error;
+
+    fn try_from(duration: chrono::Duration) -> Result<Self, Self::Error> {
+        let minutes = u32::try_from(duration.num_minutes())?;
+        if minutes == 0 {
+            return Err(Error::new(
+                "Duration::try_from",
+                "Duration must be at least one minute",
+            ));
+        }
+        if minutes % (24 * 60) == 0 {
+            Ok(Duration {
+                amount: minutes / (24 * 60),
+                unit: Unit::Day,
+            })
+        } else {
+            Ok(Duration {
+                amount: minutes,
+                unit: Unit::Minute,
+            })
+        }
+    }
+}
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.unit {
+            Unit::Minute => write!(f, "{} minutes", self.amount),
+            Unit::Day => write!(f, "{} days", self.amount),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct Body {
     items: Vec<Task>,
@@ -142,15 +211,35 @@ struct Body {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub enum Unit {
-    #[serde(rename(deserialize = "minute"))]
+    // Aliases let tasks stored in the config, such as the undo log, be read back
+    #[serde(rename(deserialize = "minute"), alias = "Minute")]
     Minute,
-    #[serde(rename(deserialize = "day"))]
+    #[serde(rename(deserialize = "day"), alias = "Day")]
     Day,
 }
 
+impl Unit {
+    /// The duration_unit value the Todoist API expects
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            Unit::Minute => "minute",
+            Unit::Day => "day",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FormatType {
     List,
     Single,
+    /// Uncolored JSON object for scripting
+    Json,
+    /// Uncolored checkbox list item for pasting into notes
+    Markdown,
+    /// Only the task content, nothing else
+    Plain,
+    /// Uncolored aligned columns of content, priority, due date and labels
+    Table,
 }
 
 enum DateTimeInfo {
@@ -167,7 +256,7 @@ enum DateTimeInfo {
     },
 }
 
-#[derive(clap::ValueEnum, Debug, Copy, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum SortOrder {
     /// Sort by Tod's configurable sort value
     Value,
@@ -175,6 +264,16 @@ pub enum SortOrder {
     Datetime,
     /// Leave Todoist's default sorting in place
     Todoist,
+    /// Sort by deadline, tasks without a deadline last
+    Deadline,
+    /// Sort by priority, highest first
+    Priority,
+    /// Sort alphabetically by content, ignoring case
+    Content,
+    /// Sort alphabetically by project name, tasks in projects that are not in config last
+    Project,
+    /// Sort by each order in turn, later orders only break ties in the earlier ones
+    Composite(Vec<SortOrder>),
 }
 
 impl std::fmt::Display for SortOrder {
@@ -183,6 +282,62 @@ impl std::fmt::Display for SortOrder {
             SortOrder::Value => write!(f, "value"),
             SortOrder::Todoist => write!(f, "todoist"),
             SortOrder::Datetime => write!(f, "datetime"),
+            SortOrder::Deadline => write!(f, "deadline"),
+            SortOrder::Priority => write!(f, "priority"),
+            SortOrder::Content => write!(f, "content"),
+            SortOrder::Project => write!(f, "project"),
+            SortOrder::Composite(orders) => {
+                let names = orders.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", names.join(","))
+            }
+        }
+    }
+}
+
+/// Saved in the config as the same string that is accepted on the command line
+impl Serialize for SortOrder {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for SortOrder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        string
+            .parse()
+            .map_err(|e: Error| serde::de::Error::custom(e.message))
+    }
+}
+
+/// Parses a single order, i.e. "priority", or a composite order separated by commas, i.e. "priority,datetime,content"
+impl std::str::FromStr for SortOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut orders = s
+            .split(',')
+            .map(|name| match name.trim().to_lowercase().as_str() {
+                "value" => Ok(SortOrder::Value),
+                "datetime" => Ok(SortOrder::Datetime),
+                "todoist" => Ok(SortOrder::Todoist),
+                "deadline" => Ok(SortOrder::Deadline),
+                "priority" => Ok(SortOrder::Priority),
+                "content" => Ok(SortOrder::Content),
+                "project" => Ok(SortOrder::Project),
+                _ => Err(Error::new(
+                    "sort",
+                    &format!(
+                        "Unknown sort order '{name}', use value, datetime, todoist, deadline, priority, content or project, separated by commas to combine them"
+                    ),
+                )),
+            })
+            .collect::<Result<Vec<SortOrder>, Error>>()?;
+
+        if orders.len() == 1 {
+            Ok(orders.remove(0))
+        } else {
+            Ok(SortOrder::Composite(orders))
         }
     }
 }
@@ -195,16 +350,27 @@ impl Task {
         format: FormatType,
         with_project: bool,
     ) -> Result<String, Error> {
+        match format {
+            FormatType::Json => return Ok(self.json(config).await?.to_string()),
+            FormatType::Markdown => return Ok(format!("{}\n", self.markdown(&comments))),
+            FormatType::Plain => return Ok(format!("{}\n", self.content)),
+            FormatType::List | FormatType::Single | FormatType::Table => (),
+        }
+
         let content = format::content(self, config);
         let buffer = match format {
-            FormatType::List => "  ".into(),
-            FormatType::Single => String::new(),
+            FormatType::List | FormatType::Table => "  ".into(),
+            FormatType::Single | FormatType::Json | FormatType::Markdown | FormatType::Plain => {
+                String::new()
+            }
         };
 
-        let description = match &*self.description {
-            "" => String::new(),
-            _ => format!("\n{buffer}{}", self.description),
-        };
+        // Every line of a multi-line description stays under the content
+        let description = self
+            .description
+            .lines()
+            .map(|line| format!("\n{buffer}{line}"))
+            .collect::<String>();
 
         let project = if with_project {
             format::project(self, config, &buffer).await?
@@ -220,8 +386,10 @@ impl Task {
 
         let due = format::due(self, config, &buffer);
         let prefix = match format {
-            FormatType::List => "- ".into(),
-            FormatType::Single => String::new(),
+            FormatType::List | FormatType::Table => "- ".into(),
+            FormatType::Single | FormatType::Json | FormatType::Markdown | FormatType::Plain => {
+                String::new()
+            }
         };
 
         let labels = if self.labels.is_empty() {
@@ -246,6 +414,42 @@ impl Task {
         ))
     }
 
+    /// A Markdown checkbox such as "- [ ] Buy milk (p1) @errands", with comments as nested blockquotes
+    pub fn markdown(&self, comments: &[Comment]) -> String {
+        let mut text = format!("- [ ] {}", self.content.trim());
+        if let Some(priority) = self.priority.shorthand() {
+            text.push_str(&format!(" ({priority})"));
+        }
+        for label in &self.labels {
+            text.push_str(&format!(" @{label}"));
+        }
+        for comment in comments {
+            for line in comment.content.lines() {
+                text.push_str(&format!("\n  > {line}"));
+            }
+        }
+        text
+    }
+
+    /// The fields of a task that are useful for scripting, with the project name looked up from config
+    pub async fn json(&self, config: &Config) -> Result<serde_json::Value, Error> {
+        let project = config
+            .projects()
+            .await?
+            .into_iter()
+            .find(|p| p.id == self.project_id)
+            .map(|p| p.name);
+
+        Ok(serde_json::json!({
+            "id": self.id,
+            "content": self.content,
+            "priority": self.priority,
+            "due": self.due,
+            "labels": self.labels,
+            "project": project,
+        }))
+    }
+
     /// Determines the numeric value of an task for sorting
     fn value(&self, config: &Config) -> u32 {
         let date_value: u8 = self.date_value(config);
@@ -287,11 +491,7 @@ impl Task {
                 } else {
                     0
                 };
-                let overdue_value = if self.is_overdue(config).unwrap_or_default() {
-                    overdue
-                } else {
-                    0
-                };
+                let overdue_value = if is_overdue(self, config) { overdue } else { 0 };
                 let recurring_value = if is_recurring.to_owned() {
                     0
                 } else {
@@ -324,6 +524,15 @@ impl Task {
         }
     }
 
+    /// Time of day the task is due in the config timezone, None when it has no due time
+    pub fn due_time(&self, config: &Config) -> Option<NaiveTime> {
+        let tz = time::timezone_from_str(&config.get_timezone().ok()?).ok()?;
+        match self.datetimeinfo(config) {
+            Ok(DateTimeInfo::DateTime { datetime, .. }) => Some(datetime.with_timezone(&tz).time()),
+            _ => None,
+        }
+    }
+
     /// Return the value of the due field
     fn datetime(&self, config: &Config) -> Option<DateTime<Tz>> {
         match self.datetimeinfo(config) {
@@ -409,12 +618,53 @@ impl Task {
         }
     }
 
+    /// The due string for the task moved later by delta, in the configured timezone.
+    /// Whole days are added to the local time, so 9am stays 9am across a daylight saving change,
+    /// and the rest of delta is added after that in absolute time.
+    /// None when there is no due date, or the due date has no time and delta is less than a day.
+    pub fn snoozed_due_string(
+        &self,
+        delta: chrono::Duration,
+        config: &Config,
+    ) -> Result<Option<String>, Error> {
+        match self.datetimeinfo(config)? {
+            DateTimeInfo::NoDateTime => Ok(None),
+            DateTimeInfo::Date { .. } if delta.num_days() == 0 => Ok(None),
+            DateTimeInfo::Date { date, .. } => {
+                let date = date + chrono::Duration::days(delta.num_days());
+                Ok(Some(date.format(time::FORMAT_DATE).to_string()))
+            }
+            DateTimeInfo::DateTime { datetime, .. } => {
+                let tz = time::timezone_from_str(&config.get_timezone()?)?;
+                let local = datetime.with_timezone(&tz);
+                let days = chrono::Duration::days(delta.num_days());
+                // A local time skipped by a daylight saving change falls back to absolute time
+                let moved = tz
+                    .from_local_datetime(&(local.naive_local() + days))
+                    .earliest()
+                    .unwrap_or(local + days);
+                let snoozed = (moved + (delta - days)).naive_local();
+                Ok(Some(snoozed.format(time::FORMAT_DATE_AND_TIME).to_string()))
+            }
+        }
+    }
+
+    /// The date the task is due in the configured timezone, None if it has no due date
+    pub fn due_date(&self, config: &Config) -> Option<NaiveDate> {
+        match self.datetimeinfo(config) {
+            Ok(DateTimeInfo::Date { date, .. }) => Some(date),
+            Ok(DateTimeInfo::DateTime { datetime, .. }) => {
+                let tz = time::timezone_from_str(&config.get_timezone().ok()?).ok()?;
+                Some(datetime.with_timezone(&tz).date_naive())
+            }
+            Ok(DateTimeInfo::NoDateTime) | Err(_) => None,
+        }
+    }
+
     pub fn filter(&self, config: &Config, filter: &projects::TaskFilter) -> bool {
         match filter {
-            projects::TaskFilter::Unscheduled => {
-                self.has_no_date() || self.is_overdue(config).unwrap_or_default()
-            }
-            projects::TaskFilter::Overdue => self.is_overdue(config).unwrap_or_default(),
+            projects::TaskFilter::Unscheduled => self.has_no_date() || is_overdue(self, config),
+            projects::TaskFilter::Overdue => is_overdue(self, config),
             projects::TaskFilter::Recurring => self.is_recurring(),
         }
     }
@@ -424,7 +674,7 @@ impl Task {
     }
 
     // Returns true if the datetime is today and there is a time
-    fn is_today(&self, config: &Config) -> Result<bool, Error> {
+    pub fn is_today(&self, config: &Config) -> Result<bool, Error> {
         let boolean = match self.datetimeinfo(config) {
             Ok(DateTimeInfo::NoDateTime) => false,
             Ok(DateTimeInfo::Date { date, .. }) => date == time::naive_date_today(config)?,
@@ -437,19 +687,6 @@ impl Task {
         Ok(boolean)
     }
 
-    fn is_overdue(&self, config: &Config) -> Result<bool, Error> {
-        let boolean = match self.clone().datetimeinfo(config) {
-            Ok(DateTimeInfo::NoDateTime) => false,
-            Ok(DateTimeInfo::Date { date, .. }) => time::is_date_in_past(date, config)?,
-            Ok(DateTimeInfo::DateTime { datetime, .. }) => {
-                time::is_date_in_past(datetime.date_naive(), config)?
-            }
-            Err(_) => false,
-        };
-
-        Ok(boolean)
-    }
-
     /// Returns true if it is a recurring task
     pub fn is_recurring(&self) -> bool {
         match self.due {
@@ -459,11 +696,62 @@ impl Task {
     }
 }
 
-pub fn sort(tasks: Vec<Task>, config: &Config, sort: &SortOrder) -> Vec<Task> {
+/// Sorts tasks, reverse flips the order while keeping tasks that tie in their original order
+pub fn sort(mut tasks: Vec<Task>, config: &Config, sort: &SortOrder, reverse: bool) -> Vec<Task> {
+    match (sort, reverse) {
+        (SortOrder::Value, false) => sort_by_value(tasks, config),
+        (SortOrder::Value, true) => {
+            tasks.sort_by_key(|task| task.value(config));
+            tasks
+        }
+        (SortOrder::Datetime, false) => sort_by_datetime(tasks, config),
+        (SortOrder::Datetime, true) => {
+            tasks.sort_by_key(|task| Reverse(task.datetime(config)));
+            tasks
+        }
+        (SortOrder::Deadline, false) => sort_by_deadline(tasks),
+        (SortOrder::Deadline, true) => {
+            tasks.sort_by_key(|task| Reverse(deadline_key(task)));
+            tasks
+        }
+        (SortOrder::Todoist, false) => tasks,
+        // There are no ties in Todoist's order so the whole list is flipped
+        (SortOrder::Todoist, true) => {
+            tasks.reverse();
+            tasks
+        }
+        (
+            SortOrder::Priority | SortOrder::Content | SortOrder::Project | SortOrder::Composite(_),
+            _,
+        ) => {
+            tasks.sort_by(|a, b| {
+                let ordering = compare(a, b, config, sort);
+                if reverse {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+            tasks
+        }
+    }
+}
+
+/// How two tasks compare for a sort order, Todoist's order counts as a tie so the existing order is kept
+fn compare(a: &Task, b: &Task, config: &Config, sort: &SortOrder) -> Ordering {
     match sort {
-        SortOrder::Value => sort_by_value(tasks, config),
-        SortOrder::Datetime => sort_by_datetime(tasks, config),
-        SortOrder::Todoist => tasks,
+        SortOrder::Value => b.value(config).cmp(&a.value(config)),
+        SortOrder::Datetime => a.datetime(config).cmp(&b.datetime(config)),
+        SortOrder::Deadline => deadline_key(a).cmp(&deadline_key(b)),
+        SortOrder::Todoist => Ordering::Equal,
+        SortOrder::Priority => (b.priority.clone() as u8).cmp(&(a.priority.clone() as u8)),
+        SortOrder::Content => a.content.to_lowercase().cmp(&b.content.to_lowercase()),
+        SortOrder::Project => project_key(a, config).cmp(&project_key(b, config)),
+        SortOrder::Composite(orders) => orders
+            .iter()
+            .map(|order| compare(a, b, config, order))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal),
     }
 }
 
@@ -528,11 +816,13 @@ pub async fn update_task(
     }
 }
 
+/// Prompts for a label to add to the task and returns the update for it. With dry_run the label is only printed.
 pub async fn label_task(
     config: &Config,
     task: Task,
     labels: &Vec<String>,
-) -> Result<JoinHandle<()>, Error> {
+    dry_run: bool,
+) -> Result<Option<TaskUpdate>, Error> {
     let comments = Vec::new();
     let text = task.fmt(comments, config, FormatType::Single, true).await?;
     println!("{text}");
@@ -540,79 +830,157 @@ pub async fn label_task(
     options.push(input::SKIP.to_string());
     let label = input::select("Select label", options, config.mock_select)?;
 
-    let config = config.clone();
-    Ok(tokio::spawn(async move {
-        if label.as_str() == input::SKIP {
-        } else if let Err(e) = todoist::add_task_label(&config, task, label, false).await {
-            config.tx().send(e).unwrap();
-        }
-    }))
+    if label.as_str() == input::SKIP {
+        return Ok(None);
+    }
+
+    if dry_run {
+        println!("Dry run: would have added label {label} to {task}");
+        return Ok(None);
+    }
+
+    let mut labels = task.labels.clone();
+    labels.push(label);
+    Ok(Some(TaskUpdate::Labels(task, labels)))
+}
+
+/// Removes the labels from the task, returns None when the task has none of them or for a dry run
+pub fn remove_task_labels(task: Task, labels: &[String], dry_run: bool) -> Option<TaskUpdate> {
+    if !task.labels.iter().any(|label| labels.contains(label)) {
+        return None;
+    }
+
+    let remaining = task
+        .labels
+        .iter()
+        .filter(|label| !labels.contains(label))
+        .cloned()
+        .collect::<Vec<String>>();
+
+    if dry_run {
+        println!(
+            "Dry run: would have removed labels {} from {task}",
+            labels.join(", ")
+        );
+        return None;
+    }
+
+    Some(TaskUpdate::Labels(task, remaining))
 }
 
 pub async fn process_task(
     comments: Vec<Comment>,
     config: &Config,
-    task: Task,
+    mut task: Task,
     task_count: &mut i32,
     with_project: bool,
-) -> Result<Option<JoinHandle<()>>, Error> {
-    let options = [
-        input::COMPLETE,
-        input::SKIP,
-        input::SCHEDULE,
-        input::COMMENT,
-        input::DELETE,
-        input::QUIT,
-    ]
-    .iter()
-    .map(|s| s.to_string())
-    .collect();
-    let formatted_task = task
-        .fmt(comments, config, FormatType::Single, with_project)
-        .await?;
+) -> Result<TaskAction, Error> {
+    // Completing a recurring task only moves it to the next date, so offer to end the series as well
+    let complete_options = if task.is_recurring() {
+        vec![input::COMPLETE_OCCURRENCE, input::STOP_RECURRENCE]
+    } else {
+        vec![input::COMPLETE]
+    };
     let mut reloaded_config = config.reload().await?.increment_completed()?;
     let tasks_completed = reloaded_config.tasks_completed()?;
-    println!("{formatted_task}{tasks_completed} completed today, {task_count} remaining");
+    let remaining = *task_count;
     *task_count -= 1;
-    let selection = input::select(input::OPTION, options, config.mock_select)?;
+
+    // After an edit the task is shown again with its new content, without the option to edit it again
+    let mut editable = true;
+    let selection = loop {
+        let options = complete_options
+            .iter()
+            .chain(&[
+                input::SKIP,
+                input::DEFER,
+                input::SCHEDULE,
+                input::COMMENT,
+                input::DELETE,
+            ])
+            .chain(editable.then_some(&input::EDIT))
+            .chain(&[input::QUIT])
+            .map(|s| s.to_string())
+            .collect();
+        let formatted_task = task
+            .fmt(comments.clone(), config, FormatType::Single, with_project)
+            .await?;
+        println!("{formatted_task}{tasks_completed} completed today, {remaining} remaining");
+        let selection = input::select(input::OPTION, options, config.mock_select)?;
+        if selection != input::EDIT {
+            break selection;
+        }
+
+        let content = input::string(CONTENT, config.mock_string.clone())?;
+        todoist::update_task_content(config, &task, content.clone(), true).await?;
+        task.content = content;
+        editable = false;
+    };
     match selection.as_str() {
-        input::COMPLETE => {
+        input::COMPLETE | input::COMPLETE_OCCURRENCE => {
+            reloaded_config.save().await.expect("Could not save config");
+            Ok(TaskAction::Completed(spawn_complete_task(
+                reloaded_config,
+                task,
+            )))
+        }
+        input::STOP_RECURRENCE => {
             reloaded_config.save().await.expect("Could not save config");
-            Ok(Some(spawn_complete_task(reloaded_config, task)))
+            Ok(TaskAction::Changed(spawn_stop_recurrence(
+                reloaded_config,
+                task,
+            )))
         }
-        input::DELETE => Ok(Some(spawn_delete_task(config.clone(), task))),
+        input::DELETE => Ok(TaskAction::Changed(spawn_delete_task(config.clone(), task))),
         input::COMMENT => {
             let content = input::string(CONTENT, config.mock_string.clone())?;
 
-            Ok(Some(spawn_comment_task(config.clone(), task, content)))
+            Ok(TaskAction::Changed(spawn_comment_task(
+                config.clone(),
+                task,
+                content,
+            )))
         }
 
         input::SCHEDULE => {
             let date = input::date()?;
-            Ok(Some(spawn_update_task_due(
+            Ok(TaskAction::Changed(spawn_update_task_due(
                 config.clone(),
                 task,
                 date,
                 None,
             )))
         }
-        input::SKIP => {
-            // Do nothing
-            Ok(Some(tokio::spawn(async move {})))
-        }
-        input::QUIT => Ok(None),
+        input::SKIP => Ok(TaskAction::Unchanged),
+        input::DEFER => Ok(TaskAction::Changed(spawn_defer_task(config.clone(), task))),
+        input::QUIT => Ok(TaskAction::Quit),
         _ => {
             unreachable!()
         }
     }
 }
 
+/// Adds the defer label to the task inside another thread, so it can be handled later
+fn spawn_defer_task(config: Config, task: Task) -> JoinHandle<()> {
+    let label = config.defer_label();
+    tokio::spawn(async move {
+        if task.labels.contains(&label) {
+            return;
+        }
+        if let Err(e) = todoist::add_task_label(&config, task, label, false).await {
+            config.tx().send(e).unwrap();
+        }
+    })
+}
+
+/// Prompts for a time and duration for the task. With dry_run the changes are printed instead of made.
 pub async fn timebox_task(
     config: &Config,
     task: Task,
     task_count: &mut i32,
     with_project: bool,
-) -> Result<Option<JoinHandle<()>>, Error> {
+    dry_run: bool,
+) -> Result<TaskAction, Error> {
     let options = [
         input::TIMEBOX,
         input::COMPLETE,
@@ -634,7 +1002,14 @@ pub async fn timebox_task(
         input::TIMEBOX => {
             let (due_string, duration) = get_timebox(config, &task)?;
 
-            Ok(Some(spawn_update_task_due(
+            if dry_run {
+                println!(
+                    "Dry run: would have scheduled {task} for {due_string} with a duration of {duration}"
+                );
+                return Ok(TaskAction::Unchanged);
+            }
+
+            Ok(TaskAction::Changed(spawn_update_task_due(
                 config.clone(),
                 task,
                 due_string,
@@ -642,16 +1017,21 @@ pub async fn timebox_task(
             )))
         }
 
-        input::DELETE => Ok(Some(spawn_delete_task(config.clone(), task))),
-        input::COMPLETE => Ok(Some(spawn_complete_task(config.clone(), task))),
-        input::SKIP => {
-            // Do nothing
-            Ok(Some(tokio::spawn(async move {})))
+        input::DELETE if dry_run => {
+            println!("Dry run: would have deleted {task}");
+            Ok(TaskAction::Unchanged)
         }
-        input::QUIT => {
-            // The quit clause
-            Ok(None)
+        input::COMPLETE if dry_run => {
+            println!("Dry run: would have completed {task}");
+            Ok(TaskAction::Unchanged)
         }
+        input::DELETE => Ok(TaskAction::Changed(spawn_delete_task(config.clone(), task))),
+        input::COMPLETE => Ok(TaskAction::Changed(spawn_complete_task(
+            config.clone(),
+            task,
+        ))),
+        input::SKIP => Ok(TaskAction::Unchanged),
+        input::QUIT => Ok(TaskAction::Quit),
         _ => {
             unreachable!()
         }
@@ -659,7 +1039,7 @@ pub async fn timebox_task(
 }
 
 /// Returns Date, time and duration for a task, uses the date and time on task if available, otherwise prompts. Always prompts for duration.
-fn get_timebox(config: &Config, task: &Task) -> Result<(String, u32), Error> {
+fn get_timebox(config: &Config, task: &Task) -> Result<(String, Duration), Error> {
     let datetime = match task {
         Task {
             due: Some(DateInfo { date, .. }),
@@ -684,9 +1064,49 @@ fn get_timebox(config: &Config, task: &Task) -> Result<(String, u32), Error> {
         }
     };
 
-    let duration = input::string(input::DURATION, config.mock_string.clone())?;
+    let duration = match default_duration(config, task) {
+        Some(default) => input::string_with_default(input::DURATION, default)?,
+        None => input::string(input::DURATION, config.mock_string.clone())?,
+    };
+
+    let duration = time::duration_from_str(&duration)?;
+    Ok((datetime, Duration::try_from(duration)?))
+}
+
+/// The duration_defaults entry in config for the first of the task's labels that has one
+fn default_duration<'a>(config: &'a Config, task: &Task) -> Option<&'a str> {
+    task.labels
+        .iter()
+        .find_map(|label| config.duration_defaults.get(label))
+        .map(String::as_str)
+}
 
-    Ok((datetime, duration.parse::<u32>()?))
+/// Prompts for a due date for a task without one, or lets the user skip the task or quit
+pub async fn schedule_undated_task(
+    config: &Config,
+    task: Task,
+    task_count: &mut i32,
+) -> Result<TaskAction, Error> {
+    let options = vec![input::SCHEDULE, input::SKIP, input::QUIT];
+    let formatted_task = task
+        .fmt(Vec::new(), config, FormatType::Single, true)
+        .await?;
+    println!("{formatted_task}{task_count} task(s) remaining");
+    *task_count -= 1;
+    match input::select(input::OPTION, options, config.mock_select)? {
+        input::SCHEDULE => {
+            let due_string = input::string(input::DATE, config.mock_string.clone())?;
+            Ok(TaskAction::Changed(spawn_update_task_due(
+                config.clone(),
+                task,
+                due_string,
+                None,
+            )))
+        }
+        input::SKIP => Ok(TaskAction::Unchanged),
+        input::QUIT => Ok(TaskAction::Quit),
+        _ => unreachable!(),
+    }
 }
 
 pub async fn spawn_schedule_task(
@@ -772,6 +1192,19 @@ pub fn spawn_complete_task(config: Config, task: Task) -> JoinHandle<()> {
     })
 }
 
+/// Ends the recurrence and then completes the task inside another thread
+fn spawn_stop_recurrence(config: Config, task: Task) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let result = match todoist::stop_recurrence(&config, &task, false).await {
+            Ok(_) => todoist::complete_task(&config, &task, false).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            config.tx().send(e).unwrap();
+        }
+    })
+}
+
 /// Deletes task inside another thread
 pub fn spawn_delete_task(config: Config, task: Task) -> JoinHandle<()> {
     tokio::spawn(async move {
@@ -786,7 +1219,7 @@ pub fn spawn_update_task_due(
     config: Config,
     task: Task,
     due_string: String,
-    duration: Option<u32>,
+    duration: Option<Duration>,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
         if let Err(e) =
@@ -798,6 +1231,19 @@ pub fn spawn_update_task_due(
     })
 }
 
+/// Updates task duration inside another thread
+pub fn spawn_update_task_duration(
+    config: Config,
+    task: Task,
+    duration: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Err(e) = todoist::update_task_duration(&config, &task, &duration, false).await {
+            config.tx().send(e).unwrap();
+        }
+    })
+}
+
 /// Updates task inside another thread
 pub fn spawn_update_task_deadline(
     config: Config,
@@ -875,6 +1321,12 @@ pub fn json_to_tasks_response(json: String) -> Result<TaskResponse, Error> {
     Ok(response)
 }
 
+/// Converts a JSON String from the completed tasks endpoint into a CompletedTaskResponse
+pub fn json_to_completed_tasks_response(json: String) -> Result<CompletedTaskResponse, Error> {
+    let response: CompletedTaskResponse = serde_json::from_str(&json)?;
+    Ok(response)
+}
+
 pub fn sort_by_value(mut tasks: Vec<Task>, config: &Config) -> Vec<Task> {
     tasks.sort_by_key(|b| Reverse(b.value(config)));
     tasks
@@ -885,13 +1337,59 @@ pub fn sort_by_datetime(mut tasks: Vec<Task>, config: &Config) -> Vec<Task> {
     tasks
 }
 
-pub fn filter_not_in_future(tasks: Vec<Task>, config: &Config) -> Result<Vec<Task>, Error> {
+pub fn sort_by_deadline(mut tasks: Vec<Task>) -> Vec<Task> {
+    tasks.sort_by_key(deadline_key);
+    tasks
+}
+
+/// Deadlines are YYYY-MM-DD so they sort correctly as strings, tasks without one go last
+fn deadline_key(task: &Task) -> (bool, Option<String>) {
+    let date = task.deadline.as_ref().map(|deadline| deadline.date.clone());
+    (date.is_none(), date)
+}
+
+/// Project names from config ignoring case, tasks whose project is not in config go last
+fn project_key(task: &Task, config: &Config) -> (bool, Option<String>) {
+    let name = config
+        .project_name(&task.project_id)
+        .map(|name| name.to_lowercase());
+    (name.is_none(), name)
+}
+
+/// A task with only a due date is overdue from the day after, in the config timezone.
+/// A task with a due time is overdue once that time has passed. Undated tasks are never overdue.
+pub fn is_overdue(task: &Task, config: &Config) -> bool {
+    match task.datetimeinfo(config) {
+        Ok(DateTimeInfo::Date { date, .. }) => {
+            time::is_date_in_past(date, config).unwrap_or_default()
+        }
+        Ok(DateTimeInfo::DateTime { datetime, .. }) => {
+            time::datetime_now(config).is_ok_and(|now| datetime < now)
+        }
+        Ok(DateTimeInfo::NoDateTime) | Err(_) => false,
+    }
+}
+
+/// Keeps tasks that are undated, overdue, due today, or due within `horizon_days` days of today.
+/// A horizon of 0 only keeps tasks up to today.
+pub fn filter_not_in_future(
+    tasks: Vec<Task>,
+    config: &Config,
+    horizon_days: i64,
+) -> Result<Vec<Task>, Error> {
+    let horizon = time::naive_date_today(config)
+        .ok()
+        .map(|today| today + chrono::Duration::days(horizon_days));
     let tasks = tasks
         .into_iter()
         .filter(|task| {
             task.is_today(config).unwrap_or_default()
                 || task.has_no_date()
-                || task.is_overdue(config).unwrap_or_default()
+                || is_overdue(task, config)
+                || task
+                    .due_date(config)
+                    .zip(horizon)
+                    .is_some_and(|(date, horizon)| date <= horizon)
         })
         .collect();
 
@@ -961,7 +1459,7 @@ async fn parent_in_future(task: Task, tasks: Vec<Task>, config: &Config) -> bool
                         false
                     }
                     Ok(task) => {
-                        !(task.is_overdue(config).unwrap_or_default()
+                        !(is_overdue(&task, config)
                             || task.has_no_date()
                             || task.is_today(config).unwrap_or_default())
                     }
@@ -971,11 +1469,13 @@ async fn parent_in_future(task: Task, tasks: Vec<Task>, config: &Config) -> bool
     }
 }
 
+/// Prompts for a new priority for the task and returns the update for it. With dry_run the priority is only printed.
 pub async fn set_priority(
     config: &Config,
     task: Task,
     with_project: bool,
-) -> Result<JoinHandle<()>, Error> {
+    dry_run: bool,
+) -> Result<Option<TaskUpdate>, Error> {
     let comments = Vec::new();
     let text = task
         .fmt(comments, config, FormatType::Single, with_project)
@@ -989,13 +1489,21 @@ pub async fn set_priority(
         Priority::High,
     ];
     let priority = input::select(input::PRIORITY, options, config.mock_select)?;
+    println!(
+        "{}",
+        color::priority_transition_string(&task.priority, &priority)
+    );
 
-    let config = config.clone();
-    Ok(tokio::spawn(async move {
-        if let Err(e) = todoist::update_task_priority(&config, &task, &priority, false).await {
-            config.tx().send(e).unwrap();
-        }
-    }))
+    if priority == task.priority {
+        return Ok(None);
+    }
+
+    if dry_run {
+        println!("Dry run: would have set priority of {task} to {priority}");
+        return Ok(None);
+    }
+
+    Ok(Some(TaskUpdate::Priority(task, priority)))
 }
 
 #[cfg(test)]
@@ -1220,6 +1728,167 @@ mod tests {
         assert_eq!(sort_by_value(input, &config), result);
     }
 
+    #[tokio::test]
+    async fn sort_reverse_works() {
+        let config = test::fixtures::config().await;
+        let high = Task {
+            id: "1".into(),
+            priority: Priority::High,
+            ..test::fixtures::today_task().await
+        };
+        let low = Task {
+            id: "2".into(),
+            priority: Priority::Low,
+            ..test::fixtures::today_task().await
+        };
+        let low_tie = Task {
+            id: "3".into(),
+            priority: Priority::Low,
+            ..test::fixtures::today_task().await
+        };
+
+        let input = vec![high.clone(), low.clone(), low_tie.clone()];
+
+        assert_eq!(
+            sort(input.clone(), &config, &SortOrder::Value, false),
+            vec![high.clone(), low.clone(), low_tie.clone()]
+        );
+        assert_eq!(
+            sort(input.clone(), &config, &SortOrder::Value, true),
+            vec![low.clone(), low_tie.clone(), high.clone()]
+        );
+        assert_eq!(
+            sort(input, &config, &SortOrder::Todoist, true),
+            vec![low_tie, low, high]
+        );
+    }
+
+    #[tokio::test]
+    async fn sort_by_deadline_works() {
+        let config = test::fixtures::config().await;
+        let deadline = |date: &str| {
+            Some(Deadline {
+                date: date.into(),
+                lang: "en".into(),
+            })
+        };
+        let no_deadline = Task {
+            id: "1".into(),
+            deadline: None,
+            ..test::fixtures::today_task().await
+        };
+        let later = Task {
+            id: "2".into(),
+            deadline: deadline("2025-12-01"),
+            ..test::fixtures::today_task().await
+        };
+        let sooner = Task {
+            id: "3".into(),
+            deadline: deadline("2025-05-12"),
+            ..test::fixtures::today_task().await
+        };
+
+        let input = vec![no_deadline.clone(), later.clone(), sooner.clone()];
+
+        assert_eq!(
+            sort(input, &config, &SortOrder::Deadline, false),
+            vec![sooner, later, no_deadline]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_markdown() {
+        let task = Task {
+            content: String::from("Buy milk"),
+            priority: Priority::High,
+            labels: vec![String::from("errands")],
+            ..test::fixtures::today_task().await
+        };
+        let comment = Comment {
+            content: String::from("Oat\nor soy"),
+            ..test::fixtures::comment()
+        };
+
+        assert_eq!(
+            task.markdown(&[comment]),
+            "- [ ] Buy milk (p1) @errands\n  > Oat\n  > or soy"
+        );
+    }
+
+    #[tokio::test]
+    async fn sort_by_composite_works() {
+        let config = test::fixtures::config().await;
+        let base = test::fixtures::today_task().await;
+        let task = |id: &str, priority: Priority, date: &str, content: &str| Task {
+            id: id.into(),
+            priority,
+            deadline: Some(Deadline {
+                date: date.into(),
+                lang: "en".into(),
+            }),
+            content: content.into(),
+            ..base.clone()
+        };
+        let zebra = task("1", Priority::High, "2025-05-12", "Zebra");
+        let apple = task("2", Priority::High, "2025-05-12", "apple");
+        let later = task("3", Priority::High, "2025-12-01", "Aardvark");
+        let low = task("4", Priority::None, "2025-01-01", "First");
+
+        let order: SortOrder = "priority, deadline, content".parse().unwrap();
+        assert_eq!(
+            order,
+            SortOrder::Composite(vec![
+                SortOrder::Priority,
+                SortOrder::Deadline,
+                SortOrder::Content
+            ])
+        );
+
+        let input = vec![low.clone(), later.clone(), zebra.clone(), apple.clone()];
+        assert_eq!(
+            sort(input.clone(), &config, &order, false),
+            vec![apple.clone(), zebra.clone(), later.clone(), low.clone()]
+        );
+        assert_eq!(
+            sort(input, &config, &order, true),
+            vec![low, later, zebra, apple]
+        );
+        assert!("priority,size".parse::<SortOrder>().is_err());
+    }
+
+    #[tokio::test]
+    async fn sort_by_project_works() {
+        let alpha = crate::projects::Project {
+            id: "456".into(),
+            name: "Alpha".into(),
+            ..test::fixtures::project()
+        };
+        let config = test::fixtures::config()
+            .await
+            .with_projects(vec![test::fixtures::project(), alpha]);
+        let base = test::fixtures::today_task().await;
+        let task = |id: &str, project_id: &str| Task {
+            id: id.into(),
+            project_id: project_id.into(),
+            ..base.clone()
+        };
+        let mine = task("1", "123");
+        let unknown = task("2", "999");
+        let first = task("3", "456");
+
+        let order: SortOrder = "project".parse().unwrap();
+        assert_eq!(order, SortOrder::Project);
+        assert_eq!(
+            sort(
+                vec![unknown.clone(), mine.clone(), first.clone()],
+                &config,
+                &order,
+                false
+            ),
+            vec![first, mine, unknown]
+        );
+    }
+
     #[tokio::test]
     async fn sort_by_datetime_works() {
         let config = test::fixtures::config().await;
@@ -1339,7 +2008,7 @@ mod tests {
             is_deleted: false,
         };
 
-        assert!(!task.is_overdue(&config).unwrap());
+        assert!(!is_overdue(&task, &config));
 
         let task_today = Task {
             due: Some(DateInfo {
@@ -1351,7 +2020,7 @@ mod tests {
             }),
             ..task.clone()
         };
-        assert!(!task_today.is_overdue(&config).unwrap());
+        assert!(!is_overdue(&task_today, &config));
 
         let task_future = Task {
             due: Some(DateInfo {
@@ -1363,7 +2032,7 @@ mod tests {
             }),
             ..task.clone()
         };
-        assert!(!task_future.is_overdue(&config).unwrap());
+        assert!(!is_overdue(&task_future, &config));
 
         let task_today = Task {
             due: Some(DateInfo {
@@ -1375,7 +2044,36 @@ mod tests {
             }),
             ..task
         };
-        assert!(task_today.is_overdue(&config).unwrap());
+        assert!(is_overdue(&task_today, &config));
+    }
+
+    #[tokio::test]
+    async fn test_is_overdue_boundaries() {
+        // Fixed time is 03:00 on 2025-05-10 in Vancouver
+        let config = test::fixtures::config().await;
+        let task = test::fixtures::today_task().await;
+        let due_at = |date: &str| Task {
+            due: Some(DateInfo {
+                date: date.into(),
+                lang: "en".into(),
+                is_recurring: false,
+                string: String::new(),
+                timezone: Some("America/Vancouver".into()),
+            }),
+            ..task.clone()
+        };
+
+        assert!(!is_overdue(&due_at("2025-05-10T10:00:00Z"), &config));
+        assert!(is_overdue(&due_at("2025-05-10T09:59:00Z"), &config));
+        // Midnight today has already passed, but a date-only task due today is not overdue until tomorrow
+        assert!(is_overdue(&due_at("2025-05-10T07:00:00Z"), &config));
+        assert!(!is_overdue(&due_at("2025-05-10"), &config));
+        assert!(is_overdue(&due_at("2025-05-09"), &config));
+        // Midnight tonight is still to come
+        assert!(!is_overdue(&due_at("2025-05-11T07:00:00Z"), &config));
+
+        let undated = Task { due: None, ..task };
+        assert!(!is_overdue(&undated, &config));
     }
 
     #[test]
@@ -1402,12 +2100,25 @@ mod tests {
             .mock_select(1)
             .with_mock_url(server.url());
 
-        let future = set_priority(&config, task, false).await.unwrap();
+        let update = set_priority(&config, task, false, false)
+            .await
+            .unwrap()
+            .unwrap();
 
-        tokio::join!(future).0.unwrap();
+        update.send(&config).await.unwrap();
         mock.assert();
     }
 
+    /// Waits for the change spawned by a prompt, panics if nothing was changed
+    async fn await_change(action: Result<TaskAction, Error>) {
+        match action {
+            Ok(TaskAction::Changed(handle) | TaskAction::Completed(handle)) => {
+                handle.await.unwrap()
+            }
+            other => panic!("Expected a change, got {other:?}"),
+        }
+    }
+
     #[tokio::test]
     async fn test_process_task() {
         let mut server = mockito::Server::new_async().await;
@@ -1430,13 +2141,262 @@ mod tests {
 
         let mut task_count = 3;
         let comments = Vec::new();
-        process_task(comments, &config, task, &mut task_count, true)
+        await_change(process_task(comments, &config, task, &mut task_count, true).await).await;
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_process_task_edit_content() {
+        let mut server = mockito::Server::new_async().await;
+        let update_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({ "content": "Fixed the typo" }),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let task = test::fixtures::today_task().await;
+        // Edit content, then Quit which takes its place once the task has been edited
+        let config = test::fixtures::config()
             .await
-            .unwrap()
-            .unwrap()
+            .with_mock_url(server.url())
+            .with_mock_string("Fixed the typo")
+            .mock_select(6)
+            .create()
             .await
             .unwrap();
-        mock.assert();
+
+        let mut task_count = 3;
+        let result = process_task(Vec::new(), &config, task, &mut task_count, true)
+            .await
+            .unwrap();
+
+        assert_matches!(result, TaskAction::Quit);
+        assert_eq!(task_count, 2);
+        update_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_snoozed_due_string_uses_config_timezone() {
+        let config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver");
+        let mut task = test::fixtures::today_task().await;
+        // 9am in Vancouver, the day before daylight saving time starts
+        task.due = Some(DateInfo {
+            date: String::from("2025-03-08T17:00:00Z"),
+            is_recurring: false,
+            string: String::from("Mar 8 9am"),
+            lang: String::from("en"),
+            timezone: None,
+        });
+
+        let by_day = task
+            .snoozed_due_string(chrono::Duration::days(1), &config)
+            .unwrap();
+        assert_eq!(by_day, Some(String::from("2025-03-09 09:00")));
+
+        let by_hours = task
+            .snoozed_due_string(chrono::Duration::hours(3), &config)
+            .unwrap();
+        assert_eq!(by_hours, Some(String::from("2025-03-08 12:00")));
+
+        let overnight = task
+            .snoozed_due_string(chrono::Duration::hours(24 + 3), &config)
+            .unwrap();
+        assert_eq!(overnight, Some(String::from("2025-03-09 12:00")));
+    }
+
+    #[tokio::test]
+    async fn test_filter_not_in_future_horizon_days() {
+        let config = test::fixtures::config()
+            .await
+            .with_timezone("America/Vancouver");
+        let today_task = test::fixtures::today_task().await;
+        let due_on = |date: &str, content: &str| Task {
+            content: content.to_string(),
+            due: Some(DateInfo {
+                date: date.to_string(),
+                is_recurring: false,
+                string: date.to_string(),
+                lang: String::from("en"),
+                timezone: None,
+            }),
+            ..today_task.clone()
+        };
+        let tasks = vec![
+            due_on("2025-05-11", "Tomorrow"),
+            due_on("2025-05-13", "In three days"),
+        ];
+        let contents = |horizon_days: i64| -> Vec<String> {
+            filter_not_in_future(tasks.clone(), &config, horizon_days)
+                .unwrap()
+                .into_iter()
+                .map(|task| task.content)
+                .collect()
+        };
+
+        assert_eq!(contents(0), Vec::<String>::new());
+        assert_eq!(contents(1), vec![String::from("Tomorrow")]);
+        assert_eq!(contents(2), vec![String::from("Tomorrow")]);
+        assert_eq!(
+            contents(3),
+            vec![String::from("Tomorrow"), String::from("In three days")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_task_defer() {
+        let mut server = mockito::Server::new_async().await;
+        let task = test::fixtures::today_task().await;
+        let mut labels = task.labels.clone();
+        labels.push(String::from("someday"));
+
+        let label_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({ "labels": labels }),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+        let close_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(2);
+        config.defer_label = Some(String::from("someday"));
+        let config = config.create().await.unwrap();
+
+        let mut task_count = 3;
+        await_change(process_task(Vec::new(), &config, task, &mut task_count, true).await).await;
+        label_mock.assert();
+        close_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_process_task_recurring() {
+        let mut server = mockito::Server::new_async().await;
+        let mut task = test::fixtures::today_task().await;
+        task.due = task.due.map(|due| DateInfo {
+            is_recurring: true,
+            ..due
+        });
+
+        let update_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({ "due_string": "no date" }),
+            ))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+        let close_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+            .with_status(204)
+            .expect(2)
+            .create_async()
+            .await;
+
+        // Complete this occurrence
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .mock_select(0)
+            .create()
+            .await
+            .unwrap();
+        let mut task_count = 2;
+        await_change(process_task(Vec::new(), &config, task.clone(), &mut task_count, true).await)
+            .await;
+
+        // Complete and stop recurrence
+        let config = config.mock_select(1);
+        await_change(process_task(Vec::new(), &config, task, &mut task_count, true).await).await;
+
+        update_mock.assert();
+        close_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_timebox_task_decrements_task_count() {
+        let task = test::fixtures::today_task().await;
+        // Skip
+        let config = test::fixtures::config().await.mock_select(2);
+
+        let mut task_count = 3;
+        let action = timebox_task(&config, task.clone(), &mut task_count, false, false).await;
+        assert_matches!(action, Ok(TaskAction::Unchanged));
+        assert_eq!(task_count, 2);
+
+        let action = timebox_task(&config, task, &mut task_count, false, false).await;
+        assert_matches!(action, Ok(TaskAction::Unchanged));
+        assert_eq!(task_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_timebox_uses_duration_defaults() {
+        let mut config = test::fixtures::config().await.with_mock_string("30");
+        config
+            .duration_defaults
+            .insert("quick".to_string(), "15m".to_string());
+        let task = test::fixtures::today_task().await;
+        let quick_task = Task {
+            labels: vec!["home".to_string(), "quick".to_string()],
+            ..task.clone()
+        };
+
+        let (_, duration) = get_timebox(&config, &quick_task).unwrap();
+        assert_eq!(
+            duration,
+            Duration {
+                amount: 15,
+                unit: Unit::Minute
+            }
+        );
+
+        let (_, duration) = get_timebox(&config, &task).unwrap();
+        assert_eq!(
+            duration,
+            Duration {
+                amount: 30,
+                unit: Unit::Minute
+            }
+        );
+    }
+
+    #[test]
+    fn test_duration_try_from() {
+        let parse = |input| Duration::try_from(time::duration_from_str(input).unwrap());
+        let minutes = |amount| Duration {
+            amount,
+            unit: Unit::Minute,
+        };
+        let days = |amount| Duration {
+            amount,
+            unit: Unit::Day,
+        };
+        assert_eq!(parse("45"), Ok(minutes(45)));
+        assert_eq!(parse("45m"), Ok(minutes(45)));
+        assert_eq!(parse("2 hours"), Ok(minutes(120)));
+        assert_eq!(parse("2d"), Ok(days(2)));
+        assert_eq!(parse("1w"), Ok(days(7)));
+        assert_eq!(
+            parse("0").unwrap_err().message,
+            "Duration must be at least one minute"
+        );
     }
 
     #[tokio::test]
@@ -1,17 +1,13 @@
+use pad::PadStr;
 use regex::Regex;
 use std::borrow::Cow;
 use supports_hyperlinks::Stream;
 
-use super::{DateTimeInfo, Duration, Task, Unit, priority};
+use super::{DateTimeInfo, Duration, Task, Unit};
 use crate::{color, comments::Comment, config::Config, errors::Error, projects::Project, time};
 
 pub fn content(task: &Task, config: &Config) -> String {
-    let content = match task.priority {
-        priority::Priority::Low => color::blue_string(&task.content),
-        priority::Priority::Medium => color::yellow_string(&task.content),
-        priority::Priority::High => color::red_string(&task.content),
-        priority::Priority::None => color::normal_string(&task.content),
-    };
+    let content = color::priority_string(&task.priority, &task.content);
 
     if hyperlinks_disabled(config) {
         content
@@ -103,6 +99,88 @@ pub fn due(task: &Task, config: &Config, buffer: &str) -> String {
     }
 }
 
+const TABLE_HEADERS: [&str; 4] = ["Content", "Priority", "Due", "Labels"];
+const TABLE_SEPARATOR: &str = " | ";
+/// Content is never cut shorter than this, even if the table overflows the width
+const MIN_CONTENT_WIDTH: usize = 10;
+
+/// Tasks as uncolored columns of content, priority, due date and labels.
+/// Content is truncated with an ellipsis so that each row fits in width.
+pub fn table(tasks: &[Task], config: &Config, width: usize) -> String {
+    let rows = tasks
+        .iter()
+        .map(|task| {
+            [
+                task.content.clone(),
+                task.priority.shorthand().unwrap_or("p4").to_string(),
+                plain_due(task, config),
+                task.labels.join(" "),
+            ]
+        })
+        .collect::<Vec<[String; 4]>>();
+
+    let mut widths = TABLE_HEADERS.map(|header| header.chars().count());
+    for row in &rows {
+        for (column_width, cell) in widths.iter_mut().zip(row) {
+            *column_width = (*column_width).max(cell.chars().count());
+        }
+    }
+    // Content gets whatever is left after the other columns
+    let others = widths[1..].iter().sum::<usize>() + TABLE_SEPARATOR.len() * 3;
+    widths[0] = widths[0].min(width.saturating_sub(others).max(MIN_CONTENT_WIDTH));
+
+    let divider = widths
+        .map(|column_width| "-".repeat(column_width))
+        .join("-+-");
+    let header = table_row(&TABLE_HEADERS.map(String::from), &widths);
+    let rows = rows
+        .iter()
+        .map(|row| table_row(row, &widths))
+        .collect::<Vec<String>>();
+
+    format!("{header}\n{divider}\n{}\n", rows.join("\n"))
+}
+
+fn table_row(cells: &[String; 4], widths: &[usize; 4]) -> String {
+    cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| truncate(cell, *width).pad_to_width(*width))
+        .collect::<Vec<String>>()
+        .join(TABLE_SEPARATOR)
+        .trim_end()
+        .to_string()
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let kept = text
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>();
+        format!("{kept}…")
+    }
+}
+
+/// Due date or time without icons, colors or recurrence
+fn plain_due(task: &Task, config: &Config) -> String {
+    match &task.datetimeinfo(config) {
+        Ok(DateTimeInfo::Date { date, .. }) => {
+            time::date_to_string(date, config).unwrap_or_default()
+        }
+        Ok(DateTimeInfo::DateTime { datetime, .. }) => {
+            match time::datetime_is_today(*datetime, config) {
+                Ok(true) => time::datetime_to_string(datetime, config).unwrap_or_default(),
+                _ => datetime.format(time::FORMAT_DATE_AND_TIME).to_string(),
+            }
+        }
+        Ok(DateTimeInfo::NoDateTime) => String::new(),
+        Err(e) => e.to_string(),
+    }
+}
+
 pub fn task_url(id: &str) -> String {
     let link = color::purple_string("link");
     format!("\x1B]8;;https://app.todoist.com/app/task/{id}\x1B\\[{link}]\x1B]8;;\x1B\\")
@@ -156,6 +234,7 @@ pub async fn render_comments(config: &Config, comments: Vec<Comment>) -> Result<
 
 #[cfg(test)]
 mod tests {
+    use crate::tasks::priority::Priority;
     use crate::test;
     use crate::test::responses::ResponseFromFile;
 
@@ -171,6 +250,44 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_table_aligns_columns() {
+        let config = test::fixtures::config().await;
+        let task = test::fixtures::today_task().await;
+        let long = Task {
+            content: "Write the quarterly report for the whole team".into(),
+            priority: Priority::None,
+            labels: vec!["work".into(), "office".into()],
+            due: None,
+            ..task.clone()
+        };
+
+        let table = table(&[task, long], &config, 50);
+        let lines = table.lines().collect::<Vec<&str>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Content           | Priority | Due   | Labels",
+                "------------------+----------+-------+------------",
+                "TEST              | p2       | 05:00 | computer",
+                "Write the quarte… | p4       |       | work office",
+            ]
+        );
+        let separators = |line: &str| {
+            line.char_indices()
+                .filter(|(_, c)| *c == '|' || *c == '+')
+                .map(|(i, _)| line[..i].chars().count())
+                .collect::<Vec<usize>>()
+        };
+        assert!(
+            lines
+                .iter()
+                .all(|line| separators(line) == separators(lines[0]))
+        );
+        assert!(lines.iter().all(|line| line.chars().count() <= 50));
+    }
+
     #[test]
     fn test_task_url() {
         assert_eq!(
@@ -1,5 +1,7 @@
 use crate::color;
+use crate::errors::Error;
 use std::fmt::Display;
+use std::str::FromStr;
 
 /// Add to all_priorities function if adding another priority
 #[derive(serde_repr::Serialize_repr, serde_repr::Deserialize_repr, Debug, Clone, Eq, PartialEq)]
@@ -13,16 +15,60 @@ pub enum Priority {
 
 impl Display for Priority {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Priority::None => write!(f, "{}", color::normal_string("NONE (P4)")),
-            Priority::Low => write!(f, "{}", color::blue_string("LOW (P3)")),
-            Priority::Medium => write!(f, "{}", color::yellow_string("MEDIUM (P2)")),
-            Priority::High => write!(f, "{}", color::red_string("HIGH (P1)")),
+        write!(f, "{}", color::priority_string(self, self.name()))
+    }
+}
+
+/// Parses priorities case-insensitively from
+/// - Todoist labels where p1 is the highest, i.e. "p1" or "P4"
+/// - API numbers where 4 is the highest, i.e. "4" or "1"
+/// - names, i.e. "high", "urgent", "medium", "low" or "none"
+impl FromStr for Priority {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "p1" | "4" | "high" | "urgent" => Ok(Priority::High),
+            "p2" | "3" | "medium" => Ok(Priority::Medium),
+            "p3" | "2" | "low" => Ok(Priority::Low),
+            "p4" | "1" | "none" => Ok(Priority::None),
+            _ => Err(Error::new(
+                "priority",
+                &format!(
+                    "Unknown priority '{s}', use p1 to p4, 1 to 4, or one of high, urgent, medium, low or none"
+                ),
+            )),
         }
     }
 }
 
 impl Priority {
+    /// The name without color, i.e. "HIGH (P1)"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Priority::None => "NONE (P4)",
+            Priority::Low => "LOW (P3)",
+            Priority::Medium => "MEDIUM (P2)",
+            Priority::High => "HIGH (P1)",
+        }
+    }
+
+    /// How Todoist writes the priority in quick add, there is none for the default priority
+    pub fn shorthand(&self) -> Option<&'static str> {
+        match self {
+            Priority::High => Some("p1"),
+            Priority::Medium => Some("p2"),
+            Priority::Low => Some("p3"),
+            Priority::None => None,
+        }
+    }
+
+    /// The priority delta levels higher, or lower when delta is negative, stopping at HIGH and NONE
+    pub fn shifted(&self, delta: i8) -> Priority {
+        let level = (i16::from(self.to_integer()) + i16::from(delta)).clamp(1, 4);
+        from_integer(&Some(level as u8)).unwrap_or(Priority::None)
+    }
+
     pub fn to_integer(&self) -> u8 {
         match self {
             Priority::None => 1,
@@ -83,6 +129,15 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_shifted() {
+        assert_eq!(Priority::None.shifted(1), Priority::Low);
+        assert_eq!(Priority::Medium.shifted(-2), Priority::None);
+        assert_eq!(Priority::High.shifted(1), Priority::High);
+        assert_eq!(Priority::None.shifted(-1), Priority::None);
+        assert_eq!(Priority::Low.shifted(i8::MAX), Priority::High);
+    }
+
     #[test]
     fn test_to_integer() {
         let result = Priority::None.to_integer();
@@ -96,6 +151,22 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_from_str() {
+        assert_eq!("p1".parse::<Priority>(), Ok(Priority::High));
+        assert_eq!("HIGH".parse::<Priority>(), Ok(Priority::High));
+        assert_eq!("Urgent".parse::<Priority>(), Ok(Priority::High));
+        assert_eq!("3".parse::<Priority>(), Ok(Priority::Medium));
+        assert_eq!(" P4 ".parse::<Priority>(), Ok(Priority::None));
+        assert_eq!(
+            "p5".parse::<Priority>(),
+            Err(Error::new(
+                "priority",
+                "Unknown priority 'p5', use p1 to p4, 1 to 4, or one of high, urgent, medium, low or none"
+            ))
+        );
+    }
+
     #[test]
     fn test_fmt() {
         let result = Priority::None.to_string();
@@ -9,6 +9,8 @@ pub enum ResponseFromFile {
     AccessToken,
     /// List of all kinds of comments
     CommentsAllTypes,
+    /// Comments on one task from the user and a teammate
+    CommentsMultipleAuthors,
     /// An unscheduled task
     Task,
     TodayTasksWithoutDuration,
@@ -18,7 +20,31 @@ pub enum ResponseFromFile {
     TodayTask,
     Ids,
     TodayTasks,
+    /// Tasks in the same project where two have the same content after normalizing
+    DuplicateTasks,
+    /// Tasks with no, medium and high priority
+    MixedPriorityTasks,
+    /// An overdue task, an overdue recurring task and a task due today
+    OverdueTasks,
+    /// Tasks where the first and last have the errands label
+    LabeledTasks,
+    /// Two tasks without a due date followed by one due today
+    UndatedTasks,
+    /// A task with a subtask and a sub-subtask listed before it, and a subtask whose parent is missing
+    NestedTasks,
+    /// A task with a two line description followed by one without a description
+    DescribedTasks,
+    /// Two tasks without a duration followed by one with a duration
+    MixedDurationTasks,
+    /// Two tasks in the config project around one in a project missing from config
+    OrphanedTasks,
+    /// An unassigned task, one assigned to the user and one assigned to someone else
+    AssignedTasks,
+    /// Two tasks completed today from the completed tasks endpoint
+    CompletedTasks,
     Comment,
+    /// The user and a teammate sharing a project
+    Collaborators,
     #[allow(dead_code)]
     Label,
     Labels,
@@ -51,7 +77,9 @@ impl ResponseFromFile {
         let replace_with: Vec<(&str, String)> = match self {
             Self::AccessToken => Vec::new(),
             Self::CommentsAllTypes => Vec::new(),
+            Self::CommentsMultipleAuthors => Vec::new(),
             Self::Comment => Vec::new(),
+            Self::Collaborators => Vec::new(),
             Self::Task => Vec::new(),
             Self::Ids => Vec::new(),
             Self::Section => Vec::new(),
@@ -66,6 +94,17 @@ impl ResponseFromFile {
             Self::UnscheduledTasks => vec![("INSERTDATE", super::today_date().await)],
             Self::TodayTasksWithoutDuration => vec![("INSERTDATE", super::today_date().await)],
             Self::TodayTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::DuplicateTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::MixedPriorityTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::OverdueTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::LabeledTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::UndatedTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::NestedTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::DescribedTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::MixedDurationTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::OrphanedTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::AssignedTasks => vec![("INSERTDATE", super::today_date().await)],
+            Self::CompletedTasks => vec![("INSERTDATE", super::today_date().await)],
             Self::Versions => vec![("INSERTVERSION", VERSION.to_string())],
         };
 
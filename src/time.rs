@@ -1,16 +1,16 @@
 use crate::config::Config;
 use crate::errors::Error;
 
-use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
 use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::str::FromStr;
 
 pub const FORMAT_DATE: &str = "%Y-%m-%d";
-const FORMAT_TIME: &str = "%H:%M";
+pub const FORMAT_TIME: &str = "%H:%M";
 const FORMAT_DATETIME: &str = "%Y-%m-%dT%H:%M:%S";
-const FORMAT_DATETIME_ZULU: &str = "%Y-%m-%dT%H:%M:%SZ";
+pub const FORMAT_DATETIME_ZULU: &str = "%Y-%m-%dT%H:%M:%SZ";
 const FORMAT_DATETIME_LONG: &str = "%Y-%m-%dT%H:%M:%S%.fZ";
 
 pub const FORMAT_DATE_AND_TIME: &str = "%Y-%m-%d %H:%M";
@@ -99,10 +99,10 @@ pub fn datetime_from_str(str: &str, timezone: Tz) -> Result<DateTime<Tz>, Error>
         19 => parse_datetime(str, timezone, FORMAT_DATETIME),
         20 => parse_datetime(str, Tz::UTC, FORMAT_DATETIME_ZULU),
         27 => parse_datetime(str, Tz::UTC, FORMAT_DATETIME_LONG),
-        length => Err(Error {
-            source: "datetime_from_str".to_string(),
-            message: format!("cannot parse {length} length DateTime: {str}"),
-        }),
+        length => Err(Error::new(
+            "datetime_from_str",
+            &format!("cannot parse {length} length DateTime: {str}"),
+        )),
     }
 }
 
@@ -227,6 +227,34 @@ pub fn datetime_to_string(datetime: &DateTime<Tz>, config: &Config) -> Result<St
     }
 }
 
+/// The first Saturday after today in the config timezone. On a Saturday this is the one a week later.
+pub fn next_saturday(config: &Config) -> Result<NaiveDate, Error> {
+    let today = naive_date_today(config)?;
+    let days = 7 - (today.weekday().num_days_from_sunday() + 1) % 7;
+    Ok(today + Duration::days(i64::from(days)))
+}
+
+/// Parses a relative amount of time such as "3 hours", "2d" or "1 week". A plain number is minutes
+pub fn duration_from_str(delta: &str) -> Result<Duration, Error> {
+    let delta = delta.trim();
+    let split = delta
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(delta.len());
+    let (amount, unit) = delta.split_at(split);
+    let amount = amount.parse::<i64>()?;
+
+    match unit.trim().to_lowercase().as_str() {
+        "" | "m" | "min" | "mins" | "minute" | "minutes" => Ok(Duration::minutes(amount)),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Ok(Duration::hours(amount)),
+        "d" | "day" | "days" => Ok(Duration::days(amount)),
+        "w" | "week" | "weeks" => Ok(Duration::weeks(amount)),
+        _ => Err(Error::new(
+            "duration_from_str",
+            &format!("Could not parse {delta}, use minutes, hours, days or weeks i.e. \"3 hours\""),
+        )),
+    }
+}
+
 // ----------- TZ FUNCTIONS --------------
 
 pub fn timezone_from_str(timezone_string: &str) -> Result<Tz, Error> {
@@ -265,6 +293,34 @@ mod tests {
     use super::*;
     use chrono_tz::Tz;
 
+    #[tokio::test]
+    async fn test_next_saturday() {
+        // The fixed clock is Saturday 2025-05-10 in Vancouver
+        let config = crate::test::fixtures::config().await;
+        assert_eq!(
+            next_saturday(&config),
+            Ok(NaiveDate::from_ymd_opt(2025, 5, 17).unwrap())
+        );
+
+        // but still Friday 2025-05-09 in Pago Pago
+        let config = config.with_timezone("Pacific/Pago_Pago");
+        assert_eq!(
+            next_saturday(&config),
+            Ok(NaiveDate::from_ymd_opt(2025, 5, 10).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_duration_from_str() {
+        assert_eq!(duration_from_str("3 hours"), Ok(Duration::hours(3)));
+        assert_eq!(duration_from_str("2d"), Ok(Duration::days(2)));
+        assert_eq!(duration_from_str("45 min"), Ok(Duration::minutes(45)));
+        assert_eq!(duration_from_str("1 Week"), Ok(Duration::weeks(1)));
+        assert_eq!(duration_from_str("45"), Ok(Duration::minutes(45)));
+        assert!(duration_from_str("3 fortnights").is_err());
+        assert!(duration_from_str("hours").is_err());
+    }
+
     #[test]
     fn test_is_date() {
         assert!(is_date("2022-10-05"));
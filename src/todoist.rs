@@ -1,13 +1,16 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use futures::future;
 use serde_json::{Number, Value, json};
 use std::collections::HashMap;
 use urlencoding::encode;
+use uuid::Uuid;
 mod request;
 
 use crate::comments::{Comment, CommentResponse};
 use crate::config::Config;
 use crate::debug::maybe_print;
-use crate::errors::Error;
+use crate::errors::{Error, ErrorKind};
 use crate::id::{self, Resource};
 use crate::labels::{self, Label, LabelResponse};
 use crate::oauth::{CLIENT_ID, CLIENT_SECRET};
@@ -15,27 +18,33 @@ use crate::projects::{Project, ProjectResponse};
 use crate::sections::{Section, SectionResponse};
 use crate::shell::execute_command;
 use crate::tasks::priority::Priority;
-use crate::tasks::{Task, TaskResponse};
+use crate::tasks::{CompletedTaskResponse, Task, TaskResponse};
 use crate::users;
-use crate::users::User;
+use crate::users::{Collaborator, CollaboratorResponse, User};
 use crate::{color, projects, sections, tasks, time};
 use crate::{comments, oauth};
 use regex::Regex;
+use serde::Deserialize;
 
 // TODOIST URLS
 pub const TASKS_URL: &str = "/api/v1/tasks/";
 pub const COMMENTS_URL: &str = "/api/v1/comments/";
+const COMPLETED_TASKS_URL: &str = "/api/v1/tasks/completed/by_completion_date";
 const SECTIONS_URL: &str = "/api/v1/sections";
 const USER_URL: &str = "/api/v1/user";
 const PROJECTS_URL: &str = "/api/v1/projects";
 const LABELS_URL: &str = "/api/v1/labels";
 const IDS_URL: &str = "/api/v1/id_mappings/";
+const SYNC_URL: &str = "/api/v1/sync";
 const ACCESS_TOKEN_URL: &str = "/oauth/access_token";
 pub const OAUTH_URL: &str = "/oauth/authorize";
 
 /// Number of items that can be requested from API at once
 pub const QUERY_LIMIT: u8 = 200;
 
+/// How far back to look for completed tasks when no start is given
+const COMPLETED_DEFAULT_DAYS: i64 = 30;
+
 /// Used to sanity check all the Todoist API endpoints to make sure that we are able to process the JSON payloads they are sending back.
 pub async fn test_all_endpoints(config: Config) -> Result<String, Error> {
     let name = "TEST".to_string();
@@ -80,17 +89,17 @@ pub async fn test_all_endpoints(config: Config) -> Result<String, Error> {
     let _comment = create_comment(&config, &task, name.clone(), false).await?;
 
     println!("Getting comments for task");
-    let _comments = all_comments(&config, &task, Some(1)).await?;
+    let _comments = all_comments(&config, &task, Some(1), None).await?;
 
     println!("Deleting task");
     delete_task(&config, &task, false).await?;
 
     println!("Creating two tasks with quick_add_task");
-    let _task = quick_create_task(&config, &name, None).await?;
-    let task = quick_create_task(&config, &name, Some(String::from("tomorrow"))).await?;
+    let _task = quick_create_task(&config, &name, None, None).await?;
+    let task = quick_create_task(&config, &name, Some(String::from("tomorrow")), None).await?;
 
     println!("Finding tasks with tasks_for_project");
-    let _tasks = all_tasks_by_project(&config, &project, Some(1)).await?;
+    let _tasks = all_tasks_by_project(&config, &project, Some(1), false).await?;
 
     println!("Finding tasks with tasks_for_filter");
     let _tasks = all_tasks_by_filter(&config, "tod", Some(1)).await?;
@@ -155,14 +164,53 @@ pub async fn get_v1_ids(
 }
 
 /// Add a new task to the inbox with natural language support
+/// With an idempotency key the request is sent with it as X-Request-Id, and a key that already
+/// created a task in this run returns that task instead of sending the request again.
 pub async fn quick_create_task(
     config: &Config,
     content: &str,
     reminder: Option<String>,
+    idempotency_key: Option<&str>,
 ) -> Result<Task, Error> {
+    let created_tasks = &config.internal.created_tasks;
+    if let Some(task) = idempotency_key.and_then(|key| created_tasks.get(key)) {
+        return Ok(task);
+    }
+
     let url = format!("{TASKS_URL}quick");
     let body = json!({"text": content, "auto_reminder": true, "reminder": reminder});
 
+    let json = match idempotency_key {
+        Some(key) => {
+            request::post_todoist_with_request_id(config, url, body, true, key.to_string()).await?
+        }
+        None => request::post_todoist(config, url, body, true).await?,
+    };
+    maybe_run_command(config.task_create_command.as_deref()).await;
+    let task = tasks::json_to_task(json)?;
+    if let Some(key) = idempotency_key {
+        created_tasks.insert(key.to_string(), task.clone());
+    }
+    Ok(task)
+}
+
+/// Add a new task to the inbox with an explicit priority, labels and description, without natural language parsing
+pub async fn create_inbox_task(
+    config: &Config,
+    content: &str,
+    priority: Option<&Priority>,
+    labels: &[String],
+    description: Option<&str>,
+) -> Result<Task, Error> {
+    let url = TASKS_URL.into();
+    let mut body = json!({"content": content, "auto_reminder": true, "labels": labels});
+    if let Some(priority) = priority {
+        body["priority"] = json!(priority.to_integer());
+    }
+    if let Some(description) = description {
+        body["description"] = json!(description);
+    }
+
     let json = request::post_todoist(config, url, body, true).await?;
     maybe_run_command(config.task_create_command.as_deref()).await;
     tasks::json_to_task(json)
@@ -232,17 +280,85 @@ pub async fn create_task(
     tasks::json_to_task(json)
 }
 
-/// Get a vector of all tasks for a project
+/// Get a vector of all tasks for a section
+pub async fn all_tasks_by_section(
+    config: &Config,
+    section: &Section,
+    limit: Option<u8>,
+) -> Result<Vec<Task>, Error> {
+    let limit = limit.unwrap_or(QUERY_LIMIT);
+    let section_id = section.id.clone();
+    let mut tasks = Vec::new();
+    let mut url = format!("{TASKS_URL}?section_id={section_id}&limit={limit}");
+    let title_regex = config.task_exclude_regex.as_ref();
+
+    loop {
+        let json = request::get_todoist(config, url, true).await?;
+        let TaskResponse {
+            results,
+            next_cursor,
+        } = tasks::json_to_tasks_response(json)?;
+
+        let results = filter_tasks_by_title(results, title_regex, config);
+        tasks.extend(results);
+
+        match next_cursor {
+            None => break,
+            Some(cursor) => {
+                url = format!("{TASKS_URL}?section_id={section_id}&limit={limit}&cursor={cursor}");
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+/// Get a vector of all tasks for a project.
+/// With recurse, tasks from sub-projects in config (at any depth) are appended after the parent's, each keeping its own project_id.
 pub async fn all_tasks_by_project(
     config: &Config,
     project: &Project,
     limit: Option<u8>,
+    recurse: bool,
+) -> Result<Vec<Task>, Error> {
+    let mut project_ids = vec![project.id.clone()];
+    if recurse {
+        project_ids.extend(descendant_project_ids(
+            &config.projects().await?,
+            &project.id,
+        ));
+    }
+
+    let mut tasks = Vec::new();
+    for project_id in project_ids {
+        tasks.extend(tasks_by_project_id(config, &project_id, limit).await?);
+    }
+    Ok(tasks)
+}
+
+/// Ids of every project below parent_id, depth first in config order
+fn descendant_project_ids(projects: &[Project], parent_id: &str) -> Vec<String> {
+    projects
+        .iter()
+        .filter(|project| project.parent_id.as_deref() == Some(parent_id))
+        .flat_map(|child| {
+            let mut ids = vec![child.id.clone()];
+            ids.extend(descendant_project_ids(projects, &child.id));
+            ids
+        })
+        .collect()
+}
+
+/// Get a vector of all tasks directly in a project, following pagination cursors
+async fn tasks_by_project_id(
+    config: &Config,
+    project_id: &str,
+    limit: Option<u8>,
 ) -> Result<Vec<Task>, Error> {
     let limit = limit.unwrap_or(QUERY_LIMIT);
-    let project_id = project.id.clone();
     let mut tasks = Vec::new();
     let mut url = format!("{TASKS_URL}?project_id={project_id}&limit={limit}");
     let title_regex = config.task_exclude_regex.as_ref();
+    let mut first_page = true;
 
     loop {
         let json = request::get_todoist(config, url, true).await?;
@@ -251,6 +367,17 @@ pub async fn all_tasks_by_project(
             next_cursor,
         } = tasks::json_to_tasks_response(json)?;
 
+        if first_page {
+            let source = format!("Project {project_id}");
+            maybe_warn_truncated(
+                config,
+                results.len(),
+                limit,
+                next_cursor.as_deref(),
+                &source,
+            );
+            first_page = false;
+        }
         let results = filter_tasks_by_title(results, title_regex, config);
         tasks.extend(results);
 
@@ -264,7 +391,46 @@ pub async fn all_tasks_by_project(
     Ok(tasks)
 }
 
-/// Uses multiple filters (comma-separated) to fetch multiple lists of tasks in parallel. Returns each list of tasks with the filter query that was used to find it.
+/// Get the tasks in a project that were completed between two datetimes.
+/// Until defaults to now and since to 30 days before until.
+pub async fn completed_tasks_by_project(
+    config: &Config,
+    project: &Project,
+    since: Option<DateTime<Tz>>,
+    until: Option<DateTime<Tz>>,
+) -> Result<Vec<Task>, Error> {
+    let until = match until {
+        Some(until) => until.with_timezone(&Utc),
+        None => time::datetime_now(config)?.with_timezone(&Utc),
+    };
+    let since = since
+        .map(|since| since.with_timezone(&Utc))
+        .unwrap_or(until - chrono::Duration::days(COMPLETED_DEFAULT_DAYS));
+    let since = encode(&since.format(time::FORMAT_DATETIME_ZULU).to_string()).into_owned();
+    let until = encode(&until.format(time::FORMAT_DATETIME_ZULU).to_string()).into_owned();
+    let project_id = project.id.clone();
+    let base_url = format!(
+        "{COMPLETED_TASKS_URL}?project_id={project_id}&since={since}&until={until}&limit={QUERY_LIMIT}"
+    );
+    let mut url = base_url.clone();
+    let mut tasks = Vec::new();
+
+    loop {
+        let json = request::get_todoist(config, url, true).await?;
+        let CompletedTaskResponse { items, next_cursor } =
+            tasks::json_to_completed_tasks_response(json)?;
+        tasks.extend(items);
+
+        match next_cursor {
+            None => break,
+            Some(cursor) => url = format!("{base_url}&cursor={cursor}"),
+        }
+    }
+    Ok(tasks)
+}
+
+/// Fetches each comma separated filter concurrently.
+/// Groups are returned in the order the filters were given, however long each request takes.
 pub async fn all_tasks_by_filters(
     config: &Config,
     filter: &str,
@@ -282,6 +448,43 @@ pub async fn all_tasks_by_filters(
     Ok(acc)
 }
 
+/// Only the fields needed to count tasks, so that pages are not deserialized into full tasks
+#[derive(Deserialize)]
+struct TaskCountResponse {
+    results: Vec<TaskContent>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TaskContent {
+    content: String,
+}
+
+/// Counts the tasks matching a filter page by page, without building the full list of tasks
+pub async fn count_tasks_by_filter(config: &Config, filter: &str) -> Result<usize, Error> {
+    let title_regex = config.task_exclude_regex.as_ref();
+    let mut count = 0;
+
+    for_each_filter_page(config, filter, QUERY_LIMIT, |json| {
+        let TaskCountResponse {
+            results,
+            next_cursor,
+        } = serde_json::from_str(&json)?;
+
+        count += match title_regex {
+            Some(re) => results
+                .iter()
+                .filter(|task| !re.is_match(&task.content))
+                .count(),
+            None => results.len(),
+        };
+        Ok(next_cursor)
+    })
+    .await?;
+
+    Ok(count)
+}
+
 /// Fetches a list of tasks by a single filter query.
 pub async fn all_tasks_by_filter(
     config: &Config,
@@ -289,30 +492,62 @@ pub async fn all_tasks_by_filter(
     limit: Option<u8>,
 ) -> Result<(String, Vec<Task>), Error> {
     let limit = limit.unwrap_or(QUERY_LIMIT);
-    let encoded = encode(filter);
+    let cache_key = format!("{}&limit={limit}", encode(filter));
+    let task_cache = &config.internal.task_cache;
+    if let Some(tasks) = task_cache.get(&cache_key, config.filter_cache_ttl()) {
+        return Ok((filter.to_string(), tasks));
+    }
+
     let mut tasks: Vec<Task> = Vec::new();
-    let mut url = format!("{TASKS_URL}filter?query={encoded}&limit={limit}");
     let title_regex = config.task_exclude_regex.as_ref();
+    let source = format!("Filter '{filter}'");
+    let mut first_page = true;
 
-    loop {
-        let json = request::get_todoist(config, url, true).await?;
+    for_each_filter_page(config, filter, limit, |json| {
         let TaskResponse {
             results,
             next_cursor,
         } = tasks::json_to_tasks_response(json)?;
 
+        if first_page {
+            maybe_warn_truncated(
+                config,
+                results.len(),
+                limit,
+                next_cursor.as_deref(),
+                &source,
+            );
+            first_page = false;
+        }
         let results = filter_tasks_by_title(results, title_regex, config);
         tasks.extend(results);
+        Ok(next_cursor)
+    })
+    .await?;
 
-        match next_cursor {
-            None => break,
-            Some(string) => {
-                url = format!("{TASKS_URL}filter?query={encoded}&limit={limit}&cursor={string}");
+    task_cache.insert(cache_key, tasks.clone());
+    Ok((filter.to_string(), tasks))
+}
+
+/// Requests every page of tasks matching a filter. on_page gets the body of each page and returns its next cursor.
+async fn for_each_filter_page(
+    config: &Config,
+    filter: &str,
+    limit: u8,
+    mut on_page: impl FnMut(String) -> Result<Option<String>, Error>,
+) -> Result<(), Error> {
+    let encoded = encode(filter);
+    let mut url = format!("{TASKS_URL}filter?query={encoded}&limit={limit}");
+
+    loop {
+        let json = request::get_todoist(config, url, true).await?;
+        match on_page(json)? {
+            None => return Ok(()),
+            Some(cursor) => {
+                url = format!("{TASKS_URL}filter?query={encoded}&limit={limit}&cursor={cursor}");
             }
-        };
+        }
     }
-
-    Ok((filter.to_string(), tasks))
 }
 
 pub async fn all_sections_by_project(
@@ -365,6 +600,35 @@ pub async fn all_projects(config: &Config, limit: Option<u8>) -> Result<Vec<Proj
     Ok(projects)
 }
 
+/// All of the people a project is shared with
+pub async fn all_collaborators(
+    config: &Config,
+    project_id: &str,
+    limit: Option<u8>,
+) -> Result<Vec<Collaborator>, Error> {
+    let limit = limit.unwrap_or(QUERY_LIMIT);
+    let mut url = format!("{PROJECTS_URL}/{project_id}/collaborators?limit={limit}");
+    let mut collaborators: Vec<Collaborator> = Vec::new();
+
+    loop {
+        let json = request::get_todoist(config, url, true).await?;
+        let CollaboratorResponse {
+            results,
+            next_cursor,
+        } = users::json_to_collaborators_response(json)?;
+        collaborators.extend(results);
+        match next_cursor {
+            None => break,
+            Some(string) => {
+                url = format!(
+                    "{PROJECTS_URL}/{project_id}/collaborators?limit={limit}&cursor={string}"
+                );
+            }
+        };
+    }
+    Ok(collaborators)
+}
+
 pub async fn all_labels(
     config: &Config,
     spinner: bool,
@@ -436,6 +700,191 @@ pub async fn update_task_priority(
     Ok("✓".into())
 }
 
+/// A change to a single task that can be sent on its own or together with others in batch_update
+#[derive(Debug, Clone)]
+pub enum TaskUpdate {
+    Priority(Task, Priority),
+    /// Replaces all of the task's labels
+    Labels(Task, Vec<String>),
+}
+
+impl TaskUpdate {
+    /// The arguments for an item_update sync command
+    fn args(&self) -> Value {
+        match self {
+            TaskUpdate::Priority(task, priority) => json!({ "id": task.id, "priority": priority }),
+            TaskUpdate::Labels(task, labels) => json!({ "id": task.id, "labels": labels }),
+        }
+    }
+
+    /// Sends the update in a request of its own
+    pub async fn send(&self, config: &Config) -> Result<String, Error> {
+        match self {
+            TaskUpdate::Priority(task, priority) => {
+                update_task_priority(config, task, priority, false).await
+            }
+            TaskUpdate::Labels(task, labels) => {
+                update_task_labels(config, task, labels.clone(), false).await
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FiltersResponse {
+    filters: Vec<SavedFilter>,
+}
+
+/// A filter saved in Todoist, only the fields needed to look it up by name
+#[derive(Deserialize)]
+struct SavedFilter {
+    name: String,
+    query: String,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+/// Keywords of Todoist's filter syntax that are never looked up as saved filter names
+const QUERY_KEYWORDS: [&str; 12] = [
+    "today",
+    "tomorrow",
+    "yesterday",
+    "overdue",
+    "recurring",
+    "no date",
+    "no due date",
+    "no deadline",
+    "no labels",
+    "no priority",
+    "subtask",
+    "shared",
+];
+
+/// Replaces each comma separated part of a filter that is the name of a saved filter, ignoring case, with its query.
+/// Parts that are clearly query syntax are not looked up, and when the lookup fails the filter is used as it is.
+pub async fn resolve_filter_names(config: &Config, filter: &str) -> String {
+    let parts = filter.split(',').map(str::trim).collect::<Vec<&str>>();
+    if parts.iter().all(|part| is_query_syntax(part)) {
+        return filter.to_string();
+    }
+
+    let filters = match saved_filters(config).await {
+        Ok(filters) => filters,
+        Err(e) => {
+            maybe_print(
+                config,
+                format!("Could not look up saved filters, using '{filter}' as a query: {e}"),
+            );
+            return filter.to_string();
+        }
+    };
+
+    parts
+        .into_iter()
+        .map(|part| {
+            filters
+                .iter()
+                .find(|saved| !saved.is_deleted && saved.name.eq_ignore_ascii_case(part))
+                .map_or(part.to_string(), |saved| saved.query.clone())
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Operators, digits and keywords only appear in queries, i.e. "today & p1" or "7 days"
+fn is_query_syntax(part: &str) -> bool {
+    let lowercase = part.to_lowercase();
+    part.is_empty()
+        || part.contains(['&', '|', '!', '(', ')', '#', '@', ':', '*', '/'])
+        || part.starts_with(|c: char| c.is_ascii_digit())
+        || ["p1", "p2", "p3", "p4"].contains(&lowercase.as_str())
+        || QUERY_KEYWORDS.contains(&lowercase.as_str())
+}
+
+async fn saved_filters(config: &Config) -> Result<Vec<SavedFilter>, Error> {
+    let body = json!({"sync_token": "*", "resource_types": ["filters"]});
+    let json = request::post_todoist(config, SYNC_URL.to_string(), body, false).await?;
+    let FiltersResponse { filters } = serde_json::from_str(&json)?;
+    Ok(filters)
+}
+
+#[derive(Deserialize)]
+struct SyncResponse {
+    /// "ok" or an error object for each command uuid
+    sync_status: HashMap<String, Value>,
+}
+
+/// Sends many task updates in a single sync request.
+/// Updates that the sync request rejects, or all of them when the request fails, are sent one at a time instead.
+pub async fn batch_update(config: &Config, updates: Vec<TaskUpdate>) -> Result<(), Error> {
+    // Each command needs its own uuid to match it with its status in the response
+    let uuids = updates
+        .iter()
+        .map(|_| Uuid::new_v4().to_string())
+        .collect::<Vec<String>>();
+    let commands = updates
+        .iter()
+        .zip(&uuids)
+        .map(|(update, uuid)| json!({"type": "item_update", "uuid": uuid, "args": update.args()}))
+        .collect::<Vec<Value>>();
+    let body = json!({ "commands": commands });
+
+    let failed = match request::post_todoist(config, SYNC_URL.to_string(), body, true).await {
+        Ok(json) => {
+            let SyncResponse { sync_status } = serde_json::from_str(&json)?;
+            updates
+                .into_iter()
+                .zip(uuids)
+                .filter(|(_, uuid)| sync_status.get(uuid) != Some(&json!("ok")))
+                .map(|(update, _)| update)
+                .collect()
+        }
+        Err(e) => {
+            maybe_print(
+                config,
+                format!("Batch update failed, sending updates one at a time: {e}"),
+            );
+            updates
+        }
+    };
+
+    let results = future::join_all(failed.iter().map(|update| update.send(config))).await;
+    results
+        .into_iter()
+        .collect::<Result<Vec<String>, Error>>()?;
+    Ok(())
+}
+
+/// Assign a task to a collaborator by their user ID
+pub async fn update_task_assignee(
+    config: &Config,
+    task: &Task,
+    responsible_uid: &str,
+    spinner: bool,
+) -> Result<String, Error> {
+    let body = json!({ "responsible_uid": responsible_uid });
+    let url = format!("{}{}", TASKS_URL, task.id);
+
+    request::post_todoist(config, url, body, spinner).await?;
+    // Does not pass back an task
+    Ok("✓".into())
+}
+
+/// Set the duration of a task, leaving its due date alone
+pub async fn update_task_duration(
+    config: &Config,
+    task: &Task,
+    duration: &tasks::Duration,
+    spinner: bool,
+) -> Result<String, Error> {
+    let body = json!({ "duration": duration.amount, "duration_unit": duration.unit.api_name() });
+    let url = format!("{}{}", TASKS_URL, task.id);
+
+    request::post_todoist(config, url, body, spinner).await?;
+    // Does not pass back an task
+    Ok("✓".into())
+}
+
 /// Add a label to task by ID
 pub async fn add_task_label(
     config: &Config,
@@ -458,7 +907,7 @@ pub async fn update_task_due_natural_language(
     config: &Config,
     task: &Task,
     due_string: String,
-    duration: Option<u32>,
+    duration: Option<tasks::Duration>,
     spinner: bool,
 ) -> Result<String, Error> {
     let due_string = if let Some(due) = &task.due {
@@ -472,7 +921,11 @@ pub async fn update_task_due_natural_language(
     };
 
     let body = if let Some(duration) = duration {
-        json!({ "due_string": due_string, "duration": duration, "duration_unit": "minute" })
+        json!({
+            "due_string": due_string,
+            "duration": duration.amount,
+            "duration_unit": duration.unit.api_name()
+        })
     } else {
         json!({ "due_string": due_string })
     };
@@ -483,6 +936,16 @@ pub async fn update_task_due_natural_language(
     Ok("✓".into())
 }
 
+/// Remove the due date so the task no longer repeats, closing it afterwards completes it for good
+pub async fn stop_recurrence(config: &Config, task: &Task, spinner: bool) -> Result<String, Error> {
+    let body = json!({ "due_string": "no date" });
+    let url = format!("{}{}", TASKS_URL, task.id);
+
+    request::post_todoist(config, url, body, spinner).await?;
+    // Does not pass back a task
+    Ok("✓".into())
+}
+
 /// Update the content of a task by ID
 pub async fn update_task_content(
     config: &Config,
@@ -508,10 +971,10 @@ pub async fn update_task_deadline(
     let body = match date {
         Some(date) => {
             if !time::is_date(&date) {
-                return Err(Error {
-                    message: "Not a valid date in format YYYY-MM-DD, got: {date}".to_string(),
-                    source: "update_task_deadline".to_string(),
-                });
+                return Err(Error::new(
+                    "update_task_deadline",
+                    "Not a valid date in format YYYY-MM-DD, got: {date}",
+                ));
             }
             json!({"deadline_date": date, "deadline_lang": "en"})
         }
@@ -558,21 +1021,70 @@ pub async fn update_task_labels(
 /// Complete the last task returned by "next task"
 /// The API does not return any data, so we can't return a new task
 pub async fn complete_task(config: &Config, task: &Task, spinner: bool) -> Result<String, Error> {
-    let task_id = task.id.clone();
-    let url = format!("{TASKS_URL}{task_id}/close");
+    post_task_action(config, &task.id, "close", spinner).await?;
+    after_complete(config, &task.id).await?;
 
-    request::post_todoist(config, url, Value::Null, spinner).await?;
+    // API does not pass back a task
+    Ok("✓".into())
+}
 
-    if !cfg!(test) {
-        maybe_run_command(config.task_complete_command.as_deref()).await;
-        config.reload().await?.clear_next_task().save().await?;
-    }
-    // Execute the execute_command() complete_task_command if set in config
+/// Reopens a completed task
+pub async fn reopen_task(config: &Config, task: &Task, spinner: bool) -> Result<String, Error> {
+    post_task_action(config, &task.id, "reopen", spinner).await?;
+    // API does not pass back a task
+    Ok("✓".into())
+}
+
+/// Complete a task by its id without fetching it first, unknown ids give a not found error
+pub async fn close_task_by_id(config: &Config, id: &str) -> Result<String, Error> {
+    post_task_action(config, id, "close", true).await?;
+    after_complete(config, id).await?;
+
+    // API does not pass back a task
+    Ok("✓".into())
+}
 
+/// Reopens a completed task by its id, unknown ids give a not found error
+pub async fn reopen_task_by_id(config: &Config, id: &str) -> Result<String, Error> {
+    post_task_action(config, id, "reopen", true).await?;
     // API does not pass back a task
     Ok("✓".into())
 }
 
+/// Posts an action such as close or reopen to a task by its id
+async fn post_task_action(
+    config: &Config,
+    id: &str,
+    action: &str,
+    spinner: bool,
+) -> Result<(), Error> {
+    let url = format!("{TASKS_URL}{id}/{action}");
+
+    request::post_todoist(config, url, Value::Null, spinner)
+        .await
+        .map_err(|e| match e.kind {
+            ErrorKind::NotFound => Error::new(
+                &format!("{action}_task"),
+                &format!("No task found with id {id}"),
+            )
+            .with_kind(ErrorKind::NotFound),
+            _ => e,
+        })?;
+    Ok(())
+}
+
+/// Runs task_complete_command and forgets the next task if it was the one completed
+async fn after_complete(config: &Config, id: &str) -> Result<(), Error> {
+    if !cfg!(test) {
+        maybe_run_command(config.task_complete_command.as_deref()).await;
+        let config = config.reload().await?;
+        if config.next_task().is_some_and(|task| task.id == id) {
+            config.clear_next_task().save().await?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn delete_task(config: &Config, task: &Task, spinner: bool) -> Result<String, Error> {
     let body = json!({});
     let url = format!("{}{}", TASKS_URL, task.id);
@@ -643,10 +1155,12 @@ pub async fn get_user_data(config: &Config) -> Result<User, Error> {
 /// Returns all of the comments for a task from the Todoist JSON API
 /// Paginates through the results until all comments are retrieved.
 /// Then will filter out deleted and excluded comments based on the Regex Config.
+/// With author_id only the comments posted by that user are kept.
 pub async fn all_comments(
     config: &Config,
     task: &Task,
     limit: Option<u8>,
+    author_id: Option<String>,
 ) -> Result<Vec<Comment>, Error> {
     let task_id = &task.id;
     let limit = limit.unwrap_or(QUERY_LIMIT);
@@ -668,13 +1182,15 @@ pub async fn all_comments(
                     Some(regex) => !regex.is_match(&c.content),
                     None => true,
                 }
+                && author_id
+                    .as_ref()
+                    .is_none_or(|author_id| c.posted_uid.as_ref() == Some(author_id))
         }));
 
         match next_cursor {
             None => break,
             Some(cursor) => {
-                url =
-                    format!("{COMMENTS_URL}?task_id={task_id}&limit={QUERY_LIMIT}&cursor={cursor}");
+                url = format!("{COMMENTS_URL}?task_id={task_id}&limit={limit}&cursor={cursor}");
             }
         };
     }
@@ -682,6 +1198,31 @@ pub async fn all_comments(
     Ok(comments)
 }
 
+/// A single full page without a cursor is what a response cut off at the limit looks like, so the user is warned
+/// that some tasks may be missing. Only the first page is checked, once a cursor has been followed the last page
+/// can be full without anything being left out.
+fn maybe_warn_truncated(
+    config: &Config,
+    page_len: usize,
+    limit: u8,
+    next_cursor: Option<&str>,
+    source: &str,
+) {
+    if next_cursor.is_none() && page_len == usize::from(limit) {
+        let warning = Error::new(
+            source,
+            &format!(
+                "Warning: {source} returned exactly {limit} tasks and no cursor, the results may be truncated"
+            ),
+        )
+        .with_kind(ErrorKind::Warning);
+        // Shown alongside errors from async processes once the command finishes
+        if let Some(tx) = &config.internal.tx {
+            let _ = tx.send(warning);
+        }
+    }
+}
+
 // Executes a CLI command (if set in the configuration).
 async fn maybe_run_command(command: Option<&str>) {
     if let Some(command) = command {
@@ -737,6 +1278,7 @@ mod tests {
         assert_eq!(
             get_user_data(&config).await,
             Ok(User {
+                id: "111111".to_string(),
                 tz_info: TzInfo {
                     timezone: "America/Vancouver".to_string()
                 }
@@ -762,12 +1304,35 @@ mod tests {
             .with_time_provider(TimeProviderEnum::Fixed(FixedTimeProvider));
 
         assert_eq!(
-            quick_create_task(&config, "testy test", None).await,
+            quick_create_task(&config, "testy test", None, None).await,
             Ok(test::fixtures::today_task().await)
         );
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn test_quick_create_task_with_idempotency_key() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/tasks/quick")
+            .match_header("X-Request-Id", "tod-import-key")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        let first = quick_create_task(&config, "testy test", None, Some("tod-import-key")).await;
+        let retried = quick_create_task(&config, "testy test", None, Some("tod-import-key")).await;
+
+        assert_eq!(first, Ok(test::fixtures::today_task().await));
+        assert_eq!(retried, first);
+        mock.assert();
+    }
+
     #[tokio::test]
     async fn test_all_labels() {
         let mut server = mockito::Server::new_async().await;
@@ -885,7 +1450,7 @@ mod tests {
         let project = binding.first().unwrap();
 
         assert_eq!(
-            all_tasks_by_project(&config_with_timezone, project, None).await,
+            all_tasks_by_project(&config_with_timezone, project, None, false).await,
             Ok(vec![test::fixtures::today_task().await])
         );
 
@@ -893,16 +1458,620 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_complete_task() {
+    async fn test_all_tasks_by_project_follows_next_cursor() {
         let mut server = mockito::Server::new_async().await;
-        let mock = server
-            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+        let response =
+            serde_json::from_str::<Value>(&ResponseFromFile::TodayTasks.read().await).unwrap();
+        let task = response["results"][0].clone();
+        let page = |range: std::ops::Range<usize>, next_cursor: Value| {
+            let results = range
+                .map(|n| {
+                    let mut task = task.clone();
+                    task["id"] = Value::String(format!("task{n}"));
+                    task
+                })
+                .collect::<Vec<Value>>();
+            serde_json::json!({"results": results, "next_cursor": next_cursor}).to_string()
+        };
+
+        let first = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(ResponseFromFile::TodayTask.read().await)
+            .with_body(page(0..200, Value::String("abc".into())))
             .create_async()
             .await;
-
+        let second = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200&cursor=abc")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(page(200..250, Value::Null))
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let project = test::fixtures::project();
+
+        let tasks = all_tasks_by_project(&config, &project, None, false)
+            .await
+            .unwrap();
+
+        assert_eq!(tasks.len(), 250);
+        assert_eq!(tasks.last().unwrap().id, "task249");
+        first.assert();
+        second.assert();
+    }
+
+    #[tokio::test]
+    async fn test_batch_update_sends_one_request() {
+        let mut server = mockito::Server::new_async().await;
+        let sync_mock = server
+            .mock("POST", "/api/v1/sync")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(
+                    r#""args":\{"id":"6Xqhv4cwxgjwG9w8","priority":4\}"#.into(),
+                ),
+                mockito::Matcher::Regex(
+                    r#""args":\{"id":"7Yrhw5dxhxkXh0x9","labels":\["errands"\]\}"#.into(),
+                ),
+                mockito::Matcher::Regex(r#""id":"8Zsix6eyiylYi1y0""#.into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            // Every command succeeds except the one for the last task
+            .with_body_from_request(|request| {
+                let body: Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+                let sync_status = body["commands"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|command| {
+                        let uuid = command["uuid"].as_str().unwrap().to_string();
+                        let status = if command["args"]["id"] == "8Zsix6eyiylYi1y0" {
+                            json!({"error_code": 20, "error": "Item not found"})
+                        } else {
+                            json!("ok")
+                        };
+                        (uuid, status)
+                    })
+                    .collect::<serde_json::Map<String, Value>>();
+                json!({ "sync_status": sync_status })
+                    .to_string()
+                    .into_bytes()
+            })
+            .expect(1)
+            .create_async()
+            .await;
+        let fallback_mock = server
+            .mock("POST", "/api/v1/tasks/8Zsix6eyiylYi1y0")
+            .match_body(mockito::Matcher::Json(json!({"priority": 2})))
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+        let single_mocks = server
+            .mock(
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(6Xqhv4cwxgjwG9w8|7Yrhw5dxhxkXh0x9)$".into(),
+                ),
+            )
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let task = test::fixtures::today_task().await;
+        let with_id = |id: &str| Task {
+            id: id.to_string(),
+            ..task.clone()
+        };
+        let updates = vec![
+            TaskUpdate::Priority(with_id("6Xqhv4cwxgjwG9w8"), Priority::High),
+            TaskUpdate::Labels(with_id("7Yrhw5dxhxkXh0x9"), vec![String::from("errands")]),
+            TaskUpdate::Priority(with_id("8Zsix6eyiylYi1y0"), Priority::Low),
+        ];
+
+        assert_eq!(batch_update(&config, updates).await, Ok(()));
+        sync_mock.assert();
+        fallback_mock.assert();
+        single_mocks.assert();
+    }
+
+    #[tokio::test]
+    async fn test_batch_update_falls_back_to_single_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let sync_mock = server
+            .mock("POST", "/api/v1/sync")
+            .with_status(400)
+            .expect(1)
+            .create_async()
+            .await;
+        let single_mocks = server
+            .mock(
+                "POST",
+                mockito::Matcher::Regex(
+                    r"^/api/v1/tasks/(6Xqhv4cwxgjwG9w8|7Yrhw5dxhxkXh0x9)$".into(),
+                ),
+            )
+            .match_body(mockito::Matcher::Json(json!({"priority": 4})))
+            .with_status(200)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let task = test::fixtures::today_task().await;
+        let updates = ["6Xqhv4cwxgjwG9w8", "7Yrhw5dxhxkXh0x9"]
+            .into_iter()
+            .map(|id| {
+                let task = Task {
+                    id: id.to_string(),
+                    ..task.clone()
+                };
+                TaskUpdate::Priority(task, Priority::High)
+            })
+            .collect();
+
+        assert_eq!(batch_update(&config, updates).await, Ok(()));
+        sync_mock.assert();
+        single_mocks.assert();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_filter_names() {
+        let mut server = mockito::Server::new_async().await;
+        let body = json!({
+            "filters": [
+                {"id": "4638878", "name": "Deep work", "query": "@focus & today", "is_deleted": false},
+                {"id": "4638879", "name": "Errands", "query": "#Errands | @errands", "is_deleted": true}
+            ],
+            "sync_token": "abc",
+            "full_sync": true
+        });
+        let mock = server
+            .mock("POST", "/api/v1/sync")
+            .match_body(mockito::Matcher::Json(
+                json!({"sync_token": "*", "resource_types": ["filters"]}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string())
+            .expect(3)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        assert_eq!(
+            resolve_filter_names(&config, "deep work").await,
+            "@focus & today"
+        );
+        // Deleted filters are not matched
+        assert_eq!(resolve_filter_names(&config, "Errands").await, "Errands");
+        assert_eq!(
+            resolve_filter_names(&config, "Deep work, tomorrow").await,
+            "@focus & today,tomorrow"
+        );
+        // Queries are used without looking up saved filters
+        assert_eq!(
+            resolve_filter_names(&config, "today & p1, overdue").await,
+            "today & p1, overdue"
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_filter_names_falls_back_to_query() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/sync")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        assert_eq!(
+            resolve_filter_names(&config, "Deep work").await,
+            "Deep work"
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_truncation_warning_at_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let response =
+            serde_json::from_str::<Value>(&ResponseFromFile::TodayTasks.read().await).unwrap();
+        let page = |ids: std::ops::Range<usize>, next_cursor: Option<&str>| {
+            let results = ids
+                .map(|n| {
+                    let mut task = response["results"][0].clone();
+                    task["id"] = Value::String(format!("task{n}"));
+                    task
+                })
+                .collect::<Vec<Value>>();
+            serde_json::json!({"results": results, "next_cursor": next_cursor}).to_string()
+        };
+
+        let full = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(page(0..200, None))
+            .create_async()
+            .await;
+        // A full last page after following a cursor is not a sign of truncation
+        let first_page = server
+            .mock("GET", "/api/v1/tasks/filter?query=tomorrow&limit=2")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(page(0..2, Some("abc")))
+            .create_async()
+            .await;
+        let last_page = server
+            .mock(
+                "GET",
+                "/api/v1/tasks/filter?query=tomorrow&limit=2&cursor=abc",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(page(2..4, None))
+            .create_async()
+            .await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Error>();
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.internal.tx = Some(tx);
+
+        let (_, tasks) = all_tasks_by_filter(&config, "today", None).await.unwrap();
+        assert_eq!(tasks.len(), 200);
+        let warning = rx.try_recv().unwrap();
+        assert_eq!(warning.kind, ErrorKind::Warning);
+        assert_eq!(
+            warning.message,
+            "Warning: Filter 'today' returned exactly 200 tasks and no cursor, the results may be truncated"
+        );
+
+        let (_, tasks) = all_tasks_by_filter(&config, "tomorrow", Some(2))
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 4);
+        assert!(rx.try_recv().is_err());
+        full.assert();
+        first_page.assert();
+        last_page.assert();
+    }
+
+    #[tokio::test]
+    async fn test_all_tasks_by_project_recurses_into_sub_projects() {
+        let mut server = mockito::Server::new_async().await;
+
+        let parent_mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=123&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+        let child_mock = server
+            .mock("GET", "/api/v1/tasks/?project_id=456&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::UndatedTasks.read().await)
+            .create_async()
+            .await;
+
+        let parent = test::fixtures::project();
+        let child = Project {
+            id: "456".to_string(),
+            name: "child".to_string(),
+            parent_id: Some(parent.id.clone()),
+            ..parent.clone()
+        };
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .with_projects(vec![parent.clone(), child]);
+
+        let tasks = all_tasks_by_project(&config, &parent, None, true)
+            .await
+            .unwrap();
+        let contents: Vec<String> = tasks.into_iter().map(|task| task.content).collect();
+        assert_eq!(contents.len(), 4);
+        assert!(contents.contains(&test::fixtures::today_task().await.content));
+        assert!(contents.contains(&"Walk the dog".to_string()));
+
+        parent_mock.assert();
+        child_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_all_tasks_by_section() {
+        let mut server = mockito::Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/api/v1/tasks/?section_id=1234&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config()
+            .await
+            .with_mock_url(server.url())
+            .with_timezone("US/Pacific");
+        let section = test::fixtures::section();
+
+        assert_eq!(
+            all_tasks_by_section(&config, &section, None).await,
+            Ok(vec![test::fixtures::today_task().await])
+        );
+        assert_eq!(crate::lists::Flag::Section(section).to_string(), "Bread");
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_count_tasks_by_filter() {
+        let mut server = mockito::Server::new_async().await;
+
+        let body = ResponseFromFile::TodayTasks.read().await;
+        let expected = serde_json::from_str::<Value>(&body).unwrap()["results"]
+            .as_array()
+            .unwrap()
+            .len();
+
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        assert_eq!(count_tasks_by_filter(&config, "today").await, Ok(expected));
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_all_tasks_by_filters_keeps_filter_order() {
+        let mut server = mockito::Server::new_async().await;
+        let body = ResponseFromFile::TodayTasks.read().await;
+        let slow_body = body.clone();
+        // The first filter only answers once the other two have, its group should still come first
+        let (answered, waiting) = std::sync::mpsc::channel::<()>();
+        let waiting = std::sync::Mutex::new(waiting);
+        let slow = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_chunked_body(move |w| {
+                let waiting = waiting.lock().unwrap();
+                waiting.recv().unwrap();
+                waiting.recv().unwrap();
+                w.write_all(slow_body.as_bytes())
+            })
+            .create_async()
+            .await;
+        let mut fast_mocks = Vec::new();
+        for query in ["overdue", "tomorrow"] {
+            let fast_body = body.clone();
+            let answered = answered.clone();
+            let mock = server
+                .mock(
+                    "GET",
+                    format!("/api/v1/tasks/filter?query={query}&limit=200").as_str(),
+                )
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_chunked_body(move |w| {
+                    w.write_all(fast_body.as_bytes())?;
+                    w.flush()?;
+                    answered.send(()).unwrap();
+                    Ok(())
+                })
+                .create_async()
+                .await;
+            fast_mocks.push(mock);
+        }
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let groups = all_tasks_by_filters(&config, "today,overdue,tomorrow")
+            .await
+            .unwrap();
+
+        let queries: Vec<&str> = groups.iter().map(|(query, _)| query.as_str()).collect();
+        assert_eq!(queries, vec!["today", "overdue", "tomorrow"]);
+        slow.assert();
+        for mock in fast_mocks {
+            mock.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_times_out() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_chunked_body(|w| {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+                w.write_all(b"{}")
+            })
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.timeout = Some(1);
+
+        let result = request::get_todoist(&config, USER_URL.to_string(), false).await;
+
+        assert_eq!(
+            result,
+            Err(Error::new("request", "Request timed out after 1s").with_kind(ErrorKind::Network))
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_error_kind_from_status() {
+        let mut server = mockito::Server::new_async().await;
+        let unauthorized = server
+            .mock("GET", "/api/v1/user")
+            .with_status(401)
+            .create_async()
+            .await;
+        let rate_limited = server
+            .mock("GET", "/api/v1/projects")
+            .with_status(429)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.max_retries = Some(0);
+
+        let error = request::get_todoist(&config, USER_URL.to_string(), false)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Auth);
+        assert!(!error.is_retryable());
+
+        let error = request::get_todoist(&config, PROJECTS_URL.to_string(), false)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::RateLimited);
+        assert!(error.is_retryable());
+        unauthorized.assert();
+        rate_limited.assert();
+    }
+
+    #[tokio::test]
+    async fn test_all_tasks_by_filter_reuses_cached_tasks() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.filter_cache_ttl = Some(60);
+
+        let (_, first) = all_tasks_by_filter(&config, "today", None).await.unwrap();
+        let (_, second) = all_tasks_by_filter(&config, "today", None).await.unwrap();
+
+        assert_eq!(first, second);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_reload_clears_task_cache() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.filter_cache_ttl = Some(60);
+        let config = config.create().await.unwrap();
+
+        all_tasks_by_filter(&config, "today", None).await.unwrap();
+        all_tasks_by_filter(&config, "today", None).await.unwrap();
+        let config = config.reload().await.unwrap();
+        all_tasks_by_filter(&config, "today", None).await.unwrap();
+
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_writes_clear_task_cache() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/v1/tasks/filter?query=today&limit=200")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTasks.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+        let close_mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.filter_cache_ttl = Some(60);
+
+        all_tasks_by_filter(&config, "today", None).await.unwrap();
+        close_task_by_id(&config, "6Xqhv4cwxgjwG9w8").await.unwrap();
+        all_tasks_by_filter(&config, "today", None).await.unwrap();
+
+        mock.assert();
+        close_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_completed_tasks_by_project() {
+        let mut server = mockito::Server::new_async().await;
+        let body = ResponseFromFile::CompletedTasks.read().await;
+        let expected = serde_json::from_str::<Value>(&body).unwrap()["items"]
+            .as_array()
+            .unwrap()
+            .len();
+
+        let mock = server
+            .mock("GET", "/api/v1/tasks/completed/by_completion_date")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("project_id".into(), "123".into()),
+                mockito::Matcher::UrlEncoded("since".into(), "2025-01-01T08:00:00Z".into()),
+                mockito::Matcher::UrlEncoded("limit".into(), "200".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let since = time::datetime_from_str("2025-01-01T00:00:00", chrono_tz::US::Pacific).unwrap();
+
+        let tasks =
+            completed_tasks_by_project(&config, &test::fixtures::project(), Some(since), None)
+                .await
+                .unwrap();
+
+        assert_eq!(tasks.len(), expected);
+        assert!(tasks.iter().all(|task| task.checked));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_complete_task() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .create_async()
+            .await;
+
         let config = test::fixtures::config().await.with_mock_url(server.url());
 
         let task = test::fixtures::today_task().await;
@@ -1007,6 +2176,151 @@ mod tests {
         assert_eq!(response.project_id, String::from("6VRRxv8CM6GVmmgf"));
     }
 
+    #[tokio::test]
+    async fn test_get_retries_server_errors() {
+        let mut server = mockito::Server::new_async().await;
+
+        let unavailable = server
+            .mock("GET", "/api/v1/tasks/5149481867")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mock = server
+            .mock("GET", "/api/v1/tasks/5149481867")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.retry_delay_ms = Some(1);
+
+        let response = get_task(&config, "5149481867").await.unwrap();
+        unavailable.assert();
+        mock.assert();
+
+        assert_eq!(response.id, String::from("6Xqhv4cwxgjwG9w8"));
+    }
+
+    #[tokio::test]
+    async fn test_close_task_by_id() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/close")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("POST", "/api/v1/tasks/missing/close")
+            .with_status(404)
+            .with_body("Task not found")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        assert_eq!(
+            close_task_by_id(&config, "6Xqhv4cwxgjwG9w8").await,
+            Ok(String::from("✓"))
+        );
+        let error = close_task_by_id(&config, "missing").await.unwrap_err();
+        assert_eq!(error.message, "No task found with id missing");
+        assert_eq!(error.kind, ErrorKind::NotFound);
+        mock.assert();
+        missing.assert();
+    }
+
+    #[tokio::test]
+    async fn test_reopen_task_by_id() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/tasks/6Xqhv4cwxgjwG9w8/reopen")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("POST", "/api/v1/tasks/missing/reopen")
+            .with_status(404)
+            .with_body("Task not found")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+
+        assert_eq!(
+            reopen_task_by_id(&config, "6Xqhv4cwxgjwG9w8").await,
+            Ok(String::from("✓"))
+        );
+        let error = reopen_task_by_id(&config, "missing").await.unwrap_err();
+        assert_eq!(error.message, "No task found with id missing");
+        assert_eq!(error.kind, ErrorKind::NotFound);
+        mock.assert();
+        missing.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_waits_for_retry_after() {
+        let mut server = mockito::Server::new_async().await;
+
+        let rate_limited = server
+            .mock("GET", "/api/v1/tasks/5149481867")
+            .with_status(429)
+            .with_header("Retry-After", "1")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mock = server
+            .mock("GET", "/api/v1/tasks/5149481867")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::TodayTask.read().await)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.retry_delay_ms = Some(1);
+
+        let started = std::time::Instant::now();
+        let response = get_task(&config, "5149481867").await.unwrap();
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        rate_limited.assert();
+        mock.assert();
+
+        assert_eq!(response.id, String::from("6Xqhv4cwxgjwG9w8"));
+    }
+
+    #[tokio::test]
+    async fn test_get_returns_error_after_retries() {
+        let mut server = mockito::Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/api/v1/tasks/5149481867")
+            .with_status(500)
+            .with_body("Internal Server Error")
+            .expect(3)
+            .create_async()
+            .await;
+
+        let mut config = test::fixtures::config().await.with_mock_url(server.url());
+        config.max_retries = Some(2);
+        config.retry_delay_ms = Some(1);
+
+        let error = get_task(&config, "5149481867").await.unwrap_err();
+        mock.assert();
+        assert_eq!(error.source, String::from("reqwest"));
+        assert!(error.message.contains("Internal Server Error"));
+    }
+
     #[tokio::test]
     async fn test_forbidden() {
         let mut server = mockito::Server::new_async().await;
@@ -1075,6 +2389,42 @@ mod tests {
         assert_eq!(response, Ok(String::from("✓")));
     }
 
+    #[tokio::test]
+    async fn test_update_task_due_with_duration_in_days() {
+        let task = test::fixtures::today_task().await;
+        let url: &str = &format!("{}{}", "/api/v1/tasks/", task.id);
+        let mut server = mockito::Server::new_async().await;
+
+        let mock = server
+            .mock("POST", url)
+            .match_body(mockito::Matcher::Json(json!({
+                "due_string": "today",
+                "duration": 2,
+                "duration_unit": "day"
+            })))
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let duration = tasks::Duration {
+            amount: 2,
+            unit: tasks::Unit::Day,
+        };
+
+        let response = update_task_due_natural_language(
+            &config,
+            &task,
+            "today".to_string(),
+            Some(duration),
+            true,
+        )
+        .await;
+        mock.assert();
+        assert_eq!(response, Ok(String::from("✓")));
+    }
+
     #[tokio::test]
     async fn test_all_comments_filters_deleted() {
         let mut server = mockito::Server::new_async().await;
@@ -1094,13 +2444,81 @@ mod tests {
 
         let task = test::fixtures::today_task().await;
 
-        let comments = all_comments(&config, &task, None).await.unwrap();
+        let comments = all_comments(&config, &task, None, None).await.unwrap();
         mock.assert();
 
         assert_eq!(comments.len(), 7); // One comment in the JSON is_deleted = true
         assert!(comments.iter().all(|c| !c.is_deleted));
     }
     #[tokio::test]
+    async fn test_all_comments_by_author() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=200",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(ResponseFromFile::CommentsMultipleAuthors.read().await)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let task = test::fixtures::today_task().await;
+
+        let mine = all_comments(&config, &task, None, Some("910".into()))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = mine.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["comment-1", "comment-3"]);
+
+        let teammate = all_comments(&config, &task, None, Some("2671355".into()))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = teammate.iter().map(|c| c.id.as_str()).collect();
+        // The teammate's deleted comment is still left out
+        assert_eq!(ids, vec!["comment-2"]);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_all_comments_follows_next_cursor() {
+        let mut server = mockito::Server::new_async().await;
+        let last_page = ResponseFromFile::CommentsAllTypes.read().await;
+        let mut first_page = serde_json::from_str::<Value>(&last_page).unwrap();
+        first_page["next_cursor"] = Value::String("abc".into());
+
+        let first = server
+            .mock("GET", "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=8")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(first_page.to_string())
+            .create_async()
+            .await;
+        let second = server
+            .mock(
+                "GET",
+                "/api/v1/comments/?task_id=6Xqhv4cwxgjwG9w8&limit=8&cursor=abc",
+            )
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(last_page)
+            .create_async()
+            .await;
+
+        let config = test::fixtures::config().await.with_mock_url(server.url());
+        let task = test::fixtures::today_task().await;
+
+        let comments = all_comments(&config, &task, Some(8), None).await.unwrap();
+
+        // Each page has seven comments that are not deleted
+        assert_eq!(comments.len(), 14);
+        first.assert();
+        second.assert();
+    }
+    #[tokio::test]
     async fn test_task_is_filtered_out_by_regex() {
         let mut task = test::fixtures::today_task().await;
         task.content = "Brush Teeth".to_string();
@@ -1,10 +1,11 @@
 use std::env;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use reqwest::Client;
 use reqwest::Response;
 use reqwest::header::AUTHORIZATION;
 use reqwest::header::CONTENT_TYPE;
+use reqwest::header::RETRY_AFTER;
 use serde_json::Value;
 use serde_json::json;
 use spinners::Spinner;
@@ -16,6 +17,7 @@ use crate::config::Args;
 use crate::config::Config;
 use crate::debug;
 use crate::errors::Error;
+use crate::errors::ErrorKind;
 
 const FAKE_UUID: &str = "42963283-2bab-4b1f-bad2-278ef2b6ba2c";
 const TODOIST_URL: &str = "https://api.todoist.com";
@@ -24,6 +26,11 @@ const SPINNER: Spinners = Spinners::Dots4;
 const MESSAGE: &str = "Querying API";
 const HTTP_UNAUTHORIZED: u16 = 401;
 const HTTP_FORBIDDEN: u16 = 403;
+const HTTP_TOO_MANY_REQUESTS: u16 = 429;
+/// Longest we are willing to wait when the server asks us to back off
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// Longest wait between retries when the delay doubles with each attempt
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
 
 /// Post to Todoist via REST api
 /// We use this when we want more options and don't need natural language processing
@@ -33,6 +40,18 @@ pub async fn post_todoist(
     url: String,
     body: serde_json::Value,
     spinner: bool,
+) -> Result<String, Error> {
+    post_todoist_with_request_id(config, url, body, spinner, new_uuid()).await
+}
+
+/// Post with a caller chosen X-Request-Id. Todoist ignores a repeated request with the same id,
+/// so retrying with the same id does not create the same task twice.
+pub async fn post_todoist_with_request_id(
+    config: &Config,
+    url: String,
+    body: serde_json::Value,
+    spinner: bool,
+    request_id: String,
 ) -> Result<String, Error> {
     let base_url = get_base_url(config);
     let token = get_token(config)?;
@@ -43,20 +62,24 @@ pub async fn post_todoist(
 
     debug::maybe_print(config, format!("POST {request_url}\nbody: {body}"));
 
+    let started = Instant::now();
     let client = Client::new()
         .post(request_url.clone())
         .header(CONTENT_TYPE, "application/json")
         .header(AUTHORIZATION, authorization)
-        .header("X-Request-Id", new_uuid())
+        .header("X-Request-Id", request_id)
         .timeout(get_timeout(config));
 
     let response = match &body {
-        Value::Null => client.send().await?,
+        Value::Null => client.send().await,
 
-        body => client.json(&body).send().await?,
-    };
+        body => client.json(&body).send().await,
+    }
+    .map_err(|e| request_error(config, e))?;
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "POST", url, body).await
+    // A write can change which tasks a filter returns
+    config.internal.task_cache.clear();
+    handle_response(config, response, "POST", url, started, body).await
 }
 
 pub async fn post_todoist_no_token(
@@ -71,6 +94,7 @@ pub async fn post_todoist_no_token(
 
     debug::maybe_print(config, format!("POST {request_url}\nbody: {body}"));
 
+    let started = Instant::now();
     let client = Client::new()
         .post(request_url.clone())
         .header(CONTENT_TYPE, "application/json")
@@ -78,12 +102,13 @@ pub async fn post_todoist_no_token(
         .timeout(get_timeout(config));
 
     let response = match &body {
-        Value::Null => client.send().await?,
+        Value::Null => client.send().await,
 
-        body => client.json(&body).send().await?,
-    };
+        body => client.json(&body).send().await,
+    }
+    .map_err(|e| request_error(config, e))?;
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "POST", url, body).await
+    handle_response(config, response, "POST", url, started, body).await
 }
 
 fn get_token(config: &Config) -> Result<String, Error> {
@@ -108,6 +133,7 @@ pub async fn delete_todoist(
 
     debug::maybe_print(config, format!("DELETE {request_url}\nbody: {body}"));
 
+    let started = Instant::now();
     let response = Client::new()
         .delete(request_url.clone())
         .header(CONTENT_TYPE, "application/json")
@@ -116,10 +142,12 @@ pub async fn delete_todoist(
         .json(&body)
         .timeout(get_timeout(config))
         .send()
-        .await?;
+        .await
+        .map_err(|e| request_error(config, e))?;
 
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "DELETE", url, body).await
+    config.internal.task_cache.clear();
+    handle_response(config, response, "DELETE", url, started, body).await
 }
 
 // Combine get and post into one function
@@ -131,20 +159,48 @@ pub async fn get_todoist(config: &Config, url: String, spinner: bool) -> Result<
     let request_url = format!("{base_url}{url}");
     let authorization = format!("Bearer {token}");
     let spinner = maybe_start_spinner(config, spinner);
-    if config.verbose.unwrap_or_default() {
-        println!("GET {request_url}")
-    }
     debug::maybe_print(config, format!("GET {request_url}"));
-    let response = Client::new()
-        .get(request_url.clone())
-        .header(CONTENT_TYPE, "application/json")
-        .header(AUTHORIZATION, authorization)
-        .timeout(get_timeout(config))
-        .send()
-        .await?;
+
+    // GET requests are idempotent, so transient failures can be retried with exponential backoff
+    let max_retries = config.max_retries() as u32;
+    let mut attempt: u32 = 0;
+    let started = Instant::now();
+    let response = loop {
+        let result = Client::new()
+            .get(request_url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .header(AUTHORIZATION, authorization.clone())
+            .timeout(get_timeout(config))
+            .send()
+            .await;
+
+        let retryable = match &result {
+            Ok(response) => is_retryable_status(response.status().as_u16()),
+            Err(error) => error.is_connect(),
+        };
+
+        if !retryable || attempt >= max_retries {
+            break result.map_err(|e| request_error(config, e))?;
+        }
+
+        let delay = result
+            .as_ref()
+            .ok()
+            .and_then(retry_after)
+            .unwrap_or_else(|| backoff(config, attempt));
+        attempt += 1;
+        debug::maybe_print(
+            config,
+            format!(
+                "Retrying GET {request_url} in {}ms, attempt {attempt} of {max_retries}",
+                delay.as_millis()
+            ),
+        );
+        tokio::time::sleep(delay).await;
+    };
 
     maybe_stop_spinner(spinner);
-    handle_response(config, response, "GET", url, json!({})).await
+    handle_response(config, response, "GET", url, started, json!({})).await
 }
 
 async fn handle_response(
@@ -152,12 +208,17 @@ async fn handle_response(
     response: Response,
     method: &str,
     url: String,
+    started: Instant,
     body: serde_json::Value,
 ) -> Result<String, Error> {
     let status = response.status();
     let status_code = status.as_u16();
+    maybe_log_request(config, method, &url, status_code, started);
     if status.is_success() {
-        let json_string = response.text().await?;
+        let json_string = response
+            .text()
+            .await
+            .map_err(|e| request_error(config, e))?;
         debug::maybe_print(config, format!("{method} {url}\nresponse: {json_string}"));
         Ok(json_string)
     } else if status_code == HTTP_UNAUTHORIZED || status_code == HTTP_FORBIDDEN {
@@ -167,9 +228,13 @@ async fn handle_response(
             &format!(
                 "Unauthorized or Forbidden response from Todoist\nRun {command} to reauthenticate"
             ),
-        ))
+        )
+        .with_kind(ErrorKind::Auth))
     } else {
-        let json_string = response.text().await?;
+        let json_string = response
+            .text()
+            .await
+            .map_err(|e| request_error(config, e))?;
         Err(Error::new(
             "reqwest",
             &format!(
@@ -179,7 +244,63 @@ async fn handle_response(
             body: {body}
             response: {json_string}",
             ),
-        ))
+        )
+        .with_kind(ErrorKind::from_status(status_code)))
+    }
+}
+
+/// In verbose mode print a one line summary of each call to stderr, with the token hidden
+fn maybe_log_request(config: &Config, method: &str, url: &str, status_code: u16, started: Instant) {
+    if config.verbose.unwrap_or_default() || config.args.verbose {
+        let base_url = get_base_url(config);
+        let elapsed = started.elapsed().as_millis();
+        let line = format!("{method} {base_url}{url} {status_code} {elapsed}ms");
+        eprintln!("{}", redact_token(config, &line));
+    }
+}
+
+/// Replaces the token with *** wherever it appears in text
+fn redact_token(config: &Config, text: &str) -> String {
+    match &config.token {
+        Some(token) if !token.is_empty() => text.replace(token.as_str(), "***"),
+        _ => text.to_string(),
+    }
+}
+
+/// Rate limits and server errors are worth trying again
+fn is_retryable_status(status_code: u16) -> bool {
+    status_code == HTTP_TOO_MANY_REQUESTS || (500..600).contains(&status_code)
+}
+
+/// The wait requested by a Retry-After header in seconds, capped at MAX_RETRY_AFTER
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+/// The retry delay doubled once for each earlier attempt, capped at MAX_BACKOFF
+fn backoff(config: &Config, attempt: u32) -> Duration {
+    config
+        .retry_delay()
+        .saturating_mul(2_u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF)
+}
+
+/// Timeouts get a clear message instead of reqwest's generic send error
+fn request_error(config: &Config, error: reqwest::Error) -> Error {
+    if error.is_timeout() {
+        let seconds = get_timeout(config).as_secs();
+        Error::new("request", &format!("Request timed out after {seconds}s"))
+            .with_kind(ErrorKind::Network)
+    } else {
+        error.into()
     }
 }
 
@@ -253,3 +374,33 @@ pub fn new_uuid() -> String {
         Uuid::new_v4().to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    #[tokio::test]
+    async fn test_redact_token() {
+        let config = test::fixtures::config().await.with_token("supersecret");
+
+        assert_eq!(
+            redact_token(&config, "GET /api/v1/tasks?token=supersecret 200 12ms"),
+            "GET /api/v1/tasks?token=*** 200 12ms"
+        );
+        assert_eq!(
+            redact_token(&config, "GET /api/v1/tasks"),
+            "GET /api/v1/tasks"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backoff_is_capped() {
+        let config = test::fixtures::config().await;
+
+        assert_eq!(backoff(&config, 0), config.retry_delay());
+        assert_eq!(backoff(&config, 2), config.retry_delay() * 4);
+        assert_eq!(backoff(&config, 20), MAX_BACKOFF);
+        assert_eq!(backoff(&config, 40), MAX_BACKOFF);
+    }
+}
@@ -3,6 +3,7 @@ use serde::Deserialize;
 
 #[derive(Deserialize, Debug, PartialEq, Eq)]
 pub struct User {
+    pub id: String,
     pub tz_info: TzInfo,
 }
 // This file is used to pull the user information (timezone) from the Todoist API
@@ -11,9 +12,35 @@ pub struct TzInfo {
     pub timezone: String,
 }
 
+/// A person a project is shared with, who tasks in that project can be assigned to
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Collaborator {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub struct CollaboratorResponse {
+    pub results: Vec<Collaborator>,
+    pub next_cursor: Option<String>,
+}
+
+impl Collaborator {
+    /// Matches on name or email, ignoring case
+    pub fn matches(&self, assignee: &str) -> bool {
+        self.name.eq_ignore_ascii_case(assignee) || self.email.eq_ignore_ascii_case(assignee)
+    }
+}
+
 pub fn json_to_user(json: String) -> Result<User, Error> {
     // Deserializes JSON string into a `User` struct using Serde.
     // Returns an error if the JSON string does not match the `User` struct format.
     let user: User = serde_json::from_str(&json)?;
     Ok(user)
 }
+
+pub fn json_to_collaborators_response(json: String) -> Result<CollaboratorResponse, Error> {
+    let response: CollaboratorResponse = serde_json::from_str(&json)?;
+    Ok(response)
+}